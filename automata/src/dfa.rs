@@ -3,6 +3,8 @@ use crate::nfa::{self, NFA};
 use crate::table::Table;
 
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::error;
+use std::fmt;
 use std::hash::Hash;
 use std::iter::Peekable;
 use std::rc::Rc;
@@ -42,6 +44,17 @@ where
     pub nfa_mapping: HashMap<usize, HashSet<usize>>,
 }
 
+/// A DFA constructed from several NFAs at once, recognizing the union of their languages.
+#[derive(Debug, Clone)]
+pub struct DFAFromNFAs<T>
+where
+    T: Clone + Eq + Hash,
+{
+    pub dfa: DFA<T>,
+    /// The indices of the NFAs that accept in each final state of the DFA, in ascending order.
+    pub accepting: HashMap<usize, Vec<usize>>,
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Transition<T>(pub T)
 where
@@ -101,6 +114,20 @@ where
     pub fn is_final_state(&self, state: &usize) -> bool {
         self.final_states.iter().any(|s| s == state)
     }
+
+    /// Return the state reached by the transition from the given state on the given symbol, if
+    /// there is one.
+    #[inline]
+    pub fn next_state<S>(&self, state: usize, symbol: &S) -> Option<usize>
+    where
+        T: PartialEq<S>,
+    {
+        self.transition
+            .get_row(&state)
+            .into_iter()
+            .find(|(Transition(t), _)| *t == *symbol)
+            .map(|(_, &s)| s)
+    }
 }
 
 struct MatchRc<T> {
@@ -223,7 +250,7 @@ where
                 span.push(is_rc);
 
                 if self.is_final_state(&state) {
-                    last_match = Some(MatchRc::new(start, i + 1, span.clone()));
+                    last_match = Some(MatchRc::new(start, start + i + 1, span.clone()));
                     if shortest {
                         break;
                     }
@@ -362,6 +389,19 @@ where
     }
 }
 
+impl<T> DFA<T>
+where
+    T: Clone + Disjoin + Eq + Hash,
+{
+    /// Create an equivalent DFA from an NFA, failing if it would have more than the given number
+    /// of states. The subset construction may produce exponentially many states, so this bounds
+    /// the time and memory taken by untrusted NFAs.
+    #[inline]
+    pub fn try_from_nfa(nfa: NFA<T>, state_limit: usize) -> Result<Self, StateLimitExceeded> {
+        DFAFromNFA::try_from_nfa(nfa, state_limit).map(|dfa_from_nfa| dfa_from_nfa.dfa)
+    }
+}
+
 impl<T> From<DFAFromNFA<T>> for DFA<T>
 where
     T: Clone + Disjoin + Eq + Hash,
@@ -376,11 +416,29 @@ impl<T> From<NFA<T>> for DFAFromNFA<T>
 where
     T: Clone + Disjoin + Eq + Hash,
 {
-    // Create an equivalent DFA from an NFA using the subset construction described by Algorithm
-    // 3.20. The construction is slightly modified, with inspiration from [this Stack Overflow
-    //   answer](https://stackoverflow.com/a/25832898/8955108) to accomodate character ranges.
     #[inline]
     fn from(nfa: NFA<T>) -> Self {
+        match Self::try_from_nfa(nfa, usize::MAX) {
+            Ok(dfa_from_nfa) => dfa_from_nfa,
+            Err(StateLimitExceeded) => unreachable!(),
+        }
+    }
+}
+
+impl<T> DFAFromNFA<T>
+where
+    T: Clone + Disjoin + Eq + Hash,
+{
+    /// Create an equivalent DFA from an NFA using the subset construction described by Algorithm
+    /// 3.20, failing if it would have more than the given number of states.
+    #[inline]
+    pub fn try_from_nfa(nfa: NFA<T>, state_limit: usize) -> Result<Self, StateLimitExceeded> {
+        // The construction is slightly modified, with inspiration from [this Stack Overflow
+        //   answer](https://stackoverflow.com/a/25832898/8955108) to accomodate character ranges.
+        if state_limit == 0 {
+            return Err(StateLimitExceeded);
+        }
+
         let mut dfa = DFA::new();
         let mut nfa_mapping = HashMap::new();
 
@@ -450,6 +508,10 @@ where
                     new_state.label = existing.label;
                     dfa.add_transition(s.label, new_state.label, Transition(t));
                 } else {
+                    if dfa.total_states >= state_limit {
+                        return Err(StateLimitExceeded);
+                    }
+
                     // If not found, set a new label and push to unmarked.
                     new_state.label = dfa.add_state(false);
 
@@ -469,6 +531,66 @@ where
             marked_states.push(s);
         }
 
-        DFAFromNFA { dfa, nfa_mapping }
+        Ok(DFAFromNFA { dfa, nfa_mapping })
+    }
+}
+
+/// Error returned when the construction of a DFA would exceed its state limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StateLimitExceeded;
+
+impl fmt::Display for StateLimitExceeded {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        write!(f, "DFA state limit exceeded")
+    }
+}
+
+impl error::Error for StateLimitExceeded {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+impl<T> DFAFromNFAs<T>
+where
+    T: Clone + Disjoin + Eq + Hash,
+{
+    /// Combine the NFAs into one, as in [NFA::combine], and convert the result into a DFA,
+    /// recording which of the NFAs accept in each DFA state.
+    #[inline]
+    pub fn new(nfas: &[&NFA<T>]) -> Self {
+        let combined = NFA::combine(nfas);
+
+        // Map the final states of the combined NFA to the index of the NFA they came from.
+        let mut final_mapping = HashMap::new();
+        let mut offset = NFA::<T>::new().total_states;
+        for (i, nfa) in nfas.iter().enumerate() {
+            for nfa_final in nfa.final_states.iter() {
+                final_mapping.insert(nfa_final + offset, i);
+            }
+            offset += nfa.total_states;
+        }
+
+        let DFAFromNFA { dfa, nfa_mapping } = combined.into();
+        let accepting = nfa_mapping
+            .into_iter()
+            .filter_map(|(dfa_state, nfa_states)| {
+                let mut indices: Vec<_> = nfa_states
+                    .iter()
+                    .filter_map(|s| final_mapping.get(s).copied())
+                    .collect();
+                if indices.is_empty() {
+                    return None;
+                }
+
+                indices.sort_unstable();
+                indices.dedup();
+                Some((dfa_state, indices))
+            })
+            .collect();
+
+        Self { dfa, accepting }
     }
 }
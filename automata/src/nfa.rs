@@ -117,7 +117,7 @@ where
         let mut new_nfa = c1.clone();
 
         let offset = new_nfa.total_states;
-        NFA::copy_into(&mut new_nfa, &c2);
+        NFA::copy_into(&mut new_nfa, c2);
 
         // Epsilon transitions from c1 finals to initial of c2
         for c1_final in c1.final_states.iter() {
@@ -139,7 +139,7 @@ where
         let mut new_nfa = NFA::new_epsilon();
         let offset = new_nfa.total_states;
 
-        NFA::copy_into(&mut new_nfa, &c1);
+        NFA::copy_into(&mut new_nfa, c1);
         new_nfa.add_epsilon_transition(new_nfa.initial_state, c1.initial_state + offset);
 
         for c1_final in c1.final_states.iter() {
@@ -374,26 +374,29 @@ where
             }
         }
 
-        let mut last_match = if self.is_final_state(&self.initial_state) {
+        let mut state_set = self.epsilon_closure(self.initial_state);
+        let mut last_match = if state_set.iter().any(|s| self.is_final_state(s)) {
             Some(MatchRc::new(start, start, vec![]))
         } else {
             None
         };
 
         if !(shortest && last_match.is_some()) {
-            let mut state_set = self.epsilon_closure(self.initial_state);
-
             let input = input.into_iter().skip(start);
             let mut span = Vec::new();
             for (i, is) in input.enumerate() {
                 let moved_set = self.move_set(&state_set, &is);
                 state_set = self.epsilon_closure_set(&moved_set);
+                // No states left to move from: no further match.
+                if state_set.is_empty() {
+                    break;
+                }
 
                 let is_rc = Rc::new(is);
                 span.push(is_rc);
 
                 if state_set.iter().any(|s| self.is_final_state(s)) {
-                    last_match = Some(MatchRc::new(start, i + 1, span.clone()));
+                    last_match = Some(MatchRc::new(start, start + i + 1, span.clone()));
                     if shortest {
                         break;
                     }
@@ -1,22 +1,21 @@
 #![deny(rust_2018_idioms)]
 #![deny(future_incompatible)]
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use llex_rules::{Rule as ParsedRule, RuleError, RulesDFA};
 use proc_macro2::{Span, TokenStream};
 use quote::{format_ident, quote};
 use regexp2::{
-    automata::{
-        dfa::{DFAFromNFA, Transition},
-        DFA, NFA,
-    },
+    automata::{dfa::Transition, DFA},
     class::{CharClass, CharRange},
-    parser::{NFAParser, Parser},
+    parser::ParseError,
 };
 use syn::{
-    parenthesized,
+    braced, parenthesized,
     parse::{Parse, ParseStream},
-    parse_macro_input, token, Expr, Ident, LitStr, Token, Type, Visibility,
+    parse_macro_input, token, Expr, Ident, Lifetime, LitStr, Token, Type, TypeReference,
+    Visibility,
 };
 
 #[proc_macro]
@@ -34,114 +33,326 @@ fn lexer_(parsed: Lexer) -> Result<TokenStream, TokenStream> {
         struct_name,
         fn_vis,
         fn_name,
+        source,
+        modes,
         span_id,
+        modes_id,
+        state,
         return_type,
         error_variant,
+        merge_errors,
         rules,
     } = parsed;
 
-    let (nfa, action_mapping) = parse_combined_nfa(&rules)?;
-    let DFAFromNFA { dfa, nfa_mapping }: DFAFromNFA<_> = nfa.into();
+    // Lexers without start conditions have a single mode, `()`.
+    let (mode_type, mode_names, mode_enum) = match &modes {
+        Some(Modes {
+            vis,
+            name,
+            variants,
+        }) => {
+            let first = &variants[0];
+            let mode_enum = quote! {
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                #vis enum #name {
+                    #( #variants ),*
+                }
 
-    let dfa_rebuilt = dfa_rebuilt(&dfa);
+                impl std::default::Default for #name {
+                    #[inline]
+                    fn default() -> Self {
+                        Self::#first
+                    }
+                }
+            };
+            (quote!(#name), variants.clone(), mode_enum)
+        }
+        None => (quote!(()), vec![format_ident!("Initial")], quote!()),
+    };
+    let modes_id = modes_id.unwrap_or_else(|| format_ident!("modes"));
+    let (state_id, state_type) = match state {
+        Some((id, ty)) => (id, quote!(#ty)),
+        None => (format_ident!("state"), quote!(())),
+    };
+
+    // Lexers over a source borrow the text of matches from it, with the lifetime of the source.
+    let (text_type, text_generics) = match &source {
+        Some(Source { lifetime, ty }) => (quote!(#ty), quote!(<#lifetime>)),
+        None => (quote!(&str), quote!()),
+    };
+
+    let mut initial_states = Vec::new();
+    let mut transition_arms = Vec::new();
+    let mut final_arms = Vec::new();
+    let mut action_fns = Vec::new();
+    let mut action_match = Vec::new();
+    let mut trailing_initial_states = Vec::new();
+    let mut trailing_match = Vec::new();
+    let grouped = mode_rules(&rules, &mode_names, &modes)?;
+    let mut shadowed = Vec::new();
+    for (mode_index, mode_rules) in grouped.iter().enumerate() {
+        let parsed = parse_rules(mode_rules)?;
+        let rules_dfa = RulesDFA::new(&parsed);
+        shadowed.push(rules_dfa.shadowed(mode_rules.len()));
+        let RulesDFA { dfa, accepting } = rules_dfa;
+
+        initial_states.push(dfa.initial_state);
+        let (transitions, finals) = dfa_coded(mode_index, &dfa);
+        transition_arms.extend(transitions);
+        final_arms.extend(finals);
+
+        // The DFAs of the parts of rules with trailing context are coded after those of the
+        // modes, by the index of the rule in the mode.
+        let mut trailing_dfas = HashMap::new();
+        for (i, rule) in parsed.iter().enumerate() {
+            if let Some(dfas) = &rule.trailing {
+                let index = mode_names.len() + trailing_initial_states.len();
+                for (j, dfa) in [&dfas.0, &dfas.1].iter().enumerate() {
+                    trailing_initial_states.push(dfa.initial_state);
+                    let (transitions, finals) = dfa_coded(index + j, dfa);
+                    transition_arms.extend(transitions);
+                    final_arms.extend(finals);
+                }
+                trailing_dfas.insert(i, (index, index + 1));
+            }
+        }
 
-    let dfa_actions: Vec<_> = nfa_mapping
-        .iter()
-        .filter_map(|(dfa_state, nfa_states)| {
-            action_mapping
-                .iter()
-                .filter(|(nfa_state, _)| nfa_states.contains(nfa_state))
-                .min_by_key(|(_, (_, precedence))| precedence)
-                .map(|(_, (action, _))| (dfa_state, action))
-        })
-        .collect();
+        // The action of each final DFA state is that of the earliest rule accepted in it.
+        for (dfa_state, indices) in accepting.iter() {
+            let action = &mode_rules[indices[0]].action;
+            if let Some((r, s)) = trailing_dfas.get(&indices[0]) {
+                trailing_match.push(quote! {
+                    (#mode_index, #dfa_state) => input.trailing_context(&CodedDFA(#r), &CodedDFA(#s), len)
+                });
+            }
 
-    let action_fns: Vec<_> = dfa_actions
-        .iter()
-        .map(|(dfa_state, action)| {
-            let fn_name = format_ident!("action_{}", dfa_state);
-            quote! {
+            let fn_name = format_ident!("action_{}_{}", mode_index, dfa_state);
+            action_fns.push(quote! {
                 #[allow(unused)]
                 #[allow(clippy::unnecessary_wraps)]
                 #[inline]
-                fn #fn_name(#span_id: &str) -> std::option::Option<#return_type> {
+                fn #fn_name #text_generics(#span_id: #text_type, #modes_id: &mut ::llex::ModeStack<#mode_type>, #state_id: &mut #state_type) -> std::option::Option<#return_type> {
                     #action
                 }
-            }
-        })
-        .collect();
-
-    let action_match: Vec<_> = dfa_actions
-        .iter()
-        .map(|(dfa_state, _)| {
-            let fn_call = format_ident!("action_{}", dfa_state);
-            quote!(#dfa_state => #fn_call(&span))
-        })
-        .collect();
+            });
+            action_match.push(quote!((#mode_index, #dfa_state) => #fn_name(span, modes, state)));
+        }
+    }
 
-    Ok(quote! {
-        #[derive(Debug, Clone)]
-        #struct_vis struct #struct_name {
-            dfa: ::llex::stream::LexerDFA,
+    // Lexers without start conditions have no mode names to report.
+    let declared_names = modes.as_ref().map(|_| mode_names.as_slice());
+    check_shadowed(&rules, &grouped, &shadowed, declared_names)?;
+
+    let mode_index = match &modes {
+        Some(_) => quote!(modes.current() as usize),
+        None => quote!(0),
+    };
+    initial_states.extend(trailing_initial_states);
+
+    // A match of a rule with trailing context is backed up to the end of its leading part.
+    let back_up = if trailing_match.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            let len = match (mode_index, final_state) {
+                #( #trailing_match, )*
+                _ => len,
+            };
         }
+    };
 
-        impl #struct_name {
+    // The DFAs of the modes, followed by those of the parts of rules with trailing context, are
+    // coded as one automaton indexed by the DFA.
+    let coded_dfa = quote! {
+        struct CodedDFA(usize);
+
+        impl ::llex::Automaton for CodedDFA {
             #[inline]
-            #struct_vis fn new() -> Self {
-                let dfa = #dfa_rebuilt;
-                Self { dfa }
+            fn initial_state(&self) -> usize {
+                [ #( #initial_states ),* ][self.0]
             }
 
             #[inline]
-            #fn_vis fn #fn_name<'a, I>(&self, input: I) -> ::llex::LexerStream<#return_type, &#struct_name, I>
-            where
-                I: std::iter::Iterator<Item = char>,
-            {
-                ::llex::LexerStream::new(self, input)
+            fn next_state(&self, state: usize, c: char) -> std::option::Option<usize> {
+                let transitions: &[(char, char, usize)] = match (self.0, state) {
+                    #( #transition_arms, )*
+                    _ => &[],
+                };
+                ::llex::automaton::next_in_ranges(transitions, c)
             }
-        }
 
-        impl ::llex::stream::LexerDFAMatcher<#return_type> for #struct_name {
             #[inline]
-            fn tokenize<'a, I>(&self, input: &mut std::iter::Peekable<I>) -> std::option::Option<(#return_type, ::llex::regexp2::automata::Match<char>)>
-            where
-                I: std::iter::Iterator<Item = char>,
-            {
-                #(
-                    #action_fns
-                )*
-
-                // Step through DFA to the find the longest match.
-                let (m, final_state) = match self.dfa.find_mut(input) {
-                    std::option::Option::Some(m) => m,
-                    std::option::Option::None => {
-                        input.next();
-                        return std::option::Option::Some((#error_variant, ::llex::regexp2::automata::Match::new(0, 0, vec![])));
-                    },
-                };
+            fn is_final_state(&self, state: usize) -> bool {
+                match (self.0, state) {
+                    #( #final_arms, )*
+                    _ => false,
+                }
+            }
+        }
+    };
 
-                // Execute the action expression corresponding to the final state.
-                let span: std::string::String = m.span.iter().cloned().collect();
-                let token_op = match final_state {
-                    #( #action_match ),*,
-                    // Catch-all branch should never execute?
-                    _ => std::unreachable!(),
-                };
+    let stream_fn;
+    let matcher_impls;
+    match &source {
+        Some(Source { lifetime, ty }) => {
+            stream_fn = quote! {
+                #[inline]
+                #fn_vis fn #fn_name<#lifetime>(&self, input: #ty) -> ::llex::LexerSourceStream<#lifetime, #ty, #return_type, &#struct_name> {
+                    ::llex::LexerSourceStream::new(self, input)
+                }
+            };
 
-                token_op.map(|t| (t, m))
-            }
+            matcher_impls = quote! {
+                impl<#lifetime> ::llex::stream::LexerSourceMatcher<#lifetime, #ty, #return_type> for #struct_name {
+                    type Mode = #mode_type;
+                    type State = #state_type;
+
+                    #[inline]
+                    fn tokenize(&self, input: &mut ::llex::SourceInput<#ty>, modes: &mut ::llex::ModeStack<#mode_type>, state: &mut #state_type) -> (std::option::Option<#return_type>, usize)
+                    {
+                        #(
+                            #action_fns
+                        )*
+
+                        #[allow(unused)]
+                        #[inline]
+                        fn error_token<#lifetime>(#span_id: #ty, #state_id: &mut #state_type) -> #return_type {
+                            #error_variant
+                        }
+
+                        #coded_dfa
+
+                        // Step through the DFA of the current mode to the find the longest match.
+                        let mode_index: usize = #mode_index;
+                        let dfa = &CodedDFA(mode_index);
+                        let (len, final_state) = match input.longest_match(dfa) {
+                            std::option::Option::Some(m) => m,
+                            std::option::Option::None => {
+                                let span = input.unmatched(dfa, #merge_errors);
+                                return (std::option::Option::Some(error_token(span, state)), span.len());
+                            },
+                        };
+
+                        #back_up
+
+                        // Execute the action expression corresponding to the final state.
+                        let span = input.consume(len);
+                        let token_op = match (mode_index, final_state) {
+                            #( #action_match ),*,
+                            // Catch-all branch should never execute?
+                            _ => std::unreachable!(),
+                        };
+
+                        (token_op, len)
+                    }
+                }
+
+                impl<#lifetime> ::llex::stream::LexerSourceMatcher<#lifetime, #ty, #return_type> for &#struct_name {
+                    type Mode = #mode_type;
+                    type State = #state_type;
+
+                    #[inline]
+                    fn tokenize(&self, input: &mut ::llex::SourceInput<#ty>, modes: &mut ::llex::ModeStack<#mode_type>, state: &mut #state_type) -> (std::option::Option<#return_type>, usize)
+                    {
+                        (*self).tokenize(input, modes, state)
+                    }
+                }
+            };
+        }
+        None => {
+            stream_fn = quote! {
+                #[inline]
+                #fn_vis fn #fn_name<'a, I>(&self, input: I) -> ::llex::LexerStream<#return_type, &#struct_name, I>
+                where
+                    I: std::iter::Iterator<Item = char>,
+                {
+                    ::llex::LexerStream::new(self, input)
+                }
+            };
+
+            matcher_impls = quote! {
+                impl ::llex::stream::LexerDFAMatcher<#return_type> for #struct_name {
+                    type Mode = #mode_type;
+                    type State = #state_type;
+
+                    #[inline]
+                    fn tokenize<'a, I>(&self, input: &mut ::llex::LexerInput<I>, modes: &mut ::llex::ModeStack<#mode_type>, state: &mut #state_type) -> (std::option::Option<#return_type>, ::llex::regexp2::automata::Match<char>)
+                    where
+                        I: std::iter::Iterator<Item = char>,
+                    {
+                        #(
+                            #action_fns
+                        )*
+
+                        #[allow(unused)]
+                        #[inline]
+                        fn error_token(#span_id: &str, #state_id: &mut #state_type) -> #return_type {
+                            #error_variant
+                        }
+
+                        #coded_dfa
+
+                        // Step through the DFA of the current mode to the find the longest match.
+                        let mode_index: usize = #mode_index;
+                        let dfa = &CodedDFA(mode_index);
+                        let (len, final_state) = match input.longest_match(dfa) {
+                            std::option::Option::Some(m) => m,
+                            std::option::Option::None => {
+                                let unmatched = input.unmatched(dfa, #merge_errors);
+                                let text: std::string::String = unmatched.iter().collect();
+                                let m = ::llex::regexp2::automata::Match::new(0, unmatched.len(), unmatched);
+                                return (std::option::Option::Some(error_token(&text, state)), m);
+                            },
+                        };
+
+                        #back_up
+
+                        // Execute the action expression corresponding to the final state.
+                        let m = ::llex::regexp2::automata::Match::new(0, len, input.consume(len));
+                        let text: std::string::String = m.span.iter().collect();
+                        let span = text.as_str();
+                        let token_op = match (mode_index, final_state) {
+                            #( #action_match ),*,
+                            // Catch-all branch should never execute?
+                            _ => std::unreachable!(),
+                        };
+
+                        (token_op, m)
+                    }
+                }
+
+                impl ::llex::stream::LexerDFAMatcher<#return_type> for &#struct_name {
+                    type Mode = #mode_type;
+                    type State = #state_type;
+
+                    #[inline]
+                    fn tokenize<I>(&self, input: &mut ::llex::LexerInput<I>, modes: &mut ::llex::ModeStack<#mode_type>, state: &mut #state_type) -> (std::option::Option<#return_type>, ::llex::regexp2::automata::Match<char>)
+                    where
+                        I: std::iter::Iterator<Item = char>,
+                    {
+                        (*self).tokenize(input, modes, state)
+                    }
+                }
+            };
         }
+    }
+
+    Ok(quote! {
+        #mode_enum
 
-        impl ::llex::stream::LexerDFAMatcher<#return_type> for &#struct_name {
+        #[derive(Debug, Clone, Copy)]
+        #struct_vis struct #struct_name;
+
+        impl #struct_name {
             #[inline]
-            fn tokenize<I>(&self, input: &mut std::iter::Peekable<I>) -> std::option::Option<(#return_type, ::llex::regexp2::automata::Match<char>)>
-            where
-                I: std::iter::Iterator<Item = char>,
-            {
-                (*self).tokenize(input)
+            #struct_vis const fn new() -> Self {
+                Self
             }
+
+            #stream_fn
         }
 
+        #matcher_impls
     })
 }
 
@@ -150,10 +361,15 @@ struct Lexer {
     struct_name: Ident,
     fn_vis: Option<Visibility>,
     fn_name: Ident,
+    source: Option<Source>,
+    modes: Option<Modes>,
 
     span_id: Ident,
+    modes_id: Option<Ident>,
+    state: Option<(Ident, Type)>,
     return_type: Type,
     error_variant: Expr,
+    merge_errors: bool,
 
     rules: Vec<Rule>,
 }
@@ -175,46 +391,96 @@ impl Parse for Lexer {
         let fn_vis = input.parse().ok();
         token!(fn);
         let fn_name = input.parse()?;
+        // Lexers over a source name its type, e.g. `(&'src str)`.
+        let source = if input.peek(token::Paren) {
+            let inner;
+            parenthesized!(inner in input);
+            Some(inner.parse()?)
+        } else {
+            None
+        };
         token!(;);
 
-        let span_id = {
+        // The start conditions are declared as an enum, if there are any.
+        let modes = {
+            let fork = input.fork();
+            let _: Option<Visibility> = fork.parse().ok();
+            if fork.peek(Token![enum]) {
+                Some(input.parse()?)
+            } else {
+                None
+            }
+        };
+
+        // The text of the match is bound first, followed optionally by the mode stack and then
+        // the state of the user with its type, e.g. `(text, modes, state: State)`.
+        let (span_id, modes_id, state) = {
             let inner;
             parenthesized!(inner in input);
             let span_id = inner.parse()?;
-            if !inner.is_empty() {
-                return Err(inner.error("Unexpected token after token string identifier"));
+            let mut modes_id = None;
+            let mut state = None;
+            while !inner.is_empty() {
+                if state.is_some() {
+                    return Err(inner.error("Unexpected token after state identifier"));
+                }
+                inner.parse::<Token![,]>()?;
+                let id: Ident = inner.parse()?;
+                if inner.peek(Token![:]) {
+                    inner.parse::<Token![:]>()?;
+                    state = Some((id, inner.parse()?));
+                } else if modes_id.is_none() {
+                    modes_id = Some(id);
+                } else {
+                    return Err(syn::Error::new(
+                        id.span(),
+                        "expected a state identifier and type",
+                    ));
+                }
             }
-            span_id
+            (span_id, modes_id, state)
         };
 
         token!(->);
         let return_type = input.parse()?;
         token!(,);
 
+        // The error token is an expression of the unmatched text, optionally followed by `merge`
+        // to merge consecutive unmatched chars into one error token.
         let error_variant = input.parse()?;
+        let merge_errors = if input.peek(Token![,]) {
+            token!(,);
+            let merge: Ident = input.parse()?;
+            if merge != "merge" {
+                return Err(syn::Error::new(merge.span(), "expected `merge`"));
+            }
+            true
+        } else {
+            false
+        };
         token!(;);
 
         let rules = {
             let mut rules = Vec::new();
             while !input.is_empty() {
-                let regexp = input.parse()?;
-                input.parse::<Token![=>]>()?;
-
-                let optional_comma = input.peek(token::Brace);
-
-                let action = input.parse()?;
-                let rule = Rule::new(regexp, action);
+                // Rules may be prefixed by the modes they are matched in, either one at a time or
+                // for a block of rules.
+                let scope = if input.peek(Token![<]) {
+                    input.parse()?
+                } else {
+                    Scope::Initial
+                };
 
-                match input.parse::<Token![,]>() {
-                    Ok(_) => {}
-                    Err(e) => {
-                        if !input.is_empty() && !optional_comma {
-                            return Err(e);
-                        }
+                if input.peek(token::Brace) {
+                    let inner;
+                    braced!(inner in input);
+                    while !inner.is_empty() {
+                        rules.push(Rule::parse_scoped(&inner, scope.clone())?);
                     }
+                    input.parse::<Option<Token![,]>>()?;
+                } else {
+                    rules.push(Rule::parse_scoped(input, scope)?);
                 }
-
-                rules.push(rule);
             }
             rules
         };
@@ -224,109 +490,345 @@ impl Parse for Lexer {
             struct_name,
             fn_vis,
             fn_name,
+            source,
+            modes,
             span_id,
+            modes_id,
+            state,
             return_type,
             error_variant,
+            merge_errors,
             rules,
         })
     }
 }
 
+/// The type of the source a lexer matches in place, either `&'src str` or `&'src [u8]`.
+struct Source {
+    lifetime: Lifetime,
+    ty: Type,
+}
+
+impl Parse for Source {
+    #[inline]
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let ty: Type = input.parse()?;
+        match &ty {
+            Type::Reference(TypeReference {
+                lifetime: Some(lifetime),
+                mutability: None,
+                ..
+            }) => Ok(Self {
+                lifetime: lifetime.clone(),
+                ty,
+            }),
+            _ => Err(syn::Error::new_spanned(
+                ty,
+                "expected a source type with a named lifetime, e.g. `&'src str`",
+            )),
+        }
+    }
+}
+
+/// The declaration of the start conditions of a lexer, the first of which is the initial mode.
+struct Modes {
+    vis: Option<Visibility>,
+    name: Ident,
+    variants: Vec<Ident>,
+}
+
+impl Parse for Modes {
+    #[inline]
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let vis = input.parse().ok();
+        input.parse::<Token![enum]>()?;
+        let name: Ident = input.parse()?;
+
+        let inner;
+        braced!(inner in input);
+        let variants: Vec<Ident> = inner
+            .parse_terminated::<_, Token![,]>(Ident::parse)?
+            .into_iter()
+            .collect();
+        if variants.is_empty() {
+            return Err(syn::Error::new(
+                name.span(),
+                "at least one mode must be declared",
+            ));
+        }
+
+        Ok(Self {
+            vis,
+            name,
+            variants,
+        })
+    }
+}
+
+/// The modes in which a rule is matched.
+#[derive(Clone)]
+enum Scope {
+    /// Rules without a prefix are matched in the initial mode.
+    Initial,
+    /// `<*>`: every mode.
+    All,
+    /// `<A, B>`: the given modes.
+    Named(Vec<Ident>),
+}
+
+impl Parse for Scope {
+    #[inline]
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        input.parse::<Token![<]>()?;
+        let scope = if input.peek(Token![*]) {
+            input.parse::<Token![*]>()?;
+            Scope::All
+        } else {
+            let mut names = vec![input.parse()?];
+            while input.peek(Token![,]) {
+                input.parse::<Token![,]>()?;
+                names.push(input.parse()?);
+            }
+            Scope::Named(names)
+        };
+        input.parse::<Token![>]>()?;
+        Ok(scope)
+    }
+}
+
 struct Rule {
+    scope: Scope,
     regexp: LitStr,
     action: Expr,
 }
 
 impl Rule {
     #[inline]
-    fn new(regexp: LitStr, action: Expr) -> Self {
-        Self { regexp, action }
+    fn new(scope: Scope, regexp: LitStr, action: Expr) -> Self {
+        Self {
+            scope,
+            regexp,
+            action,
+        }
+    }
+
+    /// Parse a rule matched in the given modes, along with the comma following it.
+    #[inline]
+    fn parse_scoped(input: ParseStream<'_>, scope: Scope) -> syn::Result<Self> {
+        let regexp = input.parse()?;
+        input.parse::<Token![=>]>()?;
+
+        // A block action needs no comma after it, so it mustn't be parsed as the start of a longer
+        // expression, such as a comparison with the mode prefix of the next rule.
+        let optional_comma = input.peek(token::Brace);
+        let action = if optional_comma {
+            Expr::Block(input.parse()?)
+        } else {
+            input.parse()?
+        };
+        let rule = Rule::new(scope, regexp, action);
+
+        match input.parse::<Token![,]>() {
+            Ok(_) => {}
+            Err(e) => {
+                if !input.is_empty() && !optional_comma {
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(rule)
     }
 }
 
-const INVALID_REGEXP_ERROR: &str = "invalid regular expression";
+/// Group the rules by the modes they are matched in, keeping their order, which gives their
+/// precedence.
+#[inline]
+fn mode_rules<'a>(
+    rules: &'a [Rule],
+    mode_names: &[Ident],
+    modes: &Option<Modes>,
+) -> Result<Vec<Vec<&'a Rule>>, TokenStream> {
+    let mut grouped = vec![Vec::new(); mode_names.len()];
+    for rule in rules {
+        match &rule.scope {
+            Scope::Initial => grouped[0].push(rule),
+            Scope::All => grouped.iter_mut().for_each(|g| g.push(rule)),
+            Scope::Named(names) => {
+                for name in names {
+                    let index = match (modes, mode_names.iter().position(|m| m == name)) {
+                        (Some(_), Some(index)) => index,
+                        _ => {
+                            return Err(span_error(
+                                name.span(),
+                                &format!("undeclared mode `{}`", name),
+                            ))
+                        }
+                    };
+                    grouped[index].push(rule);
+                }
+            }
+        }
+    }
+
+    // A declared mode without rules could never match anything.
+    if modes.is_some() {
+        for (name, group) in mode_names.iter().zip(grouped.iter()) {
+            if group.is_empty() {
+                return Err(span_error(
+                    name.span(),
+                    &format!("mode `{}` has no rules", name),
+                ));
+            }
+        }
+    }
 
-// Parse the rules into a single NFA and a map of final states to action expressions.
+    Ok(grouped)
+}
+
+// Check that each rule wins in some final state of the DFA of at least one of its modes, returning
+// an error for each rule that doesn't, naming the earlier rules that win wherever it's accepted. A
+// rule matched in several modes may be shadowed in some of them, as a catch-all rule of one mode
+// shadows a later `<*>` rule.
 #[inline]
-#[allow(clippy::type_complexity)]
-fn parse_combined_nfa(
+fn check_shadowed(
     rules: &[Rule],
-) -> Result<(NFA<CharClass>, HashMap<usize, (&Expr, usize)>), TokenStream> {
-    let nfa_parser = NFAParser::new();
-    // Parse regular expression strings into NFAs.
-    let nfa_sub: Vec<_> = rules
-        .iter()
-        .map(
-            |Rule { regexp, action }| match nfa_parser.parse(&regexp.value()) {
-                // Throw errors if failed to parse.
-                Ok(op) => match op {
-                    Some(n) => Ok(Some((n, action))),
-                    // None returned means error.
-                    None => Err(span_error(regexp.span(), INVALID_REGEXP_ERROR)),
-                },
-                Err(e) => Err(span_error(
-                    regexp.span(),
-                    &format!("{}: {}", INVALID_REGEXP_ERROR, e),
-                )),
-            },
-        )
-        .collect::<Result<_, _>>()?;
-
-    let nfa_sub: Vec<_> = nfa_sub.into_iter().filter_map(|nfa| nfa).collect();
-
-    // Combine NFAs into a single NFA.
-    let mut action_mapping = HashMap::new();
-    let mut nfa = NFA::new();
-    let mut offset = nfa.total_states;
-    for (precedence, (sub, action)) in nfa_sub.iter().enumerate() {
-        NFA::copy_into(&mut nfa, sub);
-        nfa.add_epsilon_transition(nfa.initial_state, sub.initial_state + offset);
-        // Map new, offsetted final states to their original action.
-        for sub_final in sub.final_states.iter() {
-            nfa.final_states.insert(*sub_final + offset);
-            action_mapping.insert(*sub_final + offset, (*action, precedence));
+    grouped: &[Vec<&Rule>],
+    shadowed: &[Vec<(usize, Vec<usize>)>],
+    mode_names: Option<&[Ident]>,
+) -> Result<(), TokenStream> {
+    let mut errors = TokenStream::new();
+    for rule in rules {
+        // The reason the rule never matches in each of its modes, or none if it matches in one.
+        let mut reasons = Vec::new();
+        for (mode_index, mode_rules) in grouped.iter().enumerate() {
+            let i = match mode_rules.iter().position(|&r| std::ptr::eq(r, rule)) {
+                Some(i) => i,
+                None => continue,
+            };
+            let winners = match shadowed[mode_index].iter().find(|(j, _)| *j == i) {
+                Some((_, winners)) => winners,
+                None => {
+                    reasons.clear();
+                    break;
+                }
+            };
+            let reason = match winners.len() {
+                0 => "it matches no non-empty input".to_string(),
+                1 => format!(
+                    "it is shadowed by the earlier rule `{}`",
+                    mode_rules[winners[0]].regexp.value()
+                ),
+                _ => {
+                    let shadowing: Vec<_> = winners
+                        .iter()
+                        .map(|&j| format!("`{}`", mode_rules[j].regexp.value()))
+                        .collect();
+                    format!(
+                        "it is shadowed by the earlier rules {}",
+                        shadowing.join(", ")
+                    )
+                }
+            };
+            let mode = mode_names.map(|names| &names[mode_index]);
+            reasons.push((mode, reason));
         }
 
-        offset += sub.total_states;
+        let message = match reasons.as_slice() {
+            [] => continue,
+            [(None, reason)] => format!("rule `{}` never matches: {}", rule.regexp.value(), reason),
+            [(Some(mode), reason)] => format!(
+                "rule `{}` never matches in mode `{}`: {}",
+                rule.regexp.value(),
+                mode,
+                reason
+            ),
+            _ => {
+                let reasons: Vec<_> = reasons
+                    .iter()
+                    .map(|(mode, reason)| format!("in mode `{}` {}", mode.unwrap(), reason))
+                    .collect();
+                format!(
+                    "rule `{}` never matches in any of its modes: {}",
+                    rule.regexp.value(),
+                    reasons.join("; ")
+                )
+            }
+        };
+        errors.extend(span_error(rule.regexp.span(), &message));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
     }
+}
+
+const INVALID_REGEXP_ERROR: &str = "invalid regular expression";
 
-    Ok((nfa, action_mapping))
+// Parse the patterns of rules, returning an error at the first invalid one.
+#[inline]
+fn parse_rules(rules: &[&Rule]) -> Result<Vec<ParsedRule>, TokenStream> {
+    rules
+        .iter()
+        .map(|Rule { regexp, .. }| {
+            ParsedRule::parse(&regexp.value()).map_err(|e| match e {
+                RuleError::Parse(e) => span_error(
+                    regexp_error_span(regexp, &e),
+                    &format!("{}: {}", INVALID_REGEXP_ERROR, e.render(&regexp.value())),
+                ),
+                RuleError::Empty => span_error(regexp.span(), INVALID_REGEXP_ERROR),
+                e => span_error(regexp.span(), &e.to_string()),
+            })
+        })
+        .collect()
 }
 
-fn dfa_rebuilt(dfa: &DFA<CharClass>) -> TokenStream {
-    let initial_state = dfa.initial_state;
-    let total_states = dfa.total_states;
-    let final_states: Vec<_> = dfa.final_states.iter().collect();
-    let transition_sets: Vec<_> = dfa
-        .transition
-        .clone()
-        .into_iter()
-        .map(|(src, Transition(tr), dest)| {
-            let ranges: Vec<_> = tr
-                .ranges
+// Code the DFA of a mode directly, as the arms of the `next_state` and `is_final_state` matches
+// of the generated automaton, which are on the mode index and the state. The transitions of each
+// state are a sorted array of char ranges that is searched for the next char.
+#[inline]
+fn dfa_coded(mode_index: usize, dfa: &DFA<CharClass>) -> (Vec<TokenStream>, Vec<TokenStream>) {
+    let mut transitions: BTreeMap<usize, Vec<(char, char, usize)>> = BTreeMap::new();
+    for (src, Transition(tr), dest) in dfa.transition.clone().into_iter() {
+        let ranges = transitions.entry(*src).or_default();
+        ranges.extend(
+            tr.ranges
                 .iter()
-                .map(|CharRange { start, end }| quote!(::llex::regexp2::class::CharRange::new(#start, #end)))
-                .collect();
-            quote! {
-                dfa.transition.set(#src, ::llex::regexp2::automata::dfa::Transition(vec![ #( #ranges ),* ].into()), #dest);
-            }
+                .map(|&CharRange { start, end }| (start, end, *dest)),
+        );
+    }
+
+    let transition_arms = transitions
+        .into_iter()
+        .map(|(src, mut ranges)| {
+            ranges.sort_unstable();
+            let ranges = ranges
+                .into_iter()
+                .map(|(start, end, dest)| quote!((#start, #end, #dest)));
+            quote!((#mode_index, #src) => &[ #( #ranges ),* ])
         })
         .collect();
 
-    quote! {
-        {
-            let mut dfa = ::llex::regexp2::automata::DFA::new();
-            dfa.initial_state = #initial_state;
-            dfa.total_states = #total_states;
-            dfa.final_states = std::collections::HashSet::new();
-            dfa.final_states.extend(&[ #( #final_states ),* ]);
+    let mut final_states: Vec<_> = dfa.final_states.iter().collect();
+    final_states.sort_unstable();
+    let final_arms = final_states
+        .into_iter()
+        .map(|state| quote!((#mode_index, #state) => true))
+        .collect();
 
-            dfa.transition = ::llex::regexp2::automata::table::Table::new();
-            #( #transition_sets )*
+    (transition_arms, final_arms)
+}
 
-            dfa
-        }
-    }
+/// Find the span of the part of a regular expression literal at which a parse error occurred. This
+/// falls back to the span of the whole literal if the error cannot be located in the source, such
+/// as when the literal contains escapes or the compiler does not support subspans.
+#[inline]
+fn regexp_error_span(regexp: &LitStr, e: &ParseError) -> Span {
+    e.literal_span(&regexp.value(), &regexp.token().to_string())
+        .and_then(|range| regexp.token().subspan(range))
+        .unwrap_or_else(|| regexp.span())
 }
 
 fn span_error(span: Span, message: &str) -> TokenStream {
@@ -0,0 +1,211 @@
+#![deny(rust_2018_idioms)]
+#![deny(future_incompatible)]
+
+use std::collections::{BTreeSet, HashMap};
+use std::error;
+use std::fmt;
+
+use regexp2::{
+    automata::{dfa::DFAFromNFAs, DFA, NFA},
+    class::CharClass,
+    parser::{NFAParser, ParseError, Parser},
+};
+
+/// The pattern of a lexer rule parsed into an NFA. Rules with trailing context `r/s` also have the
+/// DFAs of `r` and `s`, with which the lexer backs up to the end of `r`.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub nfa: NFA<CharClass>,
+    pub trailing: Option<(DFA<CharClass>, DFA<CharClass>)>,
+}
+
+impl Rule {
+    /// Parse the pattern of a rule, which may be followed by trailing context after a `/` that
+    /// isn't escaped, quoted or within a class or group.
+    #[inline]
+    pub fn parse(pattern: &str) -> Result<Self, RuleError> {
+        let parser = NFAParser::new();
+        let chars: Vec<char> = pattern.chars().collect();
+        match trailing_slash(&chars)? {
+            None => Ok(Self {
+                nfa: parse_nfa(&parser, pattern, 0)?,
+                trailing: None,
+            }),
+            Some(i) => {
+                let leading: String = chars[..i].iter().collect();
+                let trailing: String = chars[i + 1..].iter().collect();
+                let r = DFA::from(parse_nfa(&parser, &leading, 0)?);
+                let s = DFA::from(parse_nfa(&parser, &trailing, i + 1)?);
+                // The lexer can't back up to an empty leading part, since empty matches aren't
+                // lexed.
+                if r.is_final_state(&r.initial_state) {
+                    return Err(RuleError::TrailingEmpty);
+                }
+
+                let whole = format!("(?:{})(?:{})", leading, trailing);
+                Ok(Self {
+                    nfa: parse_nfa(&parser, &whole, 0)?,
+                    trailing: Some((r, s)),
+                })
+            }
+        }
+    }
+}
+
+/// The DFA of a list of rules, recognizing the union of their patterns.
+#[derive(Debug, Clone)]
+pub struct RulesDFA {
+    pub dfa: DFA<CharClass>,
+    /// The indices of the rules that accept in each final state of the DFA, in ascending order.
+    /// The earliest of them is the rule that wins in the state.
+    pub accepting: HashMap<usize, Vec<usize>>,
+}
+
+impl RulesDFA {
+    #[inline]
+    pub fn new(rules: &[Rule]) -> Self {
+        let nfas: Vec<_> = rules.iter().map(|rule| &rule.nfa).collect();
+        let DFAFromNFAs { dfa, accepting } = DFAFromNFAs::new(&nfas);
+        Self { dfa, accepting }
+    }
+
+    /// Return each of the first `len` rules that never wins in a final state, along with the
+    /// earlier rules that win wherever it's accepted. Only the earliest rule accepted in a state
+    /// wins, so a rule is dead if every non-empty string it matches is matched by an earlier rule.
+    /// The initial state is skipped, since empty matches aren't lexed.
+    #[inline]
+    pub fn shadowed(&self, len: usize) -> Vec<(usize, Vec<usize>)> {
+        (0..len)
+            .filter_map(|i| {
+                let winners: BTreeSet<_> = self
+                    .accepting
+                    .iter()
+                    .filter(|(&state, indices)| {
+                        state != self.dfa.initial_state && indices.contains(&i)
+                    })
+                    .map(|(_, indices)| indices[0])
+                    .collect();
+                if winners.contains(&i) {
+                    None
+                } else {
+                    Some((i, winners.into_iter().collect()))
+                }
+            })
+            .collect()
+    }
+}
+
+/// The error that occurs when the pattern of a rule is invalid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RuleError {
+    /// The pattern, or one of its parts around `/`, isn't a valid regular expression. The span of
+    /// the error is relative to the whole pattern.
+    Parse(ParseError),
+    /// The pattern, or one of its parts around `/`, is empty.
+    Empty,
+    /// A `/` is at the start or end of the pattern.
+    TrailingSlash,
+    /// More than one `/` is in the pattern.
+    TrailingTwice,
+    /// A `/` is within a group.
+    TrailingGroup,
+    /// The pattern before the trailing context matches the empty string.
+    TrailingEmpty,
+}
+
+impl fmt::Display for RuleError {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => e.fmt(f),
+            Self::Empty => write!(f, "the pattern is empty"),
+            Self::TrailingSlash => write!(
+                f,
+                "trailing context needs a pattern on both sides of `/`; escape it as `\\/` to \
+                 match a slash"
+            ),
+            Self::TrailingTwice => write!(f, "trailing context may only be given once"),
+            Self::TrailingGroup => write!(f, "trailing context may not be given within a group"),
+            Self::TrailingEmpty => write!(
+                f,
+                "the pattern before the trailing context may not match the empty string"
+            ),
+        }
+    }
+}
+
+impl error::Error for RuleError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+// Find the unescaped `/` outside of classes and quoted text that separates a pattern from its
+// trailing context, as a char index.
+#[inline]
+fn trailing_slash(chars: &[char]) -> Result<Option<usize>, RuleError> {
+    let mut slash = None;
+    let mut quoted = false;
+    let mut class_depth = 0usize;
+    let mut group_depth = 0usize;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        i += 1;
+
+        // Quoted text runs until \E or the end of the pattern.
+        if quoted {
+            if c == '\\' && chars.get(i) == Some(&'E') {
+                quoted = false;
+                i += 1;
+            }
+            continue;
+        }
+
+        match c {
+            '\\' => {
+                quoted = chars.get(i) == Some(&'Q');
+                i += 1;
+            }
+            '[' => class_depth += 1,
+            ']' => class_depth = class_depth.saturating_sub(1),
+            '(' if class_depth == 0 => group_depth += 1,
+            ')' if class_depth == 0 => group_depth = group_depth.saturating_sub(1),
+            '/' if class_depth == 0 => {
+                if group_depth > 0 {
+                    return Err(RuleError::TrailingGroup);
+                } else if slash.is_some() {
+                    return Err(RuleError::TrailingTwice);
+                }
+                slash = Some(i - 1);
+            }
+            _ => {}
+        }
+    }
+
+    match slash {
+        Some(i) if i == 0 || i == chars.len() - 1 => Err(RuleError::TrailingSlash),
+        _ => Ok(slash),
+    }
+}
+
+// Parse a part of a pattern that starts at the given char offset of it.
+#[inline]
+fn parse_nfa(
+    parser: &NFAParser<CharClass>,
+    pattern: &str,
+    offset: usize,
+) -> Result<NFA<CharClass>, RuleError> {
+    match parser.parse(pattern) {
+        Ok(Some(nfa)) => Ok(nfa),
+        Ok(None) => Err(RuleError::Empty),
+        Err(mut e) => {
+            e.span = e.span.start + offset..e.span.end + offset;
+            Err(RuleError::Parse(e))
+        }
+    }
+}
@@ -1,26 +1,38 @@
 // lexer! creates a struct with visibility (#struct_visibility) and name (#struct_name). It defines
 // the method #struct_name::#fn_name (e.g. Lexer::stream) to return an iterator for tokens
 // (LexerStream<#token_type>) parsed from the given input. See below example. On error (such as
-// where no tokens can be produced from the remaining non-empty input), the error variant
-// (#error_variant) is returned.
+// where no tokens can be produced from the remaining non-empty input), the unmatched char is
+// consumed and the error expression (#error_variant) is returned, with #span_var bound to the
+// char. If `merge` follows the error expression, consecutive unmatched chars are merged into one
+// error token.
 //
 // Define the regular expression and their corresponding actions, highest precedence first.  See
 // `regexp2` crate for supported regular expression syntax. The action expressions must return
-// Option<#token_type>.
+// Option<#token_type>. The longest match is taken, and of the rules with the longest match, the
+// earliest. A rule that never matches, since earlier rules match everything it does, is an error
+// naming the rules that shadow it.
+//
+// An unescaped `/` separates a rule into a regexp r and its trailing context s, as in flex: the
+// rule matches r only when followed by s, which is left in the input. A literal slash is written
+// `\/`.
+//
+// The DFA of the rules is coded directly as a state machine, so creating a lexer is free.
 //
 //
 // FORMAT:
 //
 // #struct_visibility struct #struct_name;
 // #fn_visibility fn #fn_name;
-// (#span_var) -> #token_type, #error_variant;
+// (#span_var) -> #token_type, #error_variant[, merge];
 //
 //
 // GENERATED:
 //
-// #struct_visibility struct #struct_name { ... }
+// #struct_visibility struct #struct_name;
 //
 // impl #struct_name {
+//     #struct_visibility const fn new() -> Self { ... }
+//
 //     #struct_visibility fn stream(&self, input: &str) -> Option<LexerItem<#token_type>> {
 //         ...
 //     }
@@ -48,7 +60,7 @@ pub enum Token {
     Semicolon,
     Comma,
 
-    Error,
+    Error(String),
 }
 
 impl fmt::Display for Token {
@@ -66,7 +78,7 @@ impl fmt::Display for Token {
             Token::RightBracket => write!(f, "]"),
             Token::Semicolon => write!(f, ";"),
             Token::Comma => write!(f, ","),
-            Token::Error => write!(f, "<error>"),
+            Token::Error(s) => write!(f, "<error {}>", s),
         }
     }
 }
@@ -75,11 +87,11 @@ lexer! {
     pub struct Lexer;
     // Generated:
     //
-    //     pub struct Lexer { ... }
+    //     pub struct Lexer;
     //
 
     pub fn stream;
-    (text) -> Token, Token::Error;
+    (text) -> Token, Token::Error(text.to_string()), merge;
     // Generated:
     //
     //     impl struct Lexer {
@@ -118,6 +130,7 @@ pub enum Token {
     Ident(String),
     Integer(i64),
     Float(f64),
+    Invalid = $$,
 }
 ";
 
@@ -127,7 +140,7 @@ fn main() {
     let tokens = lexer.stream(chars);
 
     for t in tokens {
-        print!("('{}' {}:{}) ", t.token, t.m.start, t.m.end - 1);
+        print!("('{}' {}-{}) ", t.token, t.start, t.end);
     }
     println!()
 }
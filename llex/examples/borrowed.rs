@@ -0,0 +1,55 @@
+// A lexer may match a `&str` or `&[u8]` source in place, instead of an iterator of chars, by
+// naming the type of the source after the function name. The actions and the error expression
+// then receive the text of the match as a slice of the source, with its lifetime, so tokens may
+// borrow their text instead of copying it.
+//
+// Bytes of a `&[u8]` source are matched as the chars of the same value, so that bytes outside of
+// ASCII may be matched by `[\x80-\xff]`.
+//
+//
+// FORMAT:
+//
+// #struct_visibility struct #struct_name;
+// #fn_visibility fn #fn_name(&#lifetime str);
+// (#span_var) -> #token_type, #error_variant;
+
+use std::collections::HashSet;
+
+use llex::lexer;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'src> {
+    Ident(&'src str),
+    Integer(i64),
+    Error(&'src str),
+}
+
+lexer! {
+    pub struct Lexer;
+    pub fn stream(&'src str);
+    (text) -> Token<'src>, Token::Error(text), merge;
+
+    r"\s" => None,
+    r"[A-Za-z_][A-Za-z0-9_]*" => Some(Token::Ident(text)),
+    r"[0-9]+" => Some(Token::Integer(text.parse().unwrap())),
+}
+
+const INPUT_STR: &str = "let x = y + 10; let z = x";
+
+fn main() {
+    let lexer = Lexer::new();
+
+    // Identifiers may be interned by the slices of the source they borrow.
+    let mut idents = HashSet::new();
+    for t in lexer.stream(INPUT_STR) {
+        if let Token::Ident(ident) = t.token {
+            idents.insert(ident);
+        }
+        print!("({:?} {}-{}) ", t.token, t.start, t.end);
+    }
+    println!();
+
+    let mut idents: Vec<_> = idents.into_iter().collect();
+    idents.sort_unstable();
+    println!("{:?}", idents);
+}
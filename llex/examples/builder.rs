@@ -0,0 +1,57 @@
+// A LexerBuilder builds a lexer from rules given at runtime, such as from a configuration file,
+// where the lexer! macro can't be used. Each rule is a regular expression and a closure that
+// makes the token of the matched text or returns None to skip it. As in the macro, the longest
+// match is taken, and of the rules with the longest match, the one added first.
+//
+// The lexer built is used in the same way as those generated by the macro, returning a
+// LexerStream from its `stream` method.
+
+use llex::LexerBuilder;
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    kind: String,
+    text: String,
+}
+
+// The syntax definition to load rules from, as a kind of token and the pattern that matches it
+// on each line. Text of the kind `skip` isn't returned as tokens.
+const SYNTAX: &str = r"
+keyword fn|let|return
+ident [A-Za-z_][A-Za-z0-9_]*
+number [0-9]+
+punct [(){};=+]
+skip \s+
+";
+
+const INPUT_STR: &str = "fn f() { let x = 1 + 2; return x; } #";
+
+fn main() -> Result<(), llex::BuildError> {
+    let mut builder = LexerBuilder::new(|text| Token {
+        kind: "error".to_string(),
+        text: text.to_string(),
+    });
+
+    for line in SYNTAX.lines().filter(|line| !line.is_empty()) {
+        let (kind, pattern) = line.split_at(line.find(' ').unwrap());
+        let kind = kind.to_string();
+        builder = builder.rule(&pattern[1..], move |text| {
+            if kind == "skip" {
+                None
+            } else {
+                Some(Token {
+                    kind: kind.clone(),
+                    text: text.to_string(),
+                })
+            }
+        });
+    }
+
+    let lexer = builder.build()?;
+    for t in lexer.stream(INPUT_STR.chars()) {
+        print!("({} {:?}) ", t.token.kind, t.token.text);
+    }
+    println!();
+
+    Ok(())
+}
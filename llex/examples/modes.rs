@@ -0,0 +1,78 @@
+// Start conditions (modes) select which rules are matched, as in flex. They are declared as an
+// enum after the function name, the first variant of which is the initial mode. Rules without a
+// prefix are matched in the initial mode; rules prefixed by `<A, B>` are matched in modes A and B,
+// and rules prefixed by `<*>` in every mode. A prefix may also apply to a block of rules.
+//
+// Each mode is compiled to its own DFA. The second identifier in the parentheses binds the
+// lexer's ModeStack in the actions, which may push, pop or switch modes. A rule of several modes
+// may be shadowed by earlier rules in some of them, but is an error if it never matches in any.
+//
+//
+// FORMAT:
+//
+// #struct_visibility struct #struct_name;
+// #fn_visibility fn #fn_name;
+// #enum_visibility enum #mode_type { #initial_mode, ... }
+// (#span_var, #modes_var) -> #token_type, #error_variant;
+//
+// <#mode, ...> #regexp => #action,
+// <#mode, ...> { #regexp => #action, ... }
+
+use llex::lexer;
+
+#[derive(Debug, Clone)]
+pub enum Token {
+    Ident(String),
+    Str(String),
+    Error,
+}
+
+lexer! {
+    pub struct Lexer;
+    pub fn stream;
+    pub enum Mode { Main, Str, Comment }
+    (text, modes) -> Token, Token::Error;
+
+    r"\s" => None,
+    r"\w+" => Some(Token::Ident(text.to_string())),
+    "\"" => {
+        modes.push(Mode::Str);
+        None
+    }
+    r"\/\*" => {
+        modes.push(Mode::Comment);
+        None
+    }
+
+    <Str> r#"([^"\\]|\\.)*""# => {
+        modes.pop();
+        Some(Token::Str(text.strip_suffix('"').unwrap().to_string()))
+    }
+
+    // Comments nest, since each opening delimiter pushes another comment mode.
+    <Comment> {
+        r"\/\*" => {
+            modes.push(Mode::Comment);
+            None
+        }
+        r"\*\/" => {
+            modes.pop();
+            None
+        }
+    }
+    <Comment, Str> r"[\s\S]" => None,
+}
+
+const INPUT_STR: &str = r#"
+first /* a /* nested */ comment */ second "a \"string\"" third
+"#;
+
+fn main() {
+    let lexer = Lexer::new();
+    let tokens = lexer.stream(INPUT_STR.chars());
+
+    for t in tokens {
+        print!("{:?} ", t.token);
+    }
+    println!()
+}
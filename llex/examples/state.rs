@@ -0,0 +1,76 @@
+// Actions may share a state of the user, declared by binding it with its type after the text of
+// the match (and the mode stack, if bound). Actions and the error expression receive it as
+// `&mut #state_type`. It starts as the default of its type, and stays reachable from the stream
+// through `state`, `state_mut` and `into_state`.
+//
+//
+// FORMAT:
+//
+// #struct_visibility struct #struct_name;
+// #fn_visibility fn #fn_name;
+// (#span_var[, #modes_var], #state_var: #state_type) -> #token_type, #error_variant;
+
+use std::collections::HashMap;
+
+use llex::lexer;
+
+#[derive(Debug, Clone)]
+pub enum Token {
+    /// An identifier, by its index in the table of interned identifiers.
+    Ident(usize),
+    Integer(i64),
+    Error,
+}
+
+/// Interns identifiers and records comments and errors.
+#[derive(Debug, Default)]
+pub struct State {
+    idents: HashMap<String, usize>,
+    comments: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl State {
+    fn intern(&mut self, ident: &str) -> usize {
+        let next = self.idents.len();
+        *self.idents.entry(ident.to_string()).or_insert(next)
+    }
+}
+
+lexer! {
+    pub struct Lexer;
+    pub fn stream;
+    (text, state: State) -> Token, {
+        state.errors.push(format!("unexpected {:?}", text));
+        Token::Error
+    };
+
+    r"\s" => None,
+    r"#[^\n]*" => {
+        state.comments.push(text[1..].trim().to_string());
+        None
+    }
+    r"[A-Za-z_][A-Za-z0-9_]*" => Some(Token::Ident(state.intern(text))),
+    r"[0-9]+" => Some(Token::Integer(text.parse().unwrap())),
+}
+
+const INPUT_STR: &str = r"
+# Counting.
+a b a 1 ? # Twice.
+b 2
+";
+
+fn main() {
+    let lexer = Lexer::new();
+    let mut tokens = lexer.stream(INPUT_STR.chars());
+
+    for t in &mut tokens {
+        print!("{:?} ", t.token);
+    }
+    println!();
+
+    let state = tokens.into_state();
+    println!("{:?}", state.idents);
+    println!("{:?}", state.comments);
+    println!("{:?}", state.errors);
+}
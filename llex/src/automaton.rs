@@ -0,0 +1,97 @@
+use crate::stream::LexerDFA;
+
+use std::cmp::Ordering;
+
+/// A deterministic automaton that lexers step through to find the longest match. It is
+/// implemented by the DFAs of lexers built at runtime and by the state machines coded directly by
+/// the `lexer!` macro.
+pub trait Automaton {
+    fn initial_state(&self) -> usize;
+
+    /// Return the state reached from a state on a char, or `None` if there is no transition.
+    fn next_state(&self, state: usize, c: char) -> Option<usize>;
+
+    fn is_final_state(&self, state: usize) -> bool;
+}
+
+impl Automaton for LexerDFA {
+    #[inline]
+    fn initial_state(&self) -> usize {
+        self.initial_state
+    }
+
+    #[inline]
+    fn next_state(&self, state: usize, c: char) -> Option<usize> {
+        LexerDFA::next_state(self, state, &c)
+    }
+
+    #[inline]
+    fn is_final_state(&self, state: usize) -> bool {
+        LexerDFA::is_final_state(self, &state)
+    }
+}
+
+/// Return the state reached on a char, given the transitions of a state as sorted, disjoint
+/// ranges of chars and the states they lead to. This is used by the state machines coded by the
+/// `lexer!` macro.
+#[inline]
+pub fn next_in_ranges(transitions: &[(char, char, usize)], c: char) -> Option<usize> {
+    transitions
+        .binary_search_by(|&(start, end, _)| {
+            if end < c {
+                Ordering::Less
+            } else if start > c {
+                Ordering::Greater
+            } else {
+                Ordering::Equal
+            }
+        })
+        .ok()
+        .map(|i| transitions[i].2)
+}
+
+/// Return the length in bytes of the leading part `r` of a match of trailing context `r/s`,
+/// given the chars of the match with their lengths in bytes and the automata of `r` and `s`. The
+/// leading part is the longest non-empty prefix matched by `r` that leaves the rest of the match
+/// to be matched by `s`.
+#[inline]
+pub(crate) fn trailing_context<A, B>(r: &A, s: &B, chars: &[(char, usize)]) -> usize
+where
+    A: Automaton + ?Sized,
+    B: Automaton + ?Sized,
+{
+    // The ends of the prefixes matched by `r`.
+    let mut ends = Vec::new();
+    let mut state = r.initial_state();
+    for (i, &(c, _)) in chars.iter().enumerate() {
+        state = match r.next_state(state, c) {
+            Some(state) => state,
+            None => break,
+        };
+        if r.is_final_state(state) {
+            ends.push(i + 1);
+        }
+    }
+
+    let end = ends
+        .into_iter()
+        .rev()
+        .find(|&end| is_match(s, &chars[end..]))
+        .expect("match of trailing context has no leading part");
+    chars[..end].iter().map(|&(_, len)| len).sum()
+}
+
+#[inline]
+fn is_match<A>(dfa: &A, chars: &[(char, usize)]) -> bool
+where
+    A: Automaton + ?Sized,
+{
+    let mut state = dfa.initial_state();
+    for &(c, _) in chars {
+        state = match dfa.next_state(state, c) {
+            Some(state) => state,
+            None => return false,
+        };
+    }
+    dfa.is_final_state(state)
+}
@@ -0,0 +1,402 @@
+use crate::input::LexerInput;
+use crate::mode::ModeStack;
+use crate::stream::{LexerDFA, LexerDFAMatcher, LexerStream};
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+
+use llex_rules::{Rule, RuleError, RulesDFA};
+use regexp2::automata::Match;
+
+type Action<T> = Arc<dyn Fn(&str) -> Option<T> + Send + Sync>;
+type ErrorAction<T> = Arc<dyn Fn(&str) -> T + Send + Sync>;
+/// The DFAs of the leading part and trailing context of a rule.
+type TrailingDFAs = (LexerDFA, LexerDFA);
+
+/// A builder of lexers from rules given at runtime, for when they aren't known at compile time
+/// and the `lexer!` macro can't be used. As in the macro, the longest match is taken, and of the
+/// rules with the longest match, the one added first.
+pub struct LexerBuilder<T> {
+    rules: Vec<(String, Action<T>)>,
+    error: ErrorAction<T>,
+    merge_errors: bool,
+}
+
+impl<T> LexerBuilder<T> {
+    /// Create a builder without rules. The lexers built return the token made by `error` from
+    /// the text that no rule matches.
+    #[inline]
+    pub fn new<F>(error: F) -> Self
+    where
+        F: Fn(&str) -> T + Send + Sync + 'static,
+    {
+        Self {
+            rules: Vec::new(),
+            error: Arc::new(error),
+            merge_errors: false,
+        }
+    }
+
+    /// Add a rule matching a regular expression, the action of which makes the token of the
+    /// matched text, or returns `None` to skip it. As in the macro, the pattern may be followed
+    /// by trailing context after a `/`.
+    #[inline]
+    pub fn rule<F>(mut self, pattern: &str, action: F) -> Self
+    where
+        F: Fn(&str) -> Option<T> + Send + Sync + 'static,
+    {
+        self.rules.push((pattern.to_string(), Arc::new(action)));
+        self
+    }
+
+    /// Set whether consecutive unmatched chars are merged into one error token.
+    #[inline]
+    pub fn with_merged_errors(mut self, merge_errors: bool) -> Self {
+        self.merge_errors = merge_errors;
+        self
+    }
+
+    /// Compile the rules into a lexer. As in the macro, it is an error for a rule to be
+    /// shadowed by earlier rules, so that it never matches.
+    #[inline]
+    pub fn build(&self) -> Result<RuntimeLexer<T>, BuildError> {
+        let parsed = self
+            .rules
+            .iter()
+            .enumerate()
+            .map(|(rule, (pattern, _))| {
+                Rule::parse(pattern).map_err(|error| BuildError::Invalid { rule, error })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let rules_dfa = RulesDFA::new(&parsed);
+        if let Some((rule, by)) = rules_dfa.shadowed(parsed.len()).into_iter().next() {
+            return Err(BuildError::Shadowed { rule, by });
+        }
+        let RulesDFA { dfa, accepting } = rules_dfa;
+
+        let rules = self
+            .rules
+            .iter()
+            .zip(parsed)
+            .map(|((_, action), rule)| (action.clone(), rule.trailing))
+            .collect();
+
+        // The action of each final DFA state is that of the earliest rule accepted in it.
+        let accepting = accepting
+            .into_iter()
+            .map(|(state, indices)| (state, indices[0]))
+            .collect();
+
+        Ok(RuntimeLexer {
+            dfa,
+            accepting,
+            rules,
+            error: self.error.clone(),
+            merge_errors: self.merge_errors,
+        })
+    }
+}
+
+impl<T> fmt::Debug for LexerBuilder<T> {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        let patterns: Vec<_> = self.rules.iter().map(|(pattern, _)| pattern).collect();
+        f.debug_struct("LexerBuilder")
+            .field("rules", &patterns)
+            .field("merge_errors", &self.merge_errors)
+            .finish()
+    }
+}
+
+/// The error that occurs when a rule given to a [LexerBuilder] is invalid. Rules are given by
+/// their indices, in the order they were added.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The pattern of the rule is invalid.
+    Invalid { rule: usize, error: RuleError },
+    /// The rule never matches, since every non-empty string it matches is matched by the earlier
+    /// rules that win instead of it, if any.
+    Shadowed { rule: usize, by: Vec<usize> },
+}
+
+impl fmt::Display for BuildError {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        match self {
+            Self::Invalid { rule, error } => {
+                write!(f, "invalid pattern of rule {}: {}", rule, error)
+            }
+            Self::Shadowed { rule, by } => match by.as_slice() {
+                [] => write!(
+                    f,
+                    "rule {} never matches: it matches no non-empty input",
+                    rule
+                ),
+                [earlier] => write!(
+                    f,
+                    "rule {} never matches: it is shadowed by the earlier rule {}",
+                    rule, earlier
+                ),
+                _ => {
+                    let earlier: Vec<_> = by.iter().map(usize::to_string).collect();
+                    write!(
+                        f,
+                        "rule {} never matches: it is shadowed by the earlier rules {}",
+                        rule,
+                        earlier.join(", ")
+                    )
+                }
+            },
+        }
+    }
+}
+
+impl error::Error for BuildError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Invalid { error, .. } => Some(error),
+            Self::Shadowed { .. } => None,
+        }
+    }
+}
+
+/// A lexer built at runtime by a [LexerBuilder].
+pub struct RuntimeLexer<T> {
+    dfa: LexerDFA,
+    /// The rule that wins in each final state of the DFA.
+    accepting: HashMap<usize, usize>,
+    /// The action of each rule, and the DFAs of the parts of the rule if it has trailing context.
+    rules: Vec<(Action<T>, Option<TrailingDFAs>)>,
+    error: ErrorAction<T>,
+    merge_errors: bool,
+}
+
+impl<T> RuntimeLexer<T> {
+    /// Return an iterator over the tokens lexed from the input.
+    #[inline]
+    pub fn stream<I>(&self, input: I) -> LexerStream<T, &Self, I>
+    where
+        I: Iterator<Item = char>,
+    {
+        LexerStream::new(self, input)
+    }
+}
+
+impl<T> Clone for RuntimeLexer<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            dfa: self.dfa.clone(),
+            accepting: self.accepting.clone(),
+            rules: self.rules.clone(),
+            error: self.error.clone(),
+            merge_errors: self.merge_errors,
+        }
+    }
+}
+
+impl<T> fmt::Debug for RuntimeLexer<T> {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        f.debug_struct("RuntimeLexer")
+            .field("dfa", &self.dfa)
+            .field("merge_errors", &self.merge_errors)
+            .finish()
+    }
+}
+
+impl<T> LexerDFAMatcher<T> for RuntimeLexer<T> {
+    type Mode = ();
+    type State = ();
+
+    #[inline]
+    fn tokenize<I: Iterator<Item = char>>(
+        &self,
+        input: &mut LexerInput<I>,
+        _modes: &mut ModeStack<()>,
+        _state: &mut (),
+    ) -> (Option<T>, Match<char>) {
+        let (len, final_state) = match input.longest_match(&self.dfa) {
+            Some(m) => m,
+            None => {
+                let unmatched = input.unmatched(&self.dfa, self.merge_errors);
+                let text: String = unmatched.iter().collect();
+                let m = Match::new(0, unmatched.len(), unmatched);
+                return (Some((self.error)(&text)), m);
+            }
+        };
+
+        // A match of a rule with trailing context is backed up to the end of its leading part.
+        let (action, trailing) = &self.rules[self.accepting[&final_state]];
+        let len = match trailing {
+            Some((r, s)) => input.trailing_context(r, s, len),
+            None => len,
+        };
+
+        let m = Match::new(0, len, input.consume(len));
+        let text: String = m.span.iter().collect();
+        (action(&text), m)
+    }
+}
+
+impl<T> LexerDFAMatcher<T> for &RuntimeLexer<T> {
+    type Mode = ();
+    type State = ();
+
+    #[inline]
+    fn tokenize<I: Iterator<Item = char>>(
+        &self,
+        input: &mut LexerInput<I>,
+        modes: &mut ModeStack<()>,
+        state: &mut (),
+    ) -> (Option<T>, Match<char>) {
+        (*self).tokenize(input, modes, state)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lex(lexer: &RuntimeLexer<String>, input: &str) -> Vec<String> {
+        lexer.stream(input.chars()).map(|item| item.token).collect()
+    }
+
+    fn token(kind: &'static str) -> impl Fn(&str) -> Option<String> + Send + Sync {
+        move |text| Some(format!("{}({})", kind, text))
+    }
+
+    fn builder() -> LexerBuilder<String> {
+        LexerBuilder::new(|text| format!("error({})", text)).rule(r"\s+", |_| None)
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let lexer = builder()
+            .rule("=", token("eq"))
+            .rule("==", token("eqeq"))
+            .rule("[a-z]+", token("ident"))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            lex(&lexer, "a == b = c === dd"),
+            vec![
+                "ident(a)",
+                "eqeq(==)",
+                "ident(b)",
+                "eq(=)",
+                "ident(c)",
+                "eqeq(==)",
+                "eq(=)",
+                "ident(dd)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_priority() {
+        let lexer = builder()
+            .rule("if", token("if"))
+            .rule("[a-z]+", token("ident"))
+            .build()
+            .unwrap();
+        assert_eq!(lex(&lexer, "if iffy"), vec!["if(if)", "ident(iffy)"]);
+
+        // A rule added after one that matches all of its strings never wins.
+        let error = builder()
+            .rule("[a-z]+", token("ident"))
+            .rule("if", token("if"))
+            .build()
+            .unwrap_err();
+        assert_eq!(
+            error,
+            BuildError::Shadowed {
+                rule: 2,
+                by: vec![1]
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "rule 2 never matches: it is shadowed by the earlier rule 1"
+        );
+    }
+
+    #[test]
+    fn test_trailing_context() {
+        let lexer = builder()
+            .rule("[0-9]+", token("int"))
+            .rule(r"[0-9]+/\.\.", token("int"))
+            .rule(r"[0-9]+\.[0-9]*", token("float"))
+            .rule(r"\.\.", token("range"))
+            // Slashes that are quoted, escaped or in classes are matched literally.
+            .rule(r"\Q//\E", token("comment"))
+            .rule(r"[/\/]", token("slash"))
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            lex(&lexer, "1..2 1.5 3. // /"),
+            vec![
+                "int(1)",
+                "range(..)",
+                "int(2)",
+                "float(1.5)",
+                "float(3.)",
+                "comment(//)",
+                "slash(/)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let lexer = builder().rule("[a-z]+", token("ident")).build().unwrap();
+        assert_eq!(
+            lex(&lexer, "a1 2b"),
+            vec!["ident(a)", "error(1)", "error(2)", "ident(b)"]
+        );
+
+        let lexer = builder()
+            .rule("[a-z]+", token("ident"))
+            .with_merged_errors(true)
+            .build()
+            .unwrap();
+        assert_eq!(
+            lex(&lexer, "a12b"),
+            vec!["ident(a)", "error(12)", "ident(b)"]
+        );
+
+        let invalid = |pattern| builder().rule(pattern, token("x")).build().unwrap_err();
+        match invalid("a(b") {
+            BuildError::Invalid {
+                rule: 1,
+                error: RuleError::Parse(e),
+            } => assert_eq!(e.span, 1..2),
+            e => panic!("unexpected error {:?}", e),
+        }
+        let trailing_error = |error| BuildError::Invalid { rule: 1, error };
+        assert_eq!(invalid("a/"), trailing_error(RuleError::TrailingSlash));
+        assert_eq!(invalid("a/b/c"), trailing_error(RuleError::TrailingTwice));
+        assert_eq!(invalid("(a/b)"), trailing_error(RuleError::TrailingGroup));
+        assert_eq!(invalid("a*/b"), trailing_error(RuleError::TrailingEmpty));
+        assert_eq!(
+            invalid("(?:)"),
+            BuildError::Shadowed {
+                rule: 1,
+                by: vec![]
+            }
+        );
+    }
+
+    #[test]
+    fn test_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<LexerBuilder<String>>();
+        assert_send_sync::<RuntimeLexer<String>>();
+    }
+}
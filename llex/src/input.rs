@@ -0,0 +1,126 @@
+use crate::automaton::{self, Automaton};
+
+use std::collections::VecDeque;
+
+/// The input of a lexer, which buffers the chars looked ahead at while matching so that only
+/// the chars of a match are consumed.
+#[derive(Debug, Clone)]
+pub struct LexerInput<I>
+where
+    I: Iterator<Item = char>,
+{
+    inner: I,
+    buffer: VecDeque<char>,
+}
+
+impl<I> LexerInput<I>
+where
+    I: Iterator<Item = char>,
+{
+    #[inline]
+    pub fn new(inner: I) -> Self {
+        Self {
+            inner,
+            buffer: VecDeque::new(),
+        }
+    }
+
+    /// Return the next char without consuming it.
+    #[inline]
+    pub fn peek(&mut self) -> Option<char> {
+        self.peek_nth(0)
+    }
+
+    /// Return the char `n` chars ahead without consuming any.
+    #[inline]
+    pub fn peek_nth(&mut self, n: usize) -> Option<char> {
+        while self.buffer.len() <= n {
+            let c = self.inner.next()?;
+            self.buffer.push_back(c);
+        }
+        Some(self.buffer[n])
+    }
+
+    /// Consume up to `n` chars, returning them.
+    #[inline]
+    pub fn consume(&mut self, n: usize) -> Vec<char> {
+        let mut consumed = Vec::with_capacity(n);
+        for _ in 0..n {
+            match self.next() {
+                Some(c) => consumed.push(c),
+                None => break,
+            }
+        }
+        consumed
+    }
+
+    /// Find the longest non-empty match of a DFA at the start of the input without consuming
+    /// it, returning its length in chars and the final state it ends in.
+    #[inline]
+    pub fn longest_match<A>(&mut self, dfa: &A) -> Option<(usize, usize)>
+    where
+        A: Automaton + ?Sized,
+    {
+        let mut state = dfa.initial_state();
+        let mut last_match = None;
+
+        let mut i = 0;
+        while let Some(c) = self.peek_nth(i) {
+            state = match dfa.next_state(state, c) {
+                Some(s) => s,
+                None => break,
+            };
+            i += 1;
+
+            if dfa.is_final_state(state) {
+                last_match = Some((i, state));
+            }
+        }
+
+        last_match
+    }
+
+    /// Consume the chars that a DFA doesn't match the start of, returning them. This is the next
+    /// char, or if `merge` is true, all of the chars up to the next one that starts a match.
+    #[inline]
+    pub fn unmatched<A>(&mut self, dfa: &A, merge: bool) -> Vec<char>
+    where
+        A: Automaton + ?Sized,
+    {
+        let mut unmatched = self.consume(1);
+        if merge {
+            while self.peek().is_some() && self.longest_match(dfa).is_none() {
+                unmatched.extend(self.next());
+            }
+        }
+        unmatched
+    }
+
+    /// Return the length in chars of the leading part `r` of a match of trailing context `r/s`
+    /// of `len` chars at the start of the input, given the automata of `r` and `s`. The lexer
+    /// backs up to the end of the longest leading part that leaves the rest to `s`.
+    #[inline]
+    pub fn trailing_context<A, B>(&mut self, r: &A, s: &B, len: usize) -> usize
+    where
+        A: Automaton + ?Sized,
+        B: Automaton + ?Sized,
+    {
+        let chars: Vec<_> = (0..len)
+            .filter_map(|i| self.peek_nth(i))
+            .map(|c| (c, 1))
+            .collect();
+        automaton::trailing_context(r, s, &chars)
+    }
+}
+
+impl<I> Iterator for LexerInput<I>
+where
+    I: Iterator<Item = char>,
+{
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        self.buffer.pop_front().or_else(|| self.inner.next())
+    }
+}
@@ -1,9 +1,21 @@
 #![deny(rust_2018_idioms)]
 #![deny(future_incompatible)]
 
+pub mod automaton;
+pub mod builder;
+pub mod input;
+pub mod mode;
+pub mod position;
+pub mod source;
 pub mod stream;
 
+pub use automaton::Automaton;
+pub use builder::{BuildError, LexerBuilder, RuntimeLexer};
+pub use input::LexerInput;
 pub use llex_macro::lexer;
-pub use stream::{LexerItem, LexerStream};
+pub use mode::ModeStack;
+pub use position::Position;
+pub use source::{Source, SourceInput};
+pub use stream::{LexerItem, LexerSourceStream, LexerStream};
 
 pub use regexp2;
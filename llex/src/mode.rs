@@ -0,0 +1,60 @@
+/// The stack of start conditions (modes) of a lexer. Only the rules of the mode on top of the
+/// stack are matched. The bottom of the stack is the initial mode, which is never popped.
+#[derive(Debug, Clone)]
+pub struct ModeStack<M> {
+    stack: Vec<M>,
+}
+
+impl<M: Copy> ModeStack<M> {
+    /// Create a stack containing only the initial mode.
+    #[inline]
+    pub fn new(initial: M) -> Self {
+        Self {
+            stack: vec![initial],
+        }
+    }
+
+    /// Return the current mode.
+    #[inline]
+    pub fn current(&self) -> M {
+        // The stack is never empty.
+        self.stack[self.stack.len() - 1]
+    }
+
+    /// Enter a mode, returning to the current one when it is popped.
+    #[inline]
+    pub fn push(&mut self, mode: M) {
+        self.stack.push(mode);
+    }
+
+    /// Leave the current mode, returning it, and return to the mode it was pushed from. The
+    /// initial mode is never popped, so `None` is returned if it is the current mode.
+    #[inline]
+    pub fn pop(&mut self) -> Option<M> {
+        if self.stack.len() > 1 {
+            self.stack.pop()
+        } else {
+            None
+        }
+    }
+
+    /// Replace the current mode with another, like `BEGIN` in flex.
+    #[inline]
+    pub fn switch(&mut self, mode: M) {
+        let last = self.stack.len() - 1;
+        self.stack[last] = mode;
+    }
+
+    /// Return the number of modes pushed on top of the initial mode.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.stack.len() - 1
+    }
+}
+
+impl<M: Copy + Default> Default for ModeStack<M> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(M::default())
+    }
+}
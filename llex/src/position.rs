@@ -0,0 +1,71 @@
+use std::fmt;
+
+/// A position in the input of a lexer. The line and column are counted from 1, and the column
+/// counts chars rather than bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The number of chars before the position.
+    pub offset: usize,
+    /// The number of UTF-8 bytes before the position.
+    pub byte: usize,
+    /// The line of the position.
+    pub line: usize,
+    /// The column of the position.
+    pub col: usize,
+}
+
+impl Position {
+    #[inline]
+    pub fn new(offset: usize, byte: usize, line: usize, col: usize) -> Self {
+        Self {
+            offset,
+            byte,
+            line,
+            col,
+        }
+    }
+
+    /// Move the position past a char, starting a new line after `\n`.
+    #[inline]
+    pub fn advance(&mut self, c: char) {
+        self.advance_bytes(c, c.len_utf8());
+    }
+
+    /// Move the position past a char that is `len` bytes long in the input.
+    #[inline]
+    pub fn advance_bytes(&mut self, c: char, len: usize) {
+        self.offset += 1;
+        self.byte += len;
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+    }
+
+    /// Return the position after the given chars.
+    #[inline]
+    pub fn after<'a, I>(mut self, chars: I) -> Self
+    where
+        I: IntoIterator<Item = &'a char>,
+    {
+        chars.into_iter().for_each(|&c| self.advance(c));
+        self
+    }
+}
+
+impl Default for Position {
+    /// The position at the start of the input.
+    #[inline]
+    fn default() -> Self {
+        Self::new(0, 0, 1, 1)
+    }
+}
+
+impl fmt::Display for Position {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
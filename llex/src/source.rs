@@ -0,0 +1,234 @@
+use crate::automaton::{self, Automaton};
+
+/// Input that a lexer can match in place, borrowing the text of its tokens from it.
+pub trait Source<'src>: Copy {
+    /// The type of the text of a token.
+    type Text: ?Sized + 'src;
+
+    /// Return the char at a byte offset and its length in bytes, or `None` at the end.
+    fn char_at(self, offset: usize) -> Option<(char, usize)>;
+
+    /// Return the text between two byte offsets.
+    fn text(self, start: usize, end: usize) -> &'src Self::Text;
+}
+
+impl<'src> Source<'src> for &'src str {
+    type Text = str;
+
+    #[inline]
+    fn char_at(self, offset: usize) -> Option<(char, usize)> {
+        self[offset..].chars().next().map(|c| (c, c.len_utf8()))
+    }
+
+    #[inline]
+    fn text(self, start: usize, end: usize) -> &'src str {
+        &self[start..end]
+    }
+}
+
+/// Each byte is matched as the char of the same value, so that bytes outside of ASCII may be
+/// matched by the chars `\x80` to `\xff`.
+impl<'src> Source<'src> for &'src [u8] {
+    type Text = [u8];
+
+    #[inline]
+    fn char_at(self, offset: usize) -> Option<(char, usize)> {
+        self.get(offset).map(|&b| (b as char, 1))
+    }
+
+    #[inline]
+    fn text(self, start: usize, end: usize) -> &'src [u8] {
+        &self[start..end]
+    }
+}
+
+/// A source being lexed, with the byte offset of the next char to be matched.
+#[derive(Debug, Clone)]
+pub struct SourceInput<S> {
+    source: S,
+    offset: usize,
+}
+
+impl<'src, S> SourceInput<S>
+where
+    S: Source<'src>,
+{
+    #[inline]
+    pub fn new(source: S) -> Self {
+        Self { source, offset: 0 }
+    }
+
+    /// Return the source being lexed.
+    #[inline]
+    pub fn source(&self) -> S {
+        self.source
+    }
+
+    /// Return the byte offset of the next char.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Return the next char and its length in bytes without consuming it.
+    #[inline]
+    pub fn peek(&self) -> Option<(char, usize)> {
+        self.source.char_at(self.offset)
+    }
+
+    /// Consume `len` bytes, returning their text.
+    #[inline]
+    pub fn consume(&mut self, len: usize) -> &'src S::Text {
+        let start = self.offset;
+        self.offset += len;
+        self.source.text(start, self.offset)
+    }
+
+    /// Find the longest non-empty match of a DFA at the start of the input without consuming
+    /// it, returning its length in bytes and the final state it ends in.
+    #[inline]
+    pub fn longest_match<A>(&self, dfa: &A) -> Option<(usize, usize)>
+    where
+        A: Automaton + ?Sized,
+    {
+        longest_match_at(self.source, self.offset, dfa)
+    }
+
+    /// Consume the text that a DFA doesn't match the start of, returning it. This is the next
+    /// char, or if `merge` is true, all of the chars up to the next one that starts a match.
+    #[inline]
+    pub fn unmatched<A>(&mut self, dfa: &A, merge: bool) -> &'src S::Text
+    where
+        A: Automaton + ?Sized,
+    {
+        let mut len = 0;
+        while let Some((_, n)) = self.source.char_at(self.offset + len) {
+            len += n;
+            if !merge || longest_match_at(self.source, self.offset + len, dfa).is_some() {
+                break;
+            }
+        }
+        self.consume(len)
+    }
+
+    /// Return the length in bytes of the leading part `r` of a match of trailing context `r/s`
+    /// of `len` bytes at the start of the input, given the automata of `r` and `s`. The lexer
+    /// backs up to the end of the longest leading part that leaves the rest to `s`.
+    #[inline]
+    pub fn trailing_context<A, B>(&self, r: &A, s: &B, len: usize) -> usize
+    where
+        A: Automaton + ?Sized,
+        B: Automaton + ?Sized,
+    {
+        let mut chars = Vec::new();
+        let mut i = 0;
+        while i < len {
+            let (c, n) = self.source.char_at(self.offset + i).unwrap();
+            chars.push((c, n));
+            i += n;
+        }
+        automaton::trailing_context(r, s, &chars)
+    }
+}
+
+#[inline]
+fn longest_match_at<'src, S, A>(source: S, offset: usize, dfa: &A) -> Option<(usize, usize)>
+where
+    S: Source<'src>,
+    A: Automaton + ?Sized,
+{
+    let mut state = dfa.initial_state();
+    let mut last_match = None;
+
+    let mut len = 0;
+    while let Some((c, n)) = source.char_at(offset + len) {
+        state = match dfa.next_state(state, c) {
+            Some(s) => s,
+            None => break,
+        };
+        len += n;
+
+        if dfa.is_final_state(state) {
+            last_match = Some((len, state));
+        }
+    }
+
+    last_match
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An automaton matching `[0-9]+`.
+    struct Digits;
+
+    impl Automaton for Digits {
+        fn initial_state(&self) -> usize {
+            0
+        }
+
+        fn next_state(&self, _: usize, c: char) -> Option<usize> {
+            if c.is_ascii_digit() {
+                Some(1)
+            } else {
+                None
+            }
+        }
+
+        fn is_final_state(&self, state: usize) -> bool {
+            state == 1
+        }
+    }
+
+    #[test]
+    fn test_str_source() {
+        let source = "aé1";
+
+        assert_eq!(source.char_at(0), Some(('a', 1)));
+        assert_eq!(source.char_at(1), Some(('é', 2)));
+        assert_eq!(source.char_at(3), Some(('1', 1)));
+        assert_eq!(source.char_at(4), None);
+        assert_eq!(source.text(1, 3), "é");
+    }
+
+    #[test]
+    fn test_byte_source() {
+        let source: &[u8] = b"a\xff1";
+
+        assert_eq!(source.char_at(0), Some(('a', 1)));
+        assert_eq!(source.char_at(1), Some(('\u{ff}', 1)));
+        assert_eq!(source.char_at(3), None);
+        assert_eq!(source.text(1, 3), b"\xff1");
+    }
+
+    #[test]
+    fn test_str_input() {
+        let mut input = SourceInput::new("12aé3");
+
+        assert_eq!(input.longest_match(&Digits), Some((2, 1)));
+        assert_eq!(input.offset(), 0);
+        assert_eq!(input.consume(2), "12");
+        assert_eq!(input.peek(), Some(('a', 1)));
+        assert_eq!(input.longest_match(&Digits), None);
+        assert_eq!(input.unmatched(&Digits, true), "aé");
+        assert_eq!(input.offset(), 5);
+        assert_eq!(input.longest_match(&Digits), Some((1, 1)));
+        assert_eq!(input.consume(1), "3");
+        assert_eq!(input.peek(), None);
+        assert_eq!(input.unmatched(&Digits, true), "");
+    }
+
+    #[test]
+    fn test_byte_input() {
+        let mut input = SourceInput::new(&b"\xc3\xa91"[..]);
+
+        // Bytes of a multi-byte char are matched one at a time.
+        assert_eq!(input.peek(), Some(('\u{c3}', 1)));
+        assert_eq!(input.unmatched(&Digits, false), b"\xc3");
+        assert_eq!(input.unmatched(&Digits, true), b"\xa9");
+        assert_eq!(input.longest_match(&Digits), Some((1, 1)));
+        assert_eq!(input.consume(1), b"1");
+        assert_eq!(input.source(), &b"\xc3\xa91"[..]);
+    }
+}
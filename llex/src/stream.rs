@@ -1,4 +1,9 @@
-use std::iter::Peekable;
+use crate::input::LexerInput;
+use crate::mode::ModeStack;
+use crate::position::Position;
+use crate::source::{Source, SourceInput};
+
+use std::marker::PhantomData;
 
 use regexp2::{
     automata::{Match, DFA},
@@ -8,22 +13,69 @@ use regexp2::{
 pub type LexerDFA = DFA<CharClass>;
 
 pub trait LexerDFAMatcher<T>: Clone {
+    /// The start conditions of the lexer, the default of which is the initial one. Lexers without
+    /// start conditions use `()`.
+    type Mode: Copy + Default;
+
+    /// The state of the user that actions have access to. Lexers without state use `()`.
+    type State: Default;
+
+    /// Match the next token, consuming the chars of the match from the input. If nothing
+    /// matches, the unmatched chars are consumed and the error token is returned. The match is
+    /// returned even if its action produced no token, so that the position can be advanced past
+    /// it. Its start and end are relative to the chars consumed.
     fn tokenize<I: Iterator<Item = char>>(
         &self,
-        input: &mut Peekable<I>,
-    ) -> Option<(T, Match<char>)>;
+        input: &mut LexerInput<I>,
+        modes: &mut ModeStack<Self::Mode>,
+        state: &mut Self::State,
+    ) -> (Option<T>, Match<char>);
+}
+
+/// A matcher of tokens directly in a source, the text of which is borrowed from it.
+pub trait LexerSourceMatcher<'src, S, T>: Clone
+where
+    S: Source<'src>,
+{
+    /// The start conditions of the lexer, the default of which is the initial one. Lexers without
+    /// start conditions use `()`.
+    type Mode: Copy + Default;
+
+    /// The state of the user that actions have access to. Lexers without state use `()`.
+    type State: Default;
+
+    /// Match the next token, consuming the text of the match from the input. If nothing
+    /// matches, the unmatched text is consumed and the error token is returned. The length in
+    /// bytes of the text consumed is returned even if its action produced no token.
+    fn tokenize(
+        &self,
+        input: &mut SourceInput<S>,
+        modes: &mut ModeStack<Self::Mode>,
+        state: &mut Self::State,
+    ) -> (Option<T>, usize);
 }
 
 #[derive(Debug, Clone)]
 pub struct LexerItem<T> {
     pub token: T,
+    /// The match of the token, with its start and end in chars. The span of chars is empty for
+    /// tokens matched in a source, the text of which is borrowed instead.
     pub m: Match<char>,
+    /// The position of the first char of the token.
+    pub start: Position,
+    /// The position after the last char of the token.
+    pub end: Position,
 }
 
 impl<T> LexerItem<T> {
     #[inline]
-    pub fn new(token: T, m: Match<char>) -> Self {
-        Self { token, m }
+    pub fn new(token: T, m: Match<char>, start: Position, end: Position) -> Self {
+        Self {
+            token,
+            m,
+            start,
+            end,
+        }
     }
 }
 
@@ -33,10 +85,12 @@ where
     M: LexerDFAMatcher<T>,
     I: Iterator<Item = char>,
 {
-    pub input: Peekable<I>,
+    pub input: LexerInput<I>,
     matcher: M,
-    current_item: Option<LexerItem<T>>,
-    offset: usize,
+    modes: ModeStack<M::Mode>,
+    state: M::State,
+    position: Position,
+    _token: PhantomData<fn() -> T>,
 }
 
 impl<T, M, I> LexerStream<T, M, I>
@@ -48,14 +102,54 @@ where
     pub fn new(matcher: M, input: I) -> Self {
         Self {
             matcher,
-            current_item: None,
-            input: input.peekable(),
-            offset: 0,
+            modes: ModeStack::default(),
+            state: M::State::default(),
+            input: LexerInput::new(input),
+            position: Position::default(),
+            _token: PhantomData,
         }
     }
+
+    /// Return the position of the next char of the input.
+    #[inline]
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Return the state of the user.
+    #[inline]
+    pub fn state(&self) -> &M::State {
+        &self.state
+    }
+
+    /// Return the state of the user, so that it can be changed from outside of the lexer's
+    /// actions.
+    #[inline]
+    pub fn state_mut(&mut self) -> &mut M::State {
+        &mut self.state
+    }
+
+    /// Consume the stream, returning the state of the user.
+    #[inline]
+    pub fn into_state(self) -> M::State {
+        self.state
+    }
+
+    /// Return the stack of start conditions, the top of which selects the rules matched next.
+    #[inline]
+    pub fn modes(&self) -> &ModeStack<M::Mode> {
+        &self.modes
+    }
+
+    /// Return the stack of start conditions, so that the mode can be changed from outside of the
+    /// lexer's actions.
+    #[inline]
+    pub fn modes_mut(&mut self) -> &mut ModeStack<M::Mode> {
+        &mut self.modes
+    }
 }
 
-impl<'a, T, M, I> Iterator for LexerStream<T, M, I>
+impl<T, M, I> Iterator for LexerStream<T, M, I>
 where
     M: LexerDFAMatcher<T>,
     I: Iterator<Item = char>,
@@ -64,23 +158,243 @@ where
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        self.input.peek()?;
-
-        let token_op = self.matcher.tokenize(&mut self.input);
-        match token_op {
-            // If a token was returned, return the token and the remaining input.
-            Some((t, mut m)) => {
-                m.start += self.offset;
-                m.end += self.offset;
-                self.offset += m.end - m.start;
-                Some(LexerItem::new(t, m))
+        // Skip matches whose actions produce no token until one does.
+        while self.input.peek().is_some() {
+            let (token_op, mut m) =
+                self.matcher
+                    .tokenize(&mut self.input, &mut self.modes, &mut self.state);
+
+            let start = self.position;
+            let end = start.after(&m.span);
+            self.position = end;
+
+            if let Some(t) = token_op {
+                m.start = start.offset;
+                m.end = end.offset;
+                return Some(LexerItem::new(t, m, start, end));
+            }
+        }
+        None
+    }
+}
+
+/// A stream of tokens matched directly in a source, without copying their text.
+#[derive(Debug)]
+pub struct LexerSourceStream<'src, S, T, M>
+where
+    S: Source<'src>,
+    M: LexerSourceMatcher<'src, S, T>,
+{
+    pub input: SourceInput<S>,
+    matcher: M,
+    modes: ModeStack<M::Mode>,
+    state: M::State,
+    position: Position,
+    _token: PhantomData<fn() -> T>,
+}
+
+impl<'src, S, T, M> LexerSourceStream<'src, S, T, M>
+where
+    S: Source<'src>,
+    M: LexerSourceMatcher<'src, S, T>,
+{
+    #[inline]
+    pub fn new(matcher: M, source: S) -> Self {
+        Self {
+            input: SourceInput::new(source),
+            matcher,
+            modes: ModeStack::default(),
+            state: M::State::default(),
+            position: Position::default(),
+            _token: PhantomData,
+        }
+    }
+
+    /// Return the stack of start conditions, the top of which selects the rules matched next.
+    #[inline]
+    pub fn modes(&self) -> &ModeStack<M::Mode> {
+        &self.modes
+    }
+
+    /// Return the stack of start conditions, so that the mode can be changed from outside of the
+    /// lexer's actions.
+    #[inline]
+    pub fn modes_mut(&mut self) -> &mut ModeStack<M::Mode> {
+        &mut self.modes
+    }
+
+    /// Return the position of the next char of the input.
+    #[inline]
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// Return the state of the user.
+    #[inline]
+    pub fn state(&self) -> &M::State {
+        &self.state
+    }
+
+    /// Return the state of the user, so that it can be changed from outside of the lexer's
+    /// actions.
+    #[inline]
+    pub fn state_mut(&mut self) -> &mut M::State {
+        &mut self.state
+    }
+
+    /// Consume the stream, returning the state of the user.
+    #[inline]
+    pub fn into_state(self) -> M::State {
+        self.state
+    }
+}
+
+impl<'src, S, T, M> Iterator for LexerSourceStream<'src, S, T, M>
+where
+    S: Source<'src>,
+    M: LexerSourceMatcher<'src, S, T>,
+{
+    type Item = LexerItem<T>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // Skip matches whose actions produce no token until one does.
+        while self.input.peek().is_some() {
+            let offset = self.input.offset();
+            let (token_op, len) =
+                self.matcher
+                    .tokenize(&mut self.input, &mut self.modes, &mut self.state);
+
+            let start = self.position;
+            let mut end = start;
+            let mut i = offset;
+            while i < offset + len {
+                let (c, n) = self.input.source().char_at(i).unwrap();
+                end.advance_bytes(c, n);
+                i += n;
+            }
+            self.position = end;
+
+            if let Some(t) = token_op {
+                let m = Match::new(start.offset, end.offset, Vec::new());
+                return Some(LexerItem::new(t, m, start, end));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::automaton::Automaton;
+
+    /// An automaton matching `[a-z]+`.
+    struct Letters;
+
+    impl Automaton for Letters {
+        fn initial_state(&self) -> usize {
+            0
+        }
+
+        fn next_state(&self, _: usize, c: char) -> Option<usize> {
+            if c.is_ascii_lowercase() {
+                Some(1)
+            } else {
+                None
             }
-            // If no token was returned, one input symbol should be consumed and the process
-            // restarted.
-            None => {
-                self.offset += 1;
-                self.next()
+        }
+
+        fn is_final_state(&self, state: usize) -> bool {
+            state == 1
+        }
+    }
+
+    /// A matcher of words, the tokens of which are their numbers counted in the user state.
+    #[derive(Clone)]
+    struct Words;
+
+    impl LexerDFAMatcher<usize> for Words {
+        type Mode = ();
+        type State = usize;
+
+        fn tokenize<I: Iterator<Item = char>>(
+            &self,
+            input: &mut LexerInput<I>,
+            _: &mut ModeStack<()>,
+            count: &mut usize,
+        ) -> (Option<usize>, Match<char>) {
+            match input.longest_match(&Letters) {
+                Some((len, _)) => {
+                    *count += 1;
+                    (Some(*count), Match::new(0, len, input.consume(len)))
+                }
+                None => {
+                    let span = input.unmatched(&Letters, true);
+                    (None, Match::new(0, span.len(), span))
+                }
             }
         }
     }
+
+    impl<'src, S> LexerSourceMatcher<'src, S, usize> for Words
+    where
+        S: Source<'src>,
+    {
+        type Mode = ();
+        type State = usize;
+
+        fn tokenize(
+            &self,
+            input: &mut SourceInput<S>,
+            _: &mut ModeStack<()>,
+            count: &mut usize,
+        ) -> (Option<usize>, usize) {
+            let start = input.offset();
+            match input.longest_match(&Letters) {
+                Some((len, _)) => {
+                    input.consume(len);
+                    *count += 1;
+                    (Some(*count), len)
+                }
+                None => {
+                    input.unmatched(&Letters, true);
+                    (None, input.offset() - start)
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_state() {
+        let mut stream = LexerStream::new(Words, "ab, cd ef".chars());
+
+        assert_eq!(*stream.state(), 0);
+        assert_eq!(stream.next().map(|item| item.token), Some(1));
+        assert_eq!(*stream.state(), 1);
+
+        // The state may be changed between tokens.
+        *stream.state_mut() = 10;
+        assert_eq!(stream.next().map(|item| item.token), Some(11));
+        assert_eq!(stream.next().map(|item| item.token), Some(12));
+        assert_eq!(stream.next().map(|item| item.token), None);
+        assert_eq!(stream.into_state(), 12);
+    }
+
+    #[test]
+    fn test_source_state() {
+        let mut stream = LexerSourceStream::new(Words, "ab é cd");
+
+        let item = stream.next().unwrap();
+        assert_eq!((item.token, item.start.byte, item.end.byte), (1, 0, 2));
+        *stream.state_mut() += 1;
+        let item = stream.next().unwrap();
+        assert_eq!((item.token, item.start.byte, item.end.byte), (3, 6, 8));
+        assert!(stream.next().is_none());
+        assert_eq!(stream.into_state(), 3);
+
+        let stream = LexerSourceStream::new(Words, &b"ab \xff cd ef"[..]);
+        let tokens: Vec<_> = stream.map(|item| item.token).collect();
+        assert_eq!(tokens, vec![1, 2, 3]);
+    }
 }
@@ -0,0 +1,51 @@
+use llex::lexer;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Newline,
+    Error,
+}
+
+lexer! {
+    struct Lexer;
+    fn stream;
+    enum Mode { Main, Str }
+    (text, modes) -> Token, Token::Error;
+
+    r"[ \t]" => None,
+    r"\w+" => Some(Token::Ident(text.to_string())),
+    "\"" => {
+        modes.push(Mode::Str);
+        None
+    }
+
+    <Str> "\"" => {
+        modes.pop();
+        None
+    }
+    <Str> r"[\s\S]" => None,
+
+    // Newlines within strings are matched by the earlier rule of `Str`, so this rule is only
+    // matched in `Main`.
+    <*> r"\n" => Some(Token::Newline),
+}
+
+#[test]
+fn test_shadowed_in_one_mode() {
+    let lexer = Lexer::new();
+    let tokens: Vec<_> = lexer
+        .stream("a\n\"b\nc\"\nd".chars())
+        .map(|item| item.token)
+        .collect();
+
+    assert_eq!(
+        tokens,
+        vec![
+            Token::Ident("a".to_string()),
+            Token::Newline,
+            Token::Newline,
+            Token::Ident("d".to_string()),
+        ]
+    );
+}
@@ -220,6 +220,7 @@ impl Spannable for IfBranch {
 
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde-impl", derive(Serialize, Deserialize))]
+#[allow(clippy::large_enum_variant)]
 pub enum ElseBranch {
     If {
         branch: IfBranch,
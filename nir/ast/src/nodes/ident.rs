@@ -1,6 +1,6 @@
 use super::keywords::DoubleColon;
 use super::punctuated::Punctuated;
-use super::{Span, Spannable, Spanned};
+use super::{Position, Span, Spannable, Spanned};
 
 #[cfg(feature = "serde-impl")]
 use serde::{Deserialize, Serialize};
@@ -40,7 +40,7 @@ impl Spannable for Path {
             .first()
             .map(|item| item.span().start)
             .map(|start| (start, self.segments.items.last().unwrap().span().end))
-            .unwrap_or_else(|| (0, 0));
+            .unwrap_or_else(|| (Position::default(), Position::default()));
         Span::new(start, end)
     }
 }
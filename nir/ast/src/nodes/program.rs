@@ -1,4 +1,4 @@
-use super::{Function, Span, Spannable, Struct};
+use super::{Function, Position, Span, Spannable, Struct};
 
 #[cfg(feature = "serde-impl")]
 use serde::{Deserialize, Serialize};
@@ -31,13 +31,14 @@ impl Spannable for Program {
             .first()
             .map(|item| item.span().start)
             .map(|start| (start, self.items.last().unwrap().span().end))
-            .unwrap_or_else(|| (0, 0));
+            .unwrap_or_else(|| (Position::default(), Position::default()));
         Span::new(start, end)
     }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serde-impl", derive(Serialize, Deserialize))]
+#[allow(clippy::large_enum_variant)]
 pub enum Item {
     Struct(Struct),
     Function(Function),
@@ -1,4 +1,4 @@
-use super::{Span, Spannable};
+use super::{Position, Span, Spannable};
 
 #[cfg(feature = "serde-impl")]
 use serde::{Deserialize, Serialize};
@@ -39,7 +39,7 @@ where
             .first()
             .map(|item| item.span().start)
             .map(|start| (start, self.items.last().unwrap().span().end))
-            .unwrap_or_else(|| (0, 0));
+            .unwrap_or_else(|| (Position::default(), Position::default()));
         Span::new(start, end)
     }
 }
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[cfg(feature = "serde-impl")]
 use serde::{Deserialize, Serialize};
 
@@ -31,13 +33,61 @@ impl<T> Spannable for Spanned<T> {
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(feature = "serde-impl", derive(Serialize, Deserialize))]
 pub struct Span {
-    pub start: usize,
-    pub end: usize,
+    /// The position of the first char.
+    pub start: Position,
+    /// The position after the last char.
+    pub end: Position,
 }
 
 impl Span {
     #[inline]
-    pub fn new(start: usize, end: usize) -> Self {
+    pub fn new(start: Position, end: Position) -> Self {
         Self { start, end }
     }
 }
+
+/// A position in the source, with the line and column counted from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde-impl", derive(Serialize, Deserialize))]
+pub struct Position {
+    /// The number of chars before the position.
+    pub offset: usize,
+    /// The number of UTF-8 bytes before the position.
+    pub byte: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl Position {
+    #[inline]
+    pub fn new(offset: usize, byte: usize, line: usize, col: usize) -> Self {
+        Self {
+            offset,
+            byte,
+            line,
+            col,
+        }
+    }
+}
+
+impl Default for Position {
+    /// The position at the start of the source.
+    #[inline]
+    fn default() -> Self {
+        Self::new(0, 0, 1, 1)
+    }
+}
+
+impl From<lexer::Position> for Position {
+    #[inline]
+    fn from(pos: lexer::Position) -> Self {
+        Self::new(pos.offset, pos.byte, pos.line, pos.col)
+    }
+}
+
+impl fmt::Display for Position {
+    #[inline]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
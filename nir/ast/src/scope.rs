@@ -69,6 +69,7 @@ impl Default for Scope {
 }
 
 pub trait AsSymbolKey {
+    #[allow(clippy::wrong_self_convention)]
     fn as_string(self) -> String;
 }
 
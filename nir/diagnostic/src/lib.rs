@@ -1,48 +1,65 @@
 use std::io;
 
+use ast::Position;
 use serde::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct Diagnostic {
     category: String,
     message: String,
-    start: usize,
-    end: usize,
+    /// The name of the file the diagnostic is in, set when it is emitted.
+    file: Option<String>,
+    start: Position,
+    end: Position,
 }
 
 impl Diagnostic {
-    pub const fn new(category: String, message: String, start: usize, end: usize) -> Self {
+    pub const fn new(category: String, message: String, start: Position, end: Position) -> Self {
         Self {
             category,
             message,
+            file: None,
             start,
             end,
         }
     }
+
+    /// Return the location of the start of the diagnostic, as `file:line:col` if the file is
+    /// known and as `line:col` otherwise.
+    #[inline]
+    pub fn location(&self) -> String {
+        match &self.file {
+            Some(file) => format!("{}:{}", file, self.start),
+            None => self.start.to_string(),
+        }
+    }
 }
 
 /// Trait implemented by errors for diagnostic messages.
 pub trait AsDiagnostic {
     fn as_diagnostic(val: &Self) -> Vec<Diagnostic>;
 
+    /// Write the diagnostics of an error, reporting them as in the given file if one is named.
     fn emit_diagnostic<W>(
         val: &Self,
         w: &mut W,
+        file: Option<&str>,
         format: &DiagnosticFormat,
     ) -> Result<(), DiagnosticEmitError>
     where
         W: io::Write,
     {
         let diagnostics = Self::as_diagnostic(val);
-        for d in diagnostics {
+        for mut d in diagnostics {
+            d.file = file.map(|f| f.to_string());
             match format {
                 DiagnosticFormat::Json => writeln!(w, "{}", serde_json::to_string(&d)?)?,
                 DiagnosticFormat::Text => {
-                    writeln!(w, "{}:{}: [{}] {}", d.start, d.end, d.category, d.message)?
+                    writeln!(w, "{}: [{}] {}", d.location(), d.category, d.message)?
                 }
                 DiagnosticFormat::Rich => {
                     writeln!(w, "{} error: {}", d.category, d.message)?;
-                    writeln!(w, "at position {}:{}", d.start, d.end)?
+                    writeln!(w, "  --> {}", d.location())?
                 }
             };
             writeln!(w)?;
@@ -21,9 +21,14 @@ macro_rules! literal {
 llex::lexer! {
     pub struct Lexer;
     pub fn stream;
-    (text) -> Token, Token::Unknown;
+    pub enum Mode { Main, Comment }
+    (text, modes) -> Token, Token::Unknown(text.to_string()), merge;
 
     r"\s" => None,
+    r"\/\*" => {
+        modes.push(Mode::Comment);
+        None
+    }
 
     "pub" => reserved!(Pub),
     "using" => reserved!(Using),
@@ -58,6 +63,7 @@ llex::lexer! {
     r"\(" => reserved!(LParen),
     r"\)" => reserved!(RParen),
 
+    r"\.\." => reserved!(DoubleDot),
     r"\." => reserved!(Dot),
     r"," => reserved!(Comma),
     r"::" => reserved!(DoubleColon),
@@ -69,13 +75,13 @@ llex::lexer! {
     r"=" => reserved!(Equ),
     r">=" => reserved!(GtEqu),
     r">" => reserved!(Gt),
-    r"<=" => reserved!(GtEqu),
+    r"<=" => reserved!(LtEqu),
     r"<" => reserved!(Lt),
 
     r"\+" => reserved!(Plus),
     r"-" => reserved!(Minus),
     r"\*" => reserved!(Star),
-    r"/" => reserved!(Slash),
+    r"\/" => reserved!(Slash),
 
     r"!" => reserved!(Exclamation),
     r"&&" => reserved!(DoubleAmp),
@@ -86,12 +92,7 @@ llex::lexer! {
     "true" => literal!(Literal::Boolean(true)),
     "false" => literal!(Literal::Boolean(false)),
 
-    r"[A-Za-z_][A-Za-z0-9_]*" => Some(Token::Ident(text.to_string())),
-    r#""[^"]*""# => {
-        let text = text.strip_prefix("\"").unwrap();
-        let text = text.strip_suffix("\"").unwrap();
-        literal!(Literal::Str(text.to_string()))
-    },
+    r"[\p{XID_Start}_]\p{XID_Continue}*" => Some(Token::Ident(text.to_string())),
 
     r"[0-9]+" => {
         let n = text.parse().unwrap();
@@ -101,6 +102,51 @@ llex::lexer! {
         let f = text.parse().unwrap();
         literal!(Literal::Float(f))
     }
+
+    r#""([^"\\]|\\[nrt0"\\])*""# => {
+        let text = text.strip_prefix('"').unwrap();
+        let text = text.strip_suffix('"').unwrap();
+        literal!(Literal::Str(unescape(text)))
+    }
+    // A string literal with an invalid escape or without a closing quote.
+    r#""([^"\\]|\\[\s\S])*\\?"?"# => Some(Token::Unknown(text.to_string())),
+
+    // Block comments, which may be nested.
+    <Comment> {
+        r"\/\*" => {
+            modes.push(Mode::Comment);
+            None
+        }
+        r"\*\/" => {
+            modes.pop();
+            None
+        }
+        r"[^*/]+|[*/]" => None,
+    }
+}
+
+/// Replace the escape sequences in the contents of a string literal with the chars they stand for.
+#[inline]
+fn unescape(text: &str) -> String {
+    let mut chars = text.chars();
+    let mut unescaped = String::with_capacity(text.len());
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        // The lexer only accepts valid escapes.
+        unescaped.push(match chars.next() {
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('0') => '\0',
+            Some(c) => c,
+            None => unreachable!(),
+        });
+    }
+    unescaped
 }
 
 #[cfg(test)]
@@ -120,6 +166,138 @@ mod test {
         assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::In)));
     }
 
+    #[test]
+    fn test_idents() {
+        let mut tokens = lex("foo _bar baz_9 ñandú 变量 Ωmega fn_x");
+
+        assert_eq!(tokens.next(), Some(Token::Ident("foo".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("_bar".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("baz_9".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("ñandú".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("变量".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("Ωmega".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("fn_x".to_string())));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_operators() {
+        let mut tokens = lex("<= < >= > != = && & || |");
+
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::LtEqu)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Lt)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::GtEqu)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Gt)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Nequ)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Equ)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::DoubleAmp)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Amp)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::DoubleBar)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Bar)));
+        assert_eq!(tokens.next(), None);
+        assert_eq!(Reserved::LtEqu.to_string(), "<=");
+    }
+
+    #[test]
+    fn test_numbers() {
+        let mut tokens = lex("12 3.25 4. 0..10 1...2");
+
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(12))));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Float(3.25))));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(4))));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Dot)));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(0))));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::DoubleDot)));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(10))));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(1))));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::DoubleDot)));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Dot)));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(2))));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_strings() {
+        let mut tokens = lex(r#"let s = "a \"b\"\n\\"; "x\q" "open"#);
+
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Let)));
+        assert_eq!(tokens.next(), Some(Token::Ident("s".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Equ)));
+        assert_eq!(
+            tokens.next(),
+            Some(Token::Literal(Literal::Str("a \"b\"\n\\".to_string())))
+        );
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Semicolon)));
+        assert_eq!(tokens.next(), Some(Token::Unknown(r#""x\q""#.to_string())));
+        assert_eq!(tokens.next(), Some(Token::Unknown(r#""open"#.to_string())));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_comments() {
+        let mut tokens = lex("a /* b /* c */ d */ e / f /* * / **/ g");
+
+        assert_eq!(tokens.next(), Some(Token::Ident("a".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("e".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Slash)));
+        assert_eq!(tokens.next(), Some(Token::Ident("f".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Ident("g".to_string())));
+        assert_eq!(tokens.next(), None);
+    }
+
+    #[test]
+    fn test_positions() {
+        let lexer = Lexer::new();
+        let input = "let ñ = 1;\n/* a\n b */ \"é\" x";
+        let positions: Vec<_> = lexer
+            .stream(input.chars())
+            .map(|item| (item.start, item.end))
+            .collect();
+
+        let pos = |offset, byte, line, col| llex::Position::new(offset, byte, line, col);
+        assert_eq!(positions[0], (pos(0, 0, 1, 1), pos(3, 3, 1, 4)));
+        assert_eq!(positions[1], (pos(4, 4, 1, 5), pos(5, 6, 1, 6)));
+        assert_eq!(positions[4], (pos(9, 10, 1, 10), pos(10, 11, 1, 11)));
+        assert_eq!(positions[5], (pos(22, 23, 3, 7), pos(25, 27, 3, 10)));
+        assert_eq!(positions[6], (pos(26, 28, 3, 11), pos(27, 29, 3, 12)));
+        assert_eq!(positions.len(), 7);
+    }
+
+    #[test]
+    fn test_unknown() {
+        let lexer = Lexer::new();
+        let items: Vec<_> = lexer
+            .stream("a $€ b\n@".chars())
+            .map(|item| (item.token, item.start.offset, item.end.offset))
+            .collect();
+
+        // Consecutive unmatched chars are merged into one token.
+        assert_eq!(
+            items,
+            vec![
+                (Token::Ident("a".to_string()), 0, 1),
+                (Token::Unknown("$€".to_string()), 2, 4),
+                (Token::Ident("b".to_string()), 5, 6),
+                (Token::Unknown("@".to_string()), 7, 8),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slashes() {
+        // A slash is escaped in its rule, so it's matched literally rather than starting trailing
+        // context.
+        let mut tokens = lex("a/b 1 / 2");
+
+        assert_eq!(tokens.next(), Some(Token::Ident("a".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Slash)));
+        assert_eq!(tokens.next(), Some(Token::Ident("b".to_string())));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(1))));
+        assert_eq!(tokens.next(), Some(Token::Reserved(Reserved::Slash)));
+        assert_eq!(tokens.next(), Some(Token::Literal(Literal::Integer(2))));
+        assert_eq!(tokens.next(), None);
+    }
+
     fn lex(input: &str) -> impl Iterator<Item = Token> {
         let lexer = Lexer::new();
         let tokens: Vec<_> = lexer.stream(input.chars()).map(|item| item.token).collect();
@@ -1,7 +1,8 @@
 mod lexer;
 mod reserved;
 
-pub use lexer::Lexer;
+pub use lexer::{Lexer, Mode};
+pub use llex::Position;
 pub use reserved::Reserved;
 
 use std::fmt;
@@ -22,7 +23,8 @@ pub enum Token {
     Type(Type),
     Reserved(Reserved),
 
-    Unknown,
+    /// Token for input that couldn't be lexed, with its text.
+    Unknown(String),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -64,8 +66,7 @@ impl fmt::Display for Token {
             Token::Literal(literal) => write!(f, "{}", literal),
             Token::Type(ty) => write!(f, "{}", ty),
             Token::Reserved(reserved) => write!(f, "{}", reserved),
-            // TODO: figure out what to do here
-            Token::Unknown => write!(f, ""),
+            Token::Unknown(text) => write!(f, "{}", text),
         }
     }
 }
@@ -114,6 +114,7 @@ define_reserved! {
     RBrace => "}}",
 
     Dot => ".",
+    DoubleDot => "..",
     Comma => ",",
     Colon => ":",
     DoubleColon => "::",
@@ -124,7 +125,7 @@ define_reserved! {
     Equ => "=",
     GtEqu => ">=",
     Gt => ">",
-    LtEqu => ">=",
+    LtEqu => "<=",
     Lt => "<",
 
     Plus => "+",
@@ -26,8 +26,8 @@ pub enum ParseError {
     UnexpectedToken(Symbol, Vec<ExpectedToken>),
     #[error("unexpected end-of-file")]
     UnexpectedEof(Vec<ExpectedToken>),
-    #[error("lexer error")]
-    LexerError,
+    #[error("unknown input {:?} at position {}", .0.inner(), .0.span().start)]
+    LexerError(Symbol),
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -1,7 +1,7 @@
 use crate::error::{ExpectedToken, ParseError};
 use crate::Result;
 
-use ast::{Program, Spanned};
+use ast::{Position, Program, Spanned};
 use itertools::{Itertools, MultiPeek};
 use lexer::{types as ttypes, Token};
 
@@ -55,6 +55,13 @@ impl Parser {
     }
 }
 
+impl Default for Parser {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct ParseInput<I>
 where
@@ -63,7 +70,7 @@ where
     pub errors: Vec<ParseError>,
 
     inner: MultiPeek<I>,
-    last_pos: usize,
+    last_pos: Position,
 }
 
 impl<I> ParseInput<I>
@@ -75,12 +82,19 @@ where
         Self {
             inner: inner.multipeek(),
             errors: Vec::new(),
-            last_pos: 0,
+            last_pos: Position::default(),
         }
     }
 
     #[inline]
     pub fn error(&mut self, error: ParseError) {
+        // Unknown tokens are input the lexer couldn't match rather than misplaced tokens.
+        let error = match error {
+            ParseError::UnexpectedToken(sy, _) if matches!(sy.0, Token::Unknown(_)) => {
+                ParseError::LexerError(sy)
+            }
+            error => error,
+        };
         self.errors.push(error)
     }
 
@@ -91,11 +105,11 @@ where
 
     #[inline]
     pub fn unexpected_token(&mut self, sy: Symbol, expected: Vec<ExpectedToken>) {
-        self.errors.push(ParseError::UnexpectedToken(sy, expected))
+        self.error(ParseError::UnexpectedToken(sy, expected))
     }
 
     #[inline]
-    pub fn last_pos(&self) -> usize {
+    pub fn last_pos(&self) -> Position {
         self.last_pos
     }
 
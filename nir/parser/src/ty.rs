@@ -68,7 +68,7 @@ fn from_lexer_type(ty: lexer::Type, span: Span) -> Type {
                 span,
             })
         };
-    };
+    }
 
     match ty {
         lexer::Type::Str => Type::Array(Box::new(ArrayType {
@@ -1,27 +1,24 @@
 use std::env;
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, Read};
 
 use nir::Compiler;
-use utf8_chars::BufReadCharsExt;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let compiler = Compiler::new();
 
     let stdin = io::stdin();
-    let mut input: Box<dyn BufRead> = match env::args().nth(1) {
-        Some(filepath) => {
-            let file = File::open(filepath)?;
-            Box::new(BufReader::new(file))
-        }
+    let filepath = env::args().nth(1);
+    let mut input: Box<dyn Read> = match &filepath {
+        Some(filepath) => Box::new(File::open(filepath)?),
         None => Box::new(stdin.lock()),
     };
 
-    // TODO: find better way to do this
-    let chars = input.chars().filter_map(|c| c.ok());
+    let mut source = String::new();
+    input.read_to_string(&mut source)?;
 
     let stderr = io::stderr();
-    compiler.parse_emit(&mut stderr.lock(), chars)?;
+    compiler.parse_emit(&mut stderr.lock(), filepath.as_deref(), source.chars())?;
 
     Ok(())
 }
@@ -24,10 +24,12 @@ impl Compiler {
         }
     }
 
+    /// Parse the input, emitting any errors as diagnostics in the named file.
     #[inline]
     pub fn parse_emit<W>(
         &self,
         w: &mut W,
+        file: Option<&str>,
         input: impl IntoIterator<Item = char>,
     ) -> Result<(), DiagnosticEmitError>
     where
@@ -38,7 +40,7 @@ impl Compiler {
                 println!("{:#?}", ast);
             }
             Err(err) => {
-                self.emit_errors(w, err)?;
+                self.emit_errors(w, file, err)?;
             }
         }
 
@@ -50,7 +52,7 @@ impl Compiler {
         let tokens = self
             .lexer
             .stream(input.into_iter())
-            .map(|item| Spanned::new(item.token, Span::new(item.m.start, item.m.end - 1)));
+            .map(|item| Spanned::new(item.token, Span::new(item.start.into(), item.end.into())));
 
         match self.parser.parse(tokens) {
             Ok(program) => Ok(program),
@@ -59,11 +61,16 @@ impl Compiler {
     }
 
     #[inline]
-    pub fn emit_errors<W>(&self, w: &mut W, error: CompileError) -> Result<(), DiagnosticEmitError>
+    pub fn emit_errors<W>(
+        &self,
+        w: &mut W,
+        file: Option<&str>,
+        error: CompileError,
+    ) -> Result<(), DiagnosticEmitError>
     where
         W: io::Write,
     {
-        CompileError::emit_diagnostic(&error, w, &DiagnosticFormat::Rich)
+        CompileError::emit_diagnostic(&error, w, file, &DiagnosticFormat::Rich)
     }
 }
 
@@ -1,6 +1,6 @@
 use std::fmt;
 
-use ast::Spannable;
+use ast::{Position, Span, Spannable};
 use diagnostic::{AsDiagnostic, Diagnostic};
 use parser::{ExpectedToken, ParseError};
 use serde::Serialize;
@@ -23,50 +23,44 @@ impl AsDiagnostic for CompileError {
     fn as_diagnostic(val: &Self) -> Vec<Diagnostic> {
         const CATEGORY: &str = "parsing";
         macro_rules! diagnostic {
-            ($start:expr, $end:expr, $fmtstr:expr, $($fmtarg:expr),*) => {
-                Diagnostic::new(CATEGORY.to_string(), format!($fmtstr, $($fmtarg),*), $start, $end)
-            };
-        };
+            ($span:expr, $fmtstr:expr, $($fmtarg:expr),*) => {{
+                let (start, end) = position($span);
+                Diagnostic::new(CATEGORY.to_string(), format!($fmtstr, $($fmtarg),*), start, end)
+            }};
+        }
 
         val.parse
             .iter()
             .map(|err| match err {
                 ParseError::NoMainFunction => {
-                    diagnostic!(0, 0, "required main() function not defined",)
+                    diagnostic!(None, "required main() function not defined",)
                 }
                 ParseError::UndeclaredVariable(ident) => {
-                    let span = ident.span();
                     diagnostic!(
-                        span.start,
-                        span.end,
+                        Some(&ident.span()),
                         "used an undeclared variable '{}'",
                         ident.name_str()
                     )
                 }
                 ParseError::DuplicateIdent(ident) => {
-                    let span = ident.span();
                     diagnostic!(
-                        span.start,
-                        span.end,
+                        Some(&ident.span()),
                         "duplicate identifier '{}' found",
                         ident.name_str()
                     )
                 }
-                ParseError::LexerError => {
-                    // TODO: actual positioning
-                    diagnostic!(0, 0, "unexpected input",)
+                ParseError::LexerError(found) => {
+                    diagnostic!(Some(&found.1), "unknown input '{}'", found.0)
                 }
                 ParseError::UnexpectedEof(expected) => {
                     // TODO: actual positioning
                     let expected = join_expected_token(expected);
-                    diagnostic!(0, 0, "unexpected EOF, expected one of {}", expected)
+                    diagnostic!(None, "unexpected EOF, expected one of {}", expected)
                 }
                 ParseError::UnexpectedToken(found, expected) => {
                     let expected = join_expected_token(expected);
-                    let span = &found.1;
                     diagnostic!(
-                        span.start,
-                        span.end,
+                        Some(&found.1),
                         "unexpected '{}', expected one of {}",
                         found.0,
                         expected
@@ -77,6 +71,14 @@ impl AsDiagnostic for CompileError {
     }
 }
 
+/// Return the start and end positions of a span, or the start of the source if there is none.
+fn position(span: Option<&Span>) -> (Position, Position) {
+    match span {
+        Some(span) => (span.start, span.end),
+        None => (Position::default(), Position::default()),
+    }
+}
+
 fn join_expected_token(expected: &[ExpectedToken]) -> String {
     expected
         .iter()
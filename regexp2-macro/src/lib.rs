@@ -0,0 +1,95 @@
+#![deny(rust_2018_idioms)]
+#![deny(future_incompatible)]
+
+use regexp2_syntax::class::CharClass;
+use regexp2_syntax::literal::Literals;
+use regexp2_syntax::parser::{ASTParser, NFAParser, ParseError, Parser};
+
+use automata::{dfa::Transition, DFA};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse_macro_input, LitStr};
+
+/// Compile a regular expression to a DFA at build time, expanding to a
+/// `&'static regexp2::CompiledRegExp`. Invalid expressions are reported as compile errors.
+#[proc_macro]
+pub fn regex(tok: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let regexp = parse_macro_input!(tok as LitStr);
+    match regex_(regexp) {
+        Ok(res) => res.into(),
+        Err(res) => res.into(),
+    }
+}
+
+fn regex_(regexp: LitStr) -> Result<TokenStream, TokenStream> {
+    let expr = regexp.value();
+    let parse_error = |e: ParseError| {
+        span_error(
+            regexp_error_span(&regexp, &e),
+            &format!("{}: {}", INVALID_REGEXP_ERROR, e.render(&expr)),
+        )
+    };
+
+    // A successful parse always leaves an expression, since an empty one is given a placeholder.
+    let nfa = NFAParser::new().parse(&expr).map_err(parse_error)?.unwrap();
+    let ast = ASTParser::new().parse(&expr).map_err(parse_error)?.unwrap();
+    let literals = Literals::new(&ast);
+    let dfa: DFA<CharClass> = nfa.into();
+
+    let dfa_tables = dfa_tables(&dfa);
+    let prefix = literals.prefix();
+    let required = literals.required();
+
+    Ok(quote! {
+        {
+            static RE: ::regexp2::CompiledRegExp =
+                ::regexp2::CompiledRegExp::from_parts(#expr, #dfa_tables, #prefix, #required);
+            &RE
+        }
+    })
+}
+
+/// Emit the constructor of a `regexp2::CompiledDFA` with the states and transitions of a DFA.
+fn dfa_tables(dfa: &DFA<CharClass>) -> TokenStream {
+    let initial_state = dfa.initial_state;
+    let final_states: Vec<_> = (0..dfa.total_states)
+        .map(|s| dfa.final_states.contains(&s))
+        .collect();
+
+    let mut rows = vec![Vec::new(); dfa.total_states];
+    for (src, Transition(tr), dest) in &dfa.transition {
+        rows[*src].extend(tr.iter().map(|r| (r.start, r.end, *dest)));
+    }
+    let rows = rows.into_iter().map(|mut row| {
+        // Ranges from a state are disjoint, so they can be searched by their ends.
+        row.sort_unstable();
+        let row = row
+            .into_iter()
+            .map(|(start, end, dest)| quote!((#start, #end, #dest)));
+        quote!(&[ #( #row ),* ])
+    });
+
+    quote! {
+        ::regexp2::CompiledDFA::from_tables(
+            #initial_state,
+            &[ #( #final_states ),* ],
+            &[ #( #rows ),* ],
+        )
+    }
+}
+
+const INVALID_REGEXP_ERROR: &str = "invalid regular expression";
+
+/// Find the span of the part of a regular expression literal at which a parse error occurred. This
+/// falls back to the span of the whole literal if the error cannot be located in the source, such
+/// as when the literal contains escapes or the compiler does not support subspans.
+#[inline]
+fn regexp_error_span(regexp: &LitStr, e: &ParseError) -> Span {
+    e.literal_span(&regexp.value(), &regexp.token().to_string())
+        .and_then(|range| regexp.token().subspan(range))
+        .unwrap_or_else(|| regexp.span())
+}
+
+fn span_error(span: Span, message: &str) -> TokenStream {
+    syn::Error::new(span, message).to_compile_error()
+}
@@ -0,0 +1,430 @@
+use crate::class::CharClass;
+use crate::parser::Look;
+
+use std::fmt::{self, Write};
+
+/// A node of the abstract syntax tree of a regular expression, as produced by
+/// [ASTParser](crate::parser::ASTParser).
+pub type ASTNode<T> = Node<T, Operator>;
+
+/// A node of a binary tree with leaves of type `T` and branches labeled by `U`. Operators that
+/// take one operand leave the second child as `None`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Node<T, U> {
+    Leaf(T),
+    Branch(U, Box<Self>, Box<Self>),
+    /// The empty expression, or a missing operand.
+    None,
+}
+
+/// The operators of a regular expression.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Operator {
+    KleeneStar,
+    Plus,
+    Optional,
+    Concatenation,
+    Union,
+    /// The strings matched by both operands.
+    Intersection,
+    /// The strings not matched by the operand.
+    Complement,
+    /// A capture group with the given index and optional name.
+    Capture(usize, Option<String>),
+    /// A backreference to the capture group with the given index.
+    Backreference(usize),
+    /// A lookaround assertion on the operand.
+    Lookaround(Look),
+}
+
+/// The precedence of each kind of node, from loosest to tightest binding. An operand that binds
+/// more loosely than its operator requires is written within a non-capturing group.
+const UNION: u8 = 0;
+const INTERSECTION: u8 = 1;
+const CONCATENATION: u8 = 2;
+const COMPLEMENT: u8 = 3;
+const QUANTIFIER: u8 = 4;
+const ATOM: u8 = 5;
+
+impl ASTNode<CharClass> {
+    /// Return an equivalent expression in a simpler form: unions and concatenations are
+    /// flattened, repeated and empty operands are removed, adjacent alternatives that are
+    /// character classes are merged, nested quantifiers are collapsed (`(?:a*)*` to `a*`) and the
+    /// common prefixes of adjacent alternatives are factored out (`ab|ac` to `a[bc]`) and an empty
+    /// alternative makes the others optional (`(?:)|b` to `b?`). The order of the other
+    /// alternatives is kept, so apart from an empty alternative, which the backtracking engine
+    /// then tries last, the priority of the matches it finds doesn't change.
+    ///
+    /// The tree is walked in post-order with an explicit stack rather than by recursion, since the
+    /// syntax trees of long expressions are deep.
+    #[inline]
+    pub fn simplify(&self) -> Self {
+        let mut steps = vec![SimplifyStep::Simplify(self)];
+        let mut simplified = Vec::new();
+        while let Some(step) = steps.pop() {
+            let (node, operands) = match step {
+                SimplifyStep::Simplify(node @ Node::Branch(op, c1, c2)) => {
+                    let operands = match op {
+                        Operator::Union | Operator::Concatenation => node.operands(op),
+                        _ => vec![&**c1, &**c2],
+                    };
+                    steps.push(SimplifyStep::Combine(node, operands.len()));
+                    steps.extend(operands.into_iter().rev().map(SimplifyStep::Simplify));
+                    continue;
+                }
+                SimplifyStep::Simplify(node) => {
+                    simplified.push(node.clone());
+                    continue;
+                }
+                SimplifyStep::Combine(node, len) => {
+                    (node, simplified.split_off(simplified.len() - len))
+                }
+            };
+
+            let op = match node {
+                Node::Branch(op, _, _) => op,
+                _ => unreachable!(),
+            };
+            let mut operands = operands.into_iter();
+            let c1 = operands.next().unwrap();
+            simplified.push(match op {
+                Operator::Union => Self::union(std::iter::once(c1).chain(operands).collect()),
+                Operator::Concatenation => {
+                    Self::concatenation(std::iter::once(c1).chain(operands).collect())
+                }
+                Operator::KleeneStar | Operator::Plus | Operator::Optional => {
+                    Self::quantifier(op, c1)
+                }
+                Operator::Complement => match c1 {
+                    Node::Branch(Operator::Complement, c, _) => *c,
+                    c1 => Node::Branch(Operator::Complement, Box::new(c1), Box::new(Node::None)),
+                },
+                _ => Node::Branch(op.clone(), Box::new(c1), Box::new(operands.next().unwrap())),
+            });
+        }
+        simplified.pop().unwrap()
+    }
+
+    /// Return the operands of a chain of branches with the given associative operator, in order.
+    #[inline]
+    fn operands(&self, op: &Operator) -> Vec<&Self> {
+        let mut stack = vec![self];
+        let mut operands = Vec::new();
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Branch(o, c1, c2) if o == op => {
+                    stack.push(c2);
+                    stack.push(c1);
+                }
+                _ => operands.push(node),
+            }
+        }
+        operands
+    }
+
+    /// Return the operands of a chain of branches with the given associative operator, in order,
+    /// taking them from the tree.
+    #[inline]
+    fn into_operands(self, op: &Operator) -> Vec<Self> {
+        let mut stack = vec![self];
+        let mut operands = Vec::new();
+        while let Some(node) = stack.pop() {
+            match node {
+                Node::Branch(o, c1, c2) if o == *op => {
+                    stack.push(*c2);
+                    stack.push(*c1);
+                }
+                node => operands.push(node),
+            }
+        }
+        operands
+    }
+
+    /// Build the simplified concatenation of the given simplified items.
+    #[inline]
+    fn concatenation(items: Vec<Self>) -> Self {
+        items
+            .into_iter()
+            .flat_map(|item| item.into_operands(&Operator::Concatenation))
+            .filter(|item| *item != Node::None)
+            .reduce(|c1, c2| Node::Branch(Operator::Concatenation, Box::new(c1), Box::new(c2)))
+            .unwrap_or(Node::None)
+    }
+
+    /// Build the simplified union of the given simplified alternatives.
+    #[inline]
+    fn union(alternatives: Vec<Self>) -> Self {
+        let mut flat: Vec<Self> = Vec::new();
+        for alternative in alternatives {
+            for a in alternative.into_operands(&Operator::Union) {
+                // Later duplicates never match where an earlier one doesn't.
+                if !flat.contains(&a) {
+                    flat.push(a);
+                }
+            }
+        }
+
+        // Adjacent alternatives beginning with the same item share their common prefix, which is
+        // factored out at once, so that the alternatives left to factor are fewer each time.
+        let mut factored: Vec<Self> = Vec::new();
+        let mut items = flat
+            .into_iter()
+            .map(|a| a.into_operands(&Operator::Concatenation))
+            .peekable();
+        while let Some(first) = items.next() {
+            let mut group = vec![first];
+            while let Some(a) = items.next_if(|a| a[0] == group[0][0]) {
+                group.push(a);
+            }
+
+            // An empty rest before the last would become optional and lose its priority, so each
+            // alternative before the last keeps an item of its own.
+            let limit = group[..group.len() - 1]
+                .iter()
+                .map(|a| a.len() - 1)
+                .min()
+                .unwrap_or(0);
+            let mut len = 1;
+            while len < limit
+                && group
+                    .iter()
+                    .all(|a| a.len() > len && a[len] == group[0][len])
+            {
+                len += 1;
+            }
+
+            if group.len() > 1 && limit > 0 && group[0][0] != Node::None {
+                let mut prefix = group[0][..len].to_vec();
+                let rests = group
+                    .into_iter()
+                    .map(|mut a| Self::concatenation(a.split_off(len)))
+                    .collect();
+                prefix.push(Self::union(rests));
+                factored.push(Self::concatenation(prefix));
+            } else {
+                factored.extend(group.into_iter().map(Self::concatenation));
+            }
+        }
+
+        // Adjacent character classes are merged into one.
+        let mut merged: Vec<Self> = Vec::new();
+        for a in factored {
+            match (merged.last_mut(), a) {
+                (Some(Node::Leaf(last)), Node::Leaf(class)) => last.copy_from(&class),
+                (_, a) => merged.push(a),
+            }
+        }
+
+        // An empty alternative makes the others optional.
+        if merged.len() > 1 && merged.contains(&Node::None) {
+            merged.retain(|a| *a != Node::None);
+            return Self::quantifier(&Operator::Optional, Self::union(merged));
+        }
+
+        merged
+            .into_iter()
+            .reduce(|c1, c2| Node::Branch(Operator::Union, Box::new(c1), Box::new(c2)))
+            .unwrap_or(Node::None)
+    }
+
+    /// Build the simplified repetition of a simplified operand, collapsing nested quantifiers.
+    #[inline]
+    fn quantifier(op: &Operator, operand: Self) -> Self {
+        let (op, operand) = match operand {
+            Node::None => return Node::None,
+            Node::Branch(inner @ Operator::KleeneStar, c, _)
+            | Node::Branch(inner @ Operator::Plus, c, _)
+            | Node::Branch(inner @ Operator::Optional, c, _) => {
+                let op = match (op, &inner) {
+                    (Operator::Plus, Operator::Plus) => Operator::Plus,
+                    (Operator::Optional, Operator::Optional) => Operator::Optional,
+                    _ => Operator::KleeneStar,
+                };
+                (op, *c)
+            }
+            operand => (op.clone(), operand),
+        };
+        Node::Branch(op, Box::new(operand), Box::new(Node::None))
+    }
+
+    /// Return how tightly the node binds when written.
+    #[inline]
+    fn precedence(&self) -> u8 {
+        match self {
+            Node::Leaf(_) | Node::None => ATOM,
+            Node::Branch(op, _, _) => match op {
+                Operator::Union => UNION,
+                Operator::Intersection => INTERSECTION,
+                Operator::Concatenation => CONCATENATION,
+                Operator::Complement => COMPLEMENT,
+                Operator::KleeneStar | Operator::Plus | Operator::Optional => QUANTIFIER,
+                Operator::Capture(_, _) | Operator::Backreference(_) | Operator::Lookaround(_) => {
+                    ATOM
+                }
+            },
+        }
+    }
+
+    /// Determine if the node is written ending with a backreference, which a following digit
+    /// would extend.
+    #[inline]
+    fn ends_with_backreference(&self) -> bool {
+        let mut node = self;
+        loop {
+            match node {
+                Node::Branch(Operator::Backreference(_), _, _) => return true,
+                Node::Branch(Operator::Concatenation, _, c2) => node = c2,
+                _ => return false,
+            }
+        }
+    }
+
+    /// Write the node, within a non-capturing group if it binds more loosely than the given
+    /// precedence. The nodes are written with an explicit stack of steps rather than by
+    /// recursion, since the syntax trees of long expressions are deep.
+    #[inline]
+    fn write(&self, out: &mut String, precedence: u8) -> fmt::Result {
+        let mut steps = vec![WriteStep::Write(self, precedence)];
+        let mut starts = Vec::new();
+        while let Some(step) = steps.pop() {
+            let (node, precedence) = match step {
+                WriteStep::Write(node, precedence) => (node, precedence),
+                WriteStep::Str(s) => {
+                    out.push_str(s);
+                    continue;
+                }
+                WriteStep::Start => {
+                    starts.push(out.len());
+                    continue;
+                }
+                WriteStep::Separate => {
+                    // Keep a digit from being read as part of a backreference.
+                    let start = starts.pop().unwrap();
+                    if out[start..].starts_with(|c: char| c.is_ascii_digit()) {
+                        out.insert_str(start, "(?:)");
+                    }
+                    continue;
+                }
+            };
+
+            if node.precedence() < precedence {
+                steps.push(WriteStep::Str(")"));
+                steps.push(WriteStep::Write(node, UNION));
+                steps.push(WriteStep::Str("(?:"));
+                continue;
+            }
+
+            let (op, c1, c2) = match node {
+                Node::Leaf(class) => {
+                    write!(out, "{}", class)?;
+                    continue;
+                }
+                Node::None => {
+                    out.push_str("(?:)");
+                    continue;
+                }
+                Node::Branch(op, c1, c2) => (op, c1, c2),
+            };
+
+            // The steps of each node are pushed in reverse, to be taken from the top of the stack.
+            match op {
+                Operator::Union => {
+                    steps.push(WriteStep::Write(c2, UNION));
+                    steps.push(WriteStep::Str("|"));
+                    steps.push(WriteStep::Write(c1, UNION));
+                }
+                Operator::Intersection => {
+                    steps.push(WriteStep::Write(c2, INTERSECTION));
+                    steps.push(WriteStep::Str("&"));
+                    steps.push(WriteStep::Write(c1, INTERSECTION));
+                }
+                Operator::Concatenation => {
+                    if c1.ends_with_backreference() {
+                        steps.push(WriteStep::Separate);
+                        steps.push(WriteStep::Write(c2, CONCATENATION));
+                        steps.push(WriteStep::Start);
+                    } else {
+                        steps.push(WriteStep::Write(c2, CONCATENATION));
+                    }
+                    steps.push(WriteStep::Write(c1, CONCATENATION));
+                }
+                Operator::Complement => {
+                    out.push('~');
+                    steps.push(WriteStep::Write(c1, COMPLEMENT));
+                }
+                // A repeated repetition is grouped, as `(?:a+)?`, so that it isn't read as a lazy
+                // or possessive quantifier of other syntaxes.
+                Operator::KleeneStar | Operator::Plus | Operator::Optional => {
+                    steps.push(WriteStep::Str(match op {
+                        Operator::KleeneStar => "*",
+                        Operator::Plus => "+",
+                        _ => "?",
+                    }));
+                    steps.push(WriteStep::Write(c1, ATOM));
+                }
+                Operator::Capture(_, name) => {
+                    match name {
+                        Some(name) => write!(out, "(?<{}>", name)?,
+                        None => out.push('('),
+                    }
+                    steps.push(WriteStep::Str(")"));
+                    push_group(&mut steps, c1);
+                }
+                Operator::Backreference(index) => write!(out, "\\{}", index)?,
+                Operator::Lookaround(look) => {
+                    out.push_str(match look {
+                        Look::Ahead => "(?=",
+                        Look::NegativeAhead => "(?!",
+                        Look::Behind => "(?<=",
+                        Look::NegativeBehind => "(?<!",
+                    });
+                    steps.push(WriteStep::Str(")"));
+                    push_group(&mut steps, c1);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The steps on the stack of the walk simplifying a syntax tree.
+enum SimplifyStep<'a> {
+    Simplify(&'a ASTNode<CharClass>),
+    /// Combine the given number of simplified operands of a node, once they're simplified.
+    Combine(&'a ASTNode<CharClass>, usize),
+}
+
+/// The steps on the stack of the walk writing a syntax tree.
+enum WriteStep<'a> {
+    /// Write a node at the given precedence.
+    Write(&'a ASTNode<CharClass>, u8),
+    Str(&'static str),
+    /// Mark the start of the operand after a backreference.
+    Start,
+    /// Separate the operand after a backreference from it if the operand begins with a digit.
+    Separate,
+}
+
+/// Push the step writing the contents of a group, which are left empty for the empty expression.
+#[inline]
+fn push_group<'a>(steps: &mut Vec<WriteStep<'a>>, node: &'a ASTNode<CharClass>) {
+    if *node != Node::None {
+        steps.push(WriteStep::Write(node, UNION));
+    }
+}
+
+impl fmt::Display for ASTNode<CharClass> {
+    /// Write the expression in canonical regular expression syntax, which parses to an
+    /// equivalent tree. Groups are only written where precedence requires them.
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        // The empty expression is written as nothing at all, rather than as an empty group.
+        if *self == Node::None {
+            return Ok(());
+        }
+
+        let mut out = String::new();
+        self.write(&mut out, UNION)?;
+        f.write_str(&out)
+    }
+}
@@ -0,0 +1,805 @@
+use crate::disjoint::{self, DisjointSet, Intersect, Priority};
+use crate::unicode::{self, DECIMAL_NUMBER, LETTER};
+
+use automata::{dfa::Disjoin, nfa::Transition};
+
+use std::cmp;
+use std::convert::TryInto;
+use std::fmt;
+use std::hash::Hash;
+use std::iter;
+
+/// The lowest Unicode scalar value.
+const USV_START_1: char = '\u{0}';
+/// The upper limit of the lower interval of Unicode scalar values.
+const USV_END_1: char = '\u{d7ff}';
+/// The lower limit of the upper interval of Unicode scalar values.
+const USV_START_2: char = '\u{e000}';
+/// The upper limit of the upper interval of Unicode scalar values.
+const USV_END_2: char = '\u{10ffff}';
+
+/// A set of character ranges that represent one character class. A CharClass contains all the
+/// ranges in a single bracketed segment of character ranges in a regular expression.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CharClass {
+    /// The ranges included in the character class.
+    pub ranges: DisjointSet<char, CharRange>,
+}
+
+impl CharClass {
+    /// Determine if the given char is within any of the character class's ranges.
+    #[inline]
+    pub fn contains(&self, c: char) -> bool {
+        // The only range that may contain c is the one with the greatest start not after c.
+        matches!(self.ranges.pred(&c), Some(r) if r.contains(c))
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Union of the intersections of each range in `Self` with each range in `other`. Both sets of
+    /// ranges are sorted, so they are walked side by side.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut union = CharClass::new();
+
+        let mut self_it = self.iter().peekable();
+        let mut other_it = other.iter().peekable();
+        while let (Some(&self_r), Some(&other_r)) = (self_it.peek(), other_it.peek()) {
+            if let Some(r) = self_r.intersection(other_r) {
+                union.add_range(r);
+            }
+
+            // The range that ends first can't intersect with anything further in the other set.
+            if self_r.end < other_r.end {
+                self_it.next();
+            } else {
+                other_it.next();
+            }
+        }
+
+        union
+    }
+
+    /// Return the complement of the union of the ranges in the character class.
+    #[inline]
+    pub fn complement(&self) -> Self {
+        let mut complement = CharClass::new();
+        let mut add_gap = |start, end| {
+            // A gap may span the surrogate code points, which are skipped.
+            if start <= USV_END_1 && end >= USV_START_2 {
+                complement.add_range(CharRange::new(start, USV_END_1));
+                complement.add_range(CharRange::new(USV_START_2, end));
+            } else {
+                complement.add_range(CharRange::new(start, end));
+            }
+        };
+
+        // The ranges are sorted, so the complement is made up of the gaps between them.
+        let mut gap_start = Some(USV_START_1);
+        for r in self.iter() {
+            match gap_start {
+                Some(start) if start < r.start => add_gap(start, shift_char(r.start, false)),
+                Some(_) => {}
+                None => break,
+            }
+            gap_start = Some(r.end)
+                .filter(|&end| end < USV_END_2)
+                .map(|end| shift_char(end, true));
+        }
+        if let Some(start) = gap_start {
+            add_gap(start, USV_END_2);
+        }
+
+        complement
+    }
+
+    /// Return the characters in the character class that aren't in `other`.
+    #[inline]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.intersection(&other.complement())
+    }
+
+    /// Return the characters in exactly one of the character class and `other`.
+    #[inline]
+    pub fn symmetric_difference(&self, other: &Self) -> Self {
+        let mut class = self.difference(other);
+        class.copy_from(&other.difference(self));
+        class
+    }
+
+    /// Copy the ranges in `other` to this `Self`.
+    #[inline]
+    pub fn copy_from(&mut self, other: &CharClass) {
+        for r in other {
+            self.add_range(r.clone());
+        }
+    }
+
+    /// Add a character range to the set.
+    #[inline]
+    pub fn add_range(&mut self, range: CharRange) {
+        self.ranges.insert(range);
+    }
+}
+
+impl CharClass {
+    /// Create a character class of all characters.
+    #[inline]
+    pub fn all() -> Self {
+        let mut class = CharClass::new();
+        class.add_range(CharRange::new(USV_START_1, USV_END_1));
+        class.add_range(CharRange::new(USV_START_2, USV_END_2));
+        class
+    }
+
+    /// Create a character class of all characters except the newline character.
+    #[inline]
+    pub fn all_but_newline() -> Self {
+        CharRange::new('\n', '\n').complement().into()
+    }
+
+    /// Create a character class consisting of all Unicode letter values.
+    #[inline]
+    pub fn letter() -> Self {
+        Self::from_table(LETTER)
+    }
+
+    /// Create a character class consisting of all alphanumerics and the underscore.
+    #[inline]
+    pub fn word() -> Self {
+        let ranges = vec![
+            CharRange::new('A', 'Z'),
+            CharRange::new('a', 'z'),
+            CharRange::new('0', '9'),
+            CharRange::new('_', '_'),
+        ];
+        ranges.into()
+    }
+
+    /// Create a character class consisting of all Unicode decimal numbers.
+    #[inline]
+    pub fn decimal_number() -> Self {
+        Self::from_table(DECIMAL_NUMBER)
+    }
+
+    /// Create a character class consisting of all characters in a Unicode general category,
+    /// given by its full name or abbreviation (e.g. `Uppercase_Letter` or `Lu`). Names are
+    /// matched loosely, ignoring case, spaces, underscores and hyphens.
+    #[inline]
+    pub fn unicode_category(name: &str) -> Option<Self> {
+        unicode::general_category(name).map(Self::from_table)
+    }
+
+    /// Create a character class consisting of all characters in a Unicode script, given by its
+    /// full name or abbreviation (e.g. `Greek` or `Grek`).
+    #[inline]
+    pub fn unicode_script(name: &str) -> Option<Self> {
+        unicode::script(name).map(Self::from_table)
+    }
+
+    /// Create a character class consisting of all characters with a Unicode binary property,
+    /// given by its full name or abbreviation (e.g. `XID_Start` or `XIDS`).
+    #[inline]
+    pub fn unicode_property(name: &str) -> Option<Self> {
+        unicode::binary_property(name).map(Self::from_table)
+    }
+
+    /// Create a character class from the body of a `\p{...}` escape: a general category, script
+    /// or binary property name, or a `gc=...`, `sc=...` or `script=...` pair.
+    #[inline]
+    pub fn unicode_class(query: &str) -> Option<Self> {
+        unicode::property(query).map(Self::from_table)
+    }
+
+    #[inline]
+    fn from_table(table: &[(char, char)]) -> Self {
+        table.iter().map(|&r| r.into()).collect()
+    }
+
+    /// Create a character class consisting of whitespace characters.
+    #[inline]
+    pub fn whitespace() -> Self {
+        let chars = vec![
+            ' ', '\u{000c}', '\n', '\r', '\t', '\u{000b}', '\u{00a0}', '\u{1680}', '\u{2028}',
+            '\u{2029}', '\u{202f}', '\u{205f}', '\u{3000}', '\u{feff}',
+        ];
+
+        let mut cc: Self = chars.into();
+        cc.add_range(CharRange::new('\u{2000}', '\u{200a}'));
+        cc
+    }
+
+    /// Create a character class consisting of horizontal whitespace characters: the tab, the
+    /// space and the Unicode space separators.
+    #[inline]
+    pub fn horizontal_whitespace() -> Self {
+        let chars = vec![
+            '\t', ' ', '\u{00a0}', '\u{1680}', '\u{180e}', '\u{202f}', '\u{205f}', '\u{3000}',
+        ];
+
+        let mut cc: Self = chars.into();
+        cc.add_range(CharRange::new('\u{2000}', '\u{200a}'));
+        cc
+    }
+
+    /// Create a character class consisting of vertical whitespace characters: the line feed,
+    /// vertical tab, form feed, carriage return, next line, and the line and paragraph
+    /// separators.
+    #[inline]
+    pub fn vertical_whitespace() -> Self {
+        let mut cc: Self = CharRange::new('\n', '\r').into();
+        cc.add_range(CharRange::new_single('\u{0085}'));
+        cc.add_range(CharRange::new('\u{2028}', '\u{2029}'));
+        cc
+    }
+
+    /// Create a character class consisting of the ASCII letters.
+    #[inline]
+    pub fn ascii_alphabetic() -> Self {
+        vec![CharRange::new('A', 'Z'), CharRange::new('a', 'z')].into()
+    }
+
+    /// Create a character class consisting of the ASCII uppercase letters.
+    #[inline]
+    pub fn ascii_uppercase() -> Self {
+        CharRange::new('A', 'Z').into()
+    }
+
+    /// Create a character class consisting of the ASCII lowercase letters.
+    #[inline]
+    pub fn ascii_lowercase() -> Self {
+        CharRange::new('a', 'z').into()
+    }
+
+    /// Create a character class consisting of the ASCII decimal digits.
+    #[inline]
+    pub fn ascii_digit() -> Self {
+        CharRange::new('0', '9').into()
+    }
+
+    /// Create a character class consisting of the ASCII hexadecimal digits, in either case.
+    #[inline]
+    pub fn ascii_hexdigit() -> Self {
+        let ranges = vec![
+            CharRange::new('0', '9'),
+            CharRange::new('A', 'F'),
+            CharRange::new('a', 'f'),
+        ];
+        ranges.into()
+    }
+
+    /// Create a character class consisting of the ASCII letters and decimal digits.
+    #[inline]
+    pub fn ascii_alphanumeric() -> Self {
+        let mut cc = Self::ascii_alphabetic();
+        cc.add_range(CharRange::new('0', '9'));
+        cc
+    }
+
+    /// Create a character class consisting of the ASCII punctuation characters, which are the
+    /// graphic characters that aren't letters or digits.
+    #[inline]
+    pub fn ascii_punctuation() -> Self {
+        let ranges = vec![
+            CharRange::new('!', '/'),
+            CharRange::new(':', '@'),
+            CharRange::new('[', '`'),
+            CharRange::new('{', '~'),
+        ];
+        ranges.into()
+    }
+
+    /// Create a character class consisting of the ASCII whitespace characters, including the
+    /// vertical tab.
+    #[inline]
+    pub fn ascii_whitespace() -> Self {
+        vec![CharRange::new('\t', '\r'), CharRange::new_single(' ')].into()
+    }
+
+    /// Create a character class consisting of the space and the tab.
+    #[inline]
+    pub fn ascii_blank() -> Self {
+        vec!['\t', ' '].into()
+    }
+
+    /// Create a character class consisting of the ASCII control characters.
+    #[inline]
+    pub fn ascii_control() -> Self {
+        vec![
+            CharRange::new('\0', '\u{1f}'),
+            CharRange::new_single('\u{7f}'),
+        ]
+        .into()
+    }
+
+    /// Create a character class consisting of the visible ASCII characters.
+    #[inline]
+    pub fn ascii_graphic() -> Self {
+        CharRange::new('!', '~').into()
+    }
+
+    /// Create a character class consisting of the visible ASCII characters and the space.
+    #[inline]
+    pub fn ascii_printable() -> Self {
+        CharRange::new(' ', '~').into()
+    }
+
+    /// Create a character class from the name of a POSIX bracket expression class (e.g. `alpha`
+    /// for `[[:alpha:]]`). As in the POSIX locale, these classes contain only ASCII characters.
+    #[inline]
+    pub fn posix_class(name: &str) -> Option<Self> {
+        let class = match name {
+            "alpha" => Self::ascii_alphabetic(),
+            "upper" => Self::ascii_uppercase(),
+            "lower" => Self::ascii_lowercase(),
+            "digit" => Self::ascii_digit(),
+            "xdigit" => Self::ascii_hexdigit(),
+            "alnum" => Self::ascii_alphanumeric(),
+            "punct" => Self::ascii_punctuation(),
+            "space" => Self::ascii_whitespace(),
+            "blank" => Self::ascii_blank(),
+            "cntrl" => Self::ascii_control(),
+            "graph" => Self::ascii_graphic(),
+            "print" => Self::ascii_printable(),
+            "word" => Self::word(),
+            "ascii" => CharRange::new('\0', '\u{7f}').into(),
+            _ => return None,
+        };
+        Some(class)
+    }
+}
+
+impl CharClass {
+    /// Create an empty character class.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            ranges: DisjointSet::new(),
+        }
+    }
+}
+
+impl Default for CharClass {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<CharRange> for CharClass {
+    /// Create a character class with a single range.
+    #[inline]
+    fn from(range: CharRange) -> Self {
+        let mut class = CharClass::new();
+        class.add_range(range);
+        class
+    }
+}
+
+impl From<char> for CharClass {
+    /// Create a character class with one single-character character range.
+    #[inline]
+    fn from(c: char) -> Self {
+        CharRange::from(c).into()
+    }
+}
+
+impl From<Vec<CharRange>> for CharClass {
+    #[inline]
+    fn from(vec: Vec<CharRange>) -> Self {
+        let mut class = CharClass::new();
+        class.extend(vec);
+        class
+    }
+}
+
+impl From<Vec<char>> for CharClass {
+    #[inline]
+    fn from(vec: Vec<char>) -> Self {
+        let mut class = CharClass::new();
+        class.extend(vec.into_iter().map(CharRange::from));
+        class
+    }
+}
+
+impl Extend<CharRange> for CharClass {
+    #[inline]
+    fn extend<I: IntoIterator<Item = CharRange>>(&mut self, iter: I) {
+        for r in iter {
+            self.add_range(r);
+        }
+    }
+}
+
+impl iter::FromIterator<CharRange> for CharClass {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = CharRange>>(iter: I) -> Self {
+        let mut class = Self::new();
+        class.extend(iter);
+        class
+    }
+}
+
+impl fmt::Display for CharClass {
+    /// Write the character class in regular expression syntax: a single char, `.`, one of the
+    /// class escapes, or a bracketed class, negated if that takes fewer ranges.
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        let ranges = self.coalesced_ranges();
+        match ranges.as_slice() {
+            [] => return write!(f, r"[^\s\S]"),
+            [r] if r.start == r.end => return write_char(f, r.start, false),
+            _ => {}
+        }
+
+        let escapes = [
+            (CharClass::all_but_newline(), ".", None),
+            (CharClass::all(), r"[\s\S]", None),
+            (CharClass::decimal_number(), r"\d", Some(r"\D")),
+            (CharClass::word(), r"\w", Some(r"\W")),
+            (CharClass::whitespace(), r"\s", Some(r"\S")),
+            (CharClass::horizontal_whitespace(), r"\h", Some(r"\H")),
+            (CharClass::vertical_whitespace(), r"\v", Some(r"\V")),
+        ];
+        for (class, escape, negated) in escapes.iter() {
+            if ranges == class.coalesced_ranges() {
+                return write!(f, "{}", escape);
+            }
+            if let Some(negated) = negated {
+                if ranges == class.complement().coalesced_ranges() {
+                    return write!(f, "{}", negated);
+                }
+            }
+        }
+
+        let complement = self.complement().coalesced_ranges();
+        let (negated, ranges) = if complement.len() < ranges.len() {
+            (true, complement)
+        } else {
+            (false, ranges)
+        };
+
+        write!(f, "[{}", if negated { "^" } else { "" })?;
+        for r in ranges {
+            write_char(f, r.start, true)?;
+            if r.end != r.start {
+                if shift_char(r.start, true) != r.end {
+                    write!(f, "-")?;
+                }
+                write_char(f, r.end, true)?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+impl CharClass {
+    /// Return the ranges of the character class in order, with adjacent ranges joined.
+    #[inline]
+    fn coalesced_ranges(&self) -> Vec<CharRange> {
+        let mut ranges: Vec<CharRange> = Vec::new();
+        for r in self.iter() {
+            match ranges.last_mut() {
+                Some(last) if last.end != USV_END_2 && shift_char(last.end, true) == r.start => {
+                    last.end = r.end;
+                }
+                _ => ranges.push(r.clone()),
+            }
+        }
+        ranges
+    }
+}
+
+/// Write a char as it would appear in a regular expression, escaping it if it's an operator or
+/// isn't visible. Within a bracketed class, the chars that are special there are escaped instead.
+#[inline]
+fn write_char(f: &mut fmt::Formatter<'_>, c: char, in_class: bool) -> fmt::Result {
+    let special = if in_class { r"\[]^-&~" } else { r"\|()[*+?.&~" };
+    match c {
+        '\n' => write!(f, r"\n"),
+        '\r' => write!(f, r"\r"),
+        '\t' => write!(f, r"\t"),
+        // NUL and chars that Rust escapes as `\u{...}` for debugging aren't printable.
+        c if (c.is_whitespace() && c != ' ')
+            || c == '\0'
+            || c.escape_debug().nth(1) == Some('u') =>
+        {
+            write!(f, r"\x{{{:x}}}", c as u32)
+        }
+        c if special.contains(c) => write!(f, "\\{}", c),
+        c => write!(f, "{}", c),
+    }
+}
+
+impl CharClass {
+    #[inline]
+    pub fn iter(&self) -> CharClassIter<'_> {
+        self.ranges.iter().into()
+    }
+
+    #[inline]
+    pub fn iter_mut(&mut self) -> CharClassIterMut<'_> {
+        self.ranges.iter_mut().into()
+    }
+}
+
+impl<'a> IntoIterator for &'a CharClass {
+    type Item = &'a CharRange;
+    type IntoIter = CharClassIter<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter().into()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut CharClass {
+    type Item = &'a mut CharRange;
+    type IntoIter = CharClassIterMut<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter_mut().into()
+    }
+}
+
+impl IntoIterator for CharClass {
+    type Item = CharRange;
+    type IntoIter = CharClassIntoIter;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.into_iter().into()
+    }
+}
+
+pub struct CharClassIter<'a> {
+    set_iter: disjoint::Iter<'a, char, CharRange>,
+}
+
+impl<'a> Iterator for CharClassIter<'a> {
+    type Item = &'a CharRange;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.set_iter.next()
+    }
+}
+
+impl<'a> From<disjoint::Iter<'a, char, CharRange>> for CharClassIter<'a> {
+    #[inline]
+    fn from(set_iter: disjoint::Iter<'a, char, CharRange>) -> Self {
+        Self { set_iter }
+    }
+}
+
+pub struct CharClassIterMut<'a> {
+    set_iter: disjoint::IterMut<'a, char, CharRange>,
+}
+
+impl<'a> Iterator for CharClassIterMut<'a> {
+    type Item = &'a mut CharRange;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.set_iter.next()
+    }
+}
+
+impl<'a> From<disjoint::IterMut<'a, char, CharRange>> for CharClassIterMut<'a> {
+    #[inline]
+    fn from(set_iter: disjoint::IterMut<'a, char, CharRange>) -> Self {
+        Self { set_iter }
+    }
+}
+
+pub struct CharClassIntoIter {
+    set_iter: disjoint::IntoIter<char, CharRange>,
+}
+
+impl Iterator for CharClassIntoIter {
+    type Item = CharRange;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.set_iter.next()
+    }
+}
+
+impl From<disjoint::IntoIter<char, CharRange>> for CharClassIntoIter {
+    #[inline]
+    fn from(set_iter: disjoint::IntoIter<char, CharRange>) -> Self {
+        Self { set_iter }
+    }
+}
+
+/// A range of characters representing all characters from the lower bound to the upper bound,
+/// inclusive.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct CharRange {
+    pub start: char,
+    pub end: char,
+}
+
+impl CharRange {
+    /// Create a new character range with the given bounds.
+    #[inline]
+    pub fn new(start: char, end: char) -> Self {
+        CharRange { start, end }
+    }
+
+    /// Create a single-character character range for the given character.
+    #[inline]
+    pub fn new_single(c: char) -> Self {
+        CharRange { start: c, end: c }
+    }
+
+    /// Determine if the given character is within the range.
+    #[inline]
+    pub fn contains(&self, c: char) -> bool {
+        self.start <= c && c <= self.end
+    }
+
+    /// Return the range that is the intersection between two ranges.
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        if other.start > self.end || self.start > other.end {
+            None
+        } else {
+            let start = cmp::max(self.start, other.start);
+            let end = cmp::min(self.end, other.end);
+            Some(Self::new(start, end))
+        }
+    }
+
+    /// Return the set of ranges that equals the complement of this range. Because Unicode scalar
+    /// values, which `char` encodes, consist of all Unicode code points except high-surrogate and
+    /// low-surrogate code points, characters between the values of 0xD7FF and 0xE000, exclusive,
+    /// are omitted.
+    #[inline]
+    pub fn complement(&self) -> Vec<Self> {
+        let mut ranges = Vec::new();
+
+        if self.start > USV_START_2 {
+            let r1 = Self::new(USV_START_2, shift_char(self.start, false));
+            ranges.push(r1);
+
+            let r2 = Self::new(USV_START_1, USV_END_1);
+            ranges.push(r2);
+        } else if self.start > USV_START_1 {
+            let r = Self::new(USV_START_1, shift_char(self.start, false));
+            ranges.push(r);
+        }
+
+        if self.end < USV_END_1 {
+            let r1 = Self::new(shift_char(self.end, true), USV_END_1);
+            ranges.push(r1);
+
+            let r2 = Self::new(USV_START_2, USV_END_2);
+            ranges.push(r2);
+        } else if self.end < USV_END_2 {
+            let r = Self::new(shift_char(self.end, true), USV_END_2);
+            ranges.push(r);
+        }
+
+        ranges
+    }
+}
+
+impl Intersect for CharRange {
+    #[inline]
+    fn intersect(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    #[inline]
+    fn union(&self, other: &Self) -> Self {
+        Self::new(
+            cmp::min(self.start, other.start),
+            cmp::max(self.end, other.end),
+        )
+    }
+}
+
+impl Priority<char> for CharRange {
+    #[inline]
+    fn priority(&self) -> char {
+        self.start
+    }
+}
+
+impl From<char> for CharRange {
+    #[inline]
+    fn from(c: char) -> Self {
+        Self::new(c, c)
+    }
+}
+
+impl From<(char, char)> for CharRange {
+    /// Create a character range from a tuple, where the first element is the lower bound, and the
+    /// second element is the upper bound.
+    #[inline]
+    fn from(range: (char, char)) -> Self {
+        Self::new(range.0, range.1)
+    }
+}
+
+impl PartialEq<char> for CharClass {
+    #[inline]
+    fn eq(&self, other: &char) -> bool {
+        self.contains(*other)
+    }
+}
+
+impl From<CharClass> for Transition<CharClass> {
+    #[inline]
+    fn from(c: CharClass) -> Self {
+        Transition::Some(c)
+    }
+}
+
+impl Disjoin for CharClass {
+    /// Create a set of disjoint CharClass from a set of CharClass. Algorithm inspired by [this
+    /// Stack Overflow answer](https://stackoverflow.com/a/55482655/8955108). Elementary ranges
+    /// that are covered by exactly the same input classes are grouped into one class, so that
+    /// large classes (such as Unicode properties) don't split into hundreds of transitions.
+    #[inline]
+    fn disjoin(vec: Vec<&Self>) -> Vec<Self> {
+        let mut bounds: Vec<_> = vec
+            .iter()
+            .enumerate()
+            .flat_map(|(i, cc)| {
+                cc.ranges.iter().flat_map(move |r| {
+                    vec![(r.start as u32, i, true), (r.end as u32 + 1, i, false)]
+                })
+            })
+            .collect();
+        bounds.sort_by_key(|&(x, _, _)| x);
+
+        let mut prev = 0;
+        // Number of ranges of each input class covering the current position.
+        let mut covering = vec![0; vec.len()];
+        let mut groups: Vec<(Vec<usize>, CharClass)> = Vec::new();
+        for (x, i, start) in bounds {
+            if x > prev && covering.iter().any(|&c| c != 0) {
+                let range = CharRange::new(prev.try_into().unwrap(), (x - 1).try_into().unwrap());
+                let members: Vec<_> = (0..covering.len()).filter(|&j| covering[j] != 0).collect();
+                match groups.iter_mut().find(|(m, _)| *m == members) {
+                    Some((_, cc)) => cc.add_range(range),
+                    None => groups.push((members, range.into())),
+                }
+            }
+
+            prev = x;
+            if start {
+                covering[i] += 1;
+            } else {
+                covering[i] -= 1;
+            }
+        }
+
+        groups.into_iter().map(|(_, cc)| cc).collect()
+    }
+
+    #[inline]
+    fn contains(&self, other: &Self) -> bool {
+        !self.intersection(other).is_empty()
+    }
+}
+
+/// Return the char after or before the given char. Shifting across the surrogate code points skips
+/// over all of them.
+#[inline]
+fn shift_char(c: char, up: bool) -> char {
+    match (c, up) {
+        (USV_END_1, true) => USV_START_2,
+        (USV_START_2, false) => USV_END_1,
+        _ => {
+            let shifted = if up { c as u32 + 1 } else { c as u32 - 1 };
+            shifted.try_into().unwrap()
+        }
+    }
+}
@@ -87,20 +87,27 @@ where
             }
         }
 
-        // Check for intersection with successor.
-        let succ = self.tree.succ(&priority, true);
-        if let Some((succ_pri, succ_v)) = succ {
+        // Check for intersection with successors; the item may span several of them.
+        while let Some((succ_pri, succ_v)) = self.tree.succ(&priority, true) {
             // If intersecting, merge and remove successor.
-            if item.intersect(succ_v) {
-                item = item.union(succ_v);
-                let del_pri = succ_pri.clone();
-                self.tree.remove(&del_pri);
+            if !item.intersect(succ_v) {
+                break;
             }
+
+            item = item.union(succ_v);
+            let del_pri = succ_pri.clone();
+            self.tree.remove(&del_pri);
         }
 
         self.tree.insert(priority, item);
     }
 
+    /// Return the element with the greatest priority less than or equal to the given priority.
+    #[inline]
+    pub fn pred(&self, priority: &K) -> Option<&V> {
+        self.tree.pred(priority, true).map(|(_, v)| v)
+    }
+
     #[inline]
     pub fn remove(&mut self, priority: K) -> Option<V> {
         self.tree.remove(&priority).map(|(_, v)| v)
@@ -156,7 +163,7 @@ where
     }
 }
 
-impl<'a, K, V> IntoIterator for DisjointSet<K, V>
+impl<K, V> IntoIterator for DisjointSet<K, V>
 where
     K: Clone + Ord,
     V: Intersect + Priority<K>,
@@ -249,7 +256,7 @@ impl<K, V> Iterator for IntoIter<K, V> {
     }
 }
 
-impl<'a, K, V> From<map::IntoIter<K, V>> for IntoIter<K, V> {
+impl<K, V> From<map::IntoIter<K, V>> for IntoIter<K, V> {
     #[inline]
     fn from(map_iter: map::IntoIter<K, V>) -> Self {
         Self { map_iter }
@@ -0,0 +1,10 @@
+#![deny(rust_2018_idioms)]
+#![deny(future_incompatible)]
+
+mod disjoint;
+mod unicode;
+
+pub mod ast;
+pub mod class;
+pub mod literal;
+pub mod parser;
@@ -0,0 +1,201 @@
+use crate::ast::{ASTNode, Node, Operator};
+use crate::class::CharClass;
+
+/// The steps on the stack of [Literals::new].
+enum Step<'a> {
+    Visit(&'a ASTNode<CharClass>),
+    /// Combine the literals of the operands of the operator, which have been found last.
+    Combine(&'a Operator),
+}
+
+/// The literal text known about the matches of an expression, used to skip positions of input
+/// strings at which no match can start.
+#[derive(Debug, Clone, Default)]
+pub struct Literals {
+    /// The only string matched, if there is exactly one.
+    exact: Option<String>,
+    /// Text that every match begins with.
+    prefix: String,
+    /// Text that every match ends with.
+    suffix: String,
+    /// The longest text known to be contained in every match.
+    required: String,
+}
+
+impl Literals {
+    /// Find the literals of a node from those of the nodes below it, with an explicit stack rather
+    /// than by recursion, since the syntax trees of long expressions are deep.
+    #[inline]
+    pub fn new(node: &ASTNode<CharClass>) -> Self {
+        let mut stack = vec![Step::Visit(node)];
+        let mut found = Vec::new();
+        while let Some(step) = stack.pop() {
+            let node = match step {
+                Step::Visit(node) => node,
+                Step::Combine(op) => {
+                    let b = found.pop().unwrap_or_default();
+                    let literals = match op {
+                        Operator::Concatenation => {
+                            Self::concatenation(found.pop().unwrap_or_default(), b)
+                        }
+                        Operator::Union => Self::union(found.pop().unwrap_or_default(), b),
+                        Operator::Intersection => {
+                            Self::intersection(found.pop().unwrap_or_default(), b)
+                        }
+                        _ => Self { exact: None, ..b },
+                    };
+                    found.push(literals);
+                    continue;
+                }
+            };
+
+            let (op, c1, c2) = match node {
+                Node::Leaf(c) => {
+                    let mut ranges = c.iter();
+                    found.push(match (ranges.next(), ranges.next()) {
+                        (Some(r), None) if r.start == r.end => Self::exact(r.start.to_string()),
+                        _ => Self::default(),
+                    });
+                    continue;
+                }
+                Node::None => {
+                    found.push(Self::exact(String::new()));
+                    continue;
+                }
+                Node::Branch(op, c1, c2) => (op, c1, c2),
+            };
+
+            match op {
+                Operator::Concatenation | Operator::Union | Operator::Intersection => {
+                    stack.push(Step::Combine(op));
+                    stack.push(Step::Visit(c2));
+                    stack.push(Step::Visit(c1));
+                }
+                Operator::Plus => {
+                    stack.push(Step::Combine(op));
+                    stack.push(Step::Visit(c1));
+                }
+                Operator::Capture(_, _) => stack.push(Step::Visit(c1)),
+                // Lookaround assertions don't consume any input.
+                Operator::Lookaround(_) => found.push(Self::exact(String::new())),
+                Operator::KleeneStar
+                | Operator::Optional
+                | Operator::Complement
+                | Operator::Backreference(_) => found.push(Self::default()),
+            }
+        }
+
+        found.pop().unwrap_or_default()
+    }
+
+    /// Return the text that every match begins with, which may be empty.
+    #[inline]
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Return the longest text found that every match contains, which may be empty.
+    #[inline]
+    pub fn required(&self) -> &str {
+        &self.required
+    }
+
+    #[inline]
+    fn exact(s: String) -> Self {
+        Self {
+            prefix: s.clone(),
+            suffix: s.clone(),
+            required: s.clone(),
+            exact: Some(s),
+        }
+    }
+
+    #[inline]
+    fn concatenation(a: Self, b: Self) -> Self {
+        if let (Some(a), Some(b)) = (&a.exact, &b.exact) {
+            return Self::exact(format!("{}{}", a, b));
+        }
+
+        let prefix = match &a.exact {
+            Some(a) => format!("{}{}", a, b.prefix),
+            None => a.prefix,
+        };
+        let suffix = match &b.exact {
+            Some(b) => format!("{}{}", a.suffix, b),
+            None => b.suffix,
+        };
+        let joined = format!("{}{}", a.suffix, b.prefix);
+        let required = vec![
+            a.required,
+            b.required,
+            joined,
+            prefix.clone(),
+            suffix.clone(),
+        ]
+        .into_iter()
+        .max_by_key(String::len)
+        .unwrap_or_default();
+
+        Self {
+            exact: None,
+            prefix,
+            suffix,
+            required,
+        }
+    }
+
+    /// Every match of an intersection is a match of both operands, so whatever is known about
+    /// either holds.
+    #[inline]
+    fn intersection(a: Self, b: Self) -> Self {
+        if let Some(exact) = a.exact.clone().or_else(|| b.exact.clone()) {
+            return Self::exact(exact);
+        }
+
+        let longest = |x: String, y: String| if x.len() >= y.len() { x } else { y };
+        Self {
+            exact: None,
+            prefix: longest(a.prefix, b.prefix),
+            suffix: longest(a.suffix, b.suffix),
+            required: longest(a.required, b.required),
+        }
+    }
+
+    #[inline]
+    fn union(a: Self, b: Self) -> Self {
+        if a.exact.is_some() && a.exact == b.exact {
+            return a;
+        }
+
+        let prefix: String = a
+            .prefix
+            .chars()
+            .zip(b.prefix.chars())
+            .take_while(|(x, y)| x == y)
+            .map(|(x, _)| x)
+            .collect();
+        let mut suffix: Vec<char> = a
+            .suffix
+            .chars()
+            .rev()
+            .zip(b.suffix.chars().rev())
+            .take_while(|(x, y)| x == y)
+            .map(|(x, _)| x)
+            .collect();
+        suffix.reverse();
+        let suffix: String = suffix.into_iter().collect();
+
+        let required = if prefix.len() >= suffix.len() {
+            prefix.clone()
+        } else {
+            suffix.clone()
+        };
+
+        Self {
+            exact: None,
+            prefix,
+            suffix,
+            required,
+        }
+    }
+}
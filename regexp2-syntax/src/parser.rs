@@ -0,0 +1,1520 @@
+use crate::ast::{self, ASTNode};
+use crate::class::{CharClass, CharRange};
+
+use std::convert::{TryFrom, TryInto};
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::result;
+
+use automata::{nfa::Transition, NFA};
+
+/// Alias for [std::result::Result] for [ParseError].
+pub type Result<T> = result::Result<T, ParseError>;
+
+/// Alias for the result of parser actions, which fail without knowing where in the expression the
+/// error occurred; the parser attaches the location.
+pub type ActionResult = result::Result<(), ParseErrorKind>;
+
+/// A regular expression parser that produces an NFA that describes the same language as the
+/// regular expression. The transitions of the NFA must be derivable from CharClass.
+pub struct NFAParser<T>
+where
+    T: Clone + Eq + Hash,
+    Transition<T>: From<CharClass>,
+{
+    /// The maximum depth to which groups and bracketed classes may be nested.
+    nesting_limit: usize,
+    /// The maximum number of nodes in the syntax tree of an expression.
+    size_limit: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> NFAParser<T>
+where
+    T: Clone + Eq + Hash,
+    Transition<T>: From<CharClass>,
+{
+    /// Create a new NFAParser.
+    #[inline]
+    pub fn new() -> Self {
+        NFAParser {
+            nesting_limit: usize::MAX,
+            size_limit: usize::MAX,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set the maximum depth to which groups and bracketed classes may be nested.
+    #[inline]
+    pub fn with_nesting_limit(mut self, nesting_limit: usize) -> Self {
+        self.nesting_limit = nesting_limit;
+        self
+    }
+
+    /// Set the maximum number of nodes in the syntax tree of an expression. See
+    /// [Parser::size_limit].
+    #[inline]
+    pub fn with_size_limit(mut self, size_limit: usize) -> Self {
+        self.size_limit = size_limit;
+        self
+    }
+}
+
+impl<T> Default for NFAParser<T>
+where
+    T: Clone + Eq + Hash,
+    Transition<T>: From<CharClass>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Parser<NFA<T>> for NFAParser<T>
+where
+    T: Clone + Eq + Hash,
+    Transition<T>: From<CharClass>,
+{
+    /// Implement the shift action. A new NFA with two states and a single transition on the given
+    /// character between them is pushed to the parsing stack.
+    #[inline]
+    fn shift_action(&self, stack: &mut Vec<NFA<T>>, c: CharClass) -> ActionResult {
+        let transition = c.into();
+
+        let mut nfa = NFA::new();
+        let final_state = nfa.add_state(true);
+        nfa.add_transition(nfa.initial_state, final_state, transition);
+
+        stack.push(nfa);
+
+        Ok(())
+    }
+
+    /// Implement the reduce action for parsing. Sub-NFAs are popped from the NFA stack according to
+    /// the operator, and a new NFA is constructed and pushed to the stack.
+    #[inline]
+    fn reduce_action(&self, stack: &mut Vec<NFA<T>>, op: Operator) -> ActionResult {
+        let mut new_nfa: NFA<T>;
+
+        match op {
+            // A union NFA is constructed from the 2 operands of the union operator.
+            Operator::Union => {
+                let c2 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                let c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                new_nfa = NFA::union(&c1, &c2);
+            }
+            // A concatenated NFA is constructed from the 2 operands of the concatenation
+            // operator.
+            Operator::Concatenation => {
+                let c2 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                let c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                new_nfa = NFA::concatenation(&c1, &c2);
+            }
+            // A new NFA is constructed from the most recent NFA on the stack for kleene star,
+            // plus, and optional operators.
+            Operator::KleeneStar => {
+                let c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                new_nfa = NFA::kleene_star(&c1);
+            }
+            Operator::Plus => {
+                let c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                let kleene = NFA::kleene_star(&c1);
+                new_nfa = NFA::concatenation(&kleene, &c1);
+            }
+            Operator::Optional => {
+                let c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                let c2 = NFA::new_epsilon();
+                new_nfa = NFA::union(&c1, &c2);
+            }
+            // A new NFA with a single epsilon transition is pushed to the stack.
+            Operator::EmptyPlaceholder => {
+                new_nfa = NFA::new();
+                new_nfa.final_states.insert(new_nfa.initial_state);
+            }
+            // Capture groups don't affect the language of the expression.
+            Operator::Capture(_, _) => return Ok(()),
+            // Backreferences and lookaround assertions can't be expressed by finite automata.
+            Operator::Backreference(_) | Operator::Lookaround(_) => {
+                return Err(ParseErrorKind::BacktrackingRequired)
+            }
+            // The extended operators aren't recognized by this parser.
+            Operator::Intersection | Operator::Complement => unreachable!(),
+            Operator::LeftParen => return Err(ParseErrorKind::UnbalancedParentheses),
+        }
+
+        stack.push(new_nfa);
+        Ok(())
+    }
+
+    #[inline]
+    fn nesting_limit(&self) -> usize {
+        self.nesting_limit
+    }
+
+    #[inline]
+    fn size_limit(&self) -> usize {
+        self.size_limit
+    }
+}
+
+pub struct ASTParser<T>
+where
+    T: Clone + Eq + Hash + From<CharClass>,
+{
+    /// Whether the intersection (`&`) and complement (`~`) operators are recognized.
+    extended: bool,
+    /// The maximum depth to which groups and bracketed classes may be nested.
+    nesting_limit: usize,
+    /// The maximum number of nodes in the syntax tree of an expression.
+    size_limit: usize,
+    _phantom: PhantomData<T>,
+}
+
+impl<T> ASTParser<T>
+where
+    T: Clone + Eq + Hash + From<CharClass>,
+{
+    /// Create a new ASTParser.
+    #[inline]
+    pub fn new() -> Self {
+        ASTParser {
+            extended: false,
+            nesting_limit: usize::MAX,
+            size_limit: usize::MAX,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Create an ASTParser that also recognizes the intersection (`&`) and complement (`~`)
+    /// operators, which are otherwise literals. Only the derivative engine supports them, so
+    /// backreferences and lookaround assertions are rejected.
+    #[inline]
+    pub fn extended() -> Self {
+        ASTParser {
+            extended: true,
+            nesting_limit: usize::MAX,
+            size_limit: usize::MAX,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Set the maximum depth to which groups and bracketed classes may be nested.
+    #[inline]
+    pub fn with_nesting_limit(mut self, nesting_limit: usize) -> Self {
+        self.nesting_limit = nesting_limit;
+        self
+    }
+
+    /// Set the maximum number of nodes in the syntax tree of an expression. See
+    /// [Parser::size_limit].
+    #[inline]
+    pub fn with_size_limit(mut self, size_limit: usize) -> Self {
+        self.size_limit = size_limit;
+        self
+    }
+}
+
+impl<T> Default for ASTParser<T>
+where
+    T: Clone + Eq + Hash + From<CharClass>,
+{
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Parser<ASTNode<T>> for ASTParser<T>
+where
+    T: Clone + Eq + Hash + From<CharClass>,
+{
+    /// Implement the shift action. A new leaf node is pushed to the parsing stack.
+    #[inline]
+    fn shift_action(&self, stack: &mut Vec<ASTNode<T>>, c: CharClass) -> ActionResult {
+        let new_node = ASTNode::Leaf(c.into());
+        stack.push(new_node);
+        Ok(())
+    }
+
+    /// Implement the reduce action for parsing. Child nodes are popped from the node stack
+    /// according to the operator, and a new node is constructed and pushed to the stack.
+    #[inline]
+    fn reduce_action(&self, stack: &mut Vec<ASTNode<T>>, op: Operator) -> ActionResult {
+        if self.extended {
+            if let Operator::Backreference(_) | Operator::Lookaround(_) = op {
+                return Err(ParseErrorKind::BacktrackingRequired);
+            }
+        }
+
+        let new_node;
+        if op == Operator::EmptyPlaceholder {
+            // A new blank leaf node is pushed to the stack if operator is an empty placeholder.
+            new_node = ASTNode::None;
+        } else {
+            // Otherwise, a new branch node is constructed from operands.
+            let node_op = op
+                .try_into()
+                .map_err(|_| ParseErrorKind::UnbalancedParentheses)?;
+            let c1: ASTNode<T>;
+            let c2: ASTNode<T>;
+
+            match node_op {
+                // Union, intersection and concatenation branch nodes are constructed from the 2
+                // topmost nodes.
+                ast::Operator::Union
+                | ast::Operator::Intersection
+                | ast::Operator::Concatenation => {
+                    c2 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                    c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                }
+                // A new node is constructed from the topmost node on the stack for kleene star,
+                // plus, optional, complement, capture and lookaround operators.
+                ast::Operator::KleeneStar
+                | ast::Operator::Complement
+                | ast::Operator::Plus
+                | ast::Operator::Optional
+                | ast::Operator::Capture(_, _)
+                | ast::Operator::Lookaround(_) => {
+                    c1 = stack.pop().ok_or(ParseErrorKind::UnbalancedOperators)?;
+                    c2 = ASTNode::None;
+                }
+                // Backreferences have no operands.
+                ast::Operator::Backreference(_) => {
+                    c1 = ASTNode::None;
+                    c2 = ASTNode::None;
+                }
+            }
+
+            new_node = ASTNode::Branch(node_op, Box::new(c1), Box::new(c2));
+        }
+
+        stack.push(new_node);
+        Ok(())
+    }
+
+    #[inline]
+    fn extended_operators(&self) -> bool {
+        self.extended
+    }
+
+    #[inline]
+    fn nesting_limit(&self) -> usize {
+        self.nesting_limit
+    }
+
+    #[inline]
+    fn size_limit(&self) -> usize {
+        self.size_limit
+    }
+}
+
+impl TryFrom<Operator> for ast::Operator {
+    type Error = ();
+
+    #[inline]
+    fn try_from(op: Operator) -> result::Result<Self, Self::Error> {
+        match op {
+            Operator::KleeneStar => Ok(Self::KleeneStar),
+            Operator::Plus => Ok(Self::Plus),
+            Operator::Optional => Ok(Self::Optional),
+            Operator::Concatenation => Ok(Self::Concatenation),
+            Operator::Union => Ok(Self::Union),
+            Operator::Intersection => Ok(Self::Intersection),
+            Operator::Complement => Ok(Self::Complement),
+            Operator::Capture(index, name) => Ok(Self::Capture(index, name)),
+            Operator::Backreference(index) => Ok(Self::Backreference(index)),
+            Operator::Lookaround(look) => Ok(Self::Lookaround(look)),
+            Operator::EmptyPlaceholder => Err(()),
+            Operator::LeftParen => Err(()),
+        }
+    }
+}
+
+/// Parser implementations must define the shift and reduce actions. The symbols in a regular
+/// expression are iterated through and parsed according to these functions.
+pub trait Parser<T>
+where
+    T: Clone,
+{
+    fn shift_action(&self, stack: &mut Vec<T>, c: CharClass) -> ActionResult;
+
+    fn reduce_action(&self, stack: &mut Vec<T>, op: Operator) -> ActionResult;
+
+    /// Determine if the intersection (`&`) and complement (`~`) operators are recognized. They
+    /// are literals otherwise.
+    #[inline]
+    fn extended_operators(&self) -> bool {
+        false
+    }
+
+    /// Return the maximum depth to which groups and bracketed classes may be nested. Deeper
+    /// expressions are rejected, since they are processed recursively.
+    #[inline]
+    fn nesting_limit(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Return the maximum number of nodes in the syntax tree of an expression: each character
+    /// class and each operator, including the implicit concatenation of adjacent items, is a node.
+    /// Larger expressions are rejected as soon as the limit is passed, before the rest is parsed.
+    #[inline]
+    fn size_limit(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Compile a regular expresion.
+    #[inline]
+    fn parse(&self, expr: &str) -> Result<Option<T>> {
+        let mut state = ParserState::new(
+            |stack, c| self.shift_action(stack, c),
+            |stack, op| self.reduce_action(stack, op),
+            self.nesting_limit(),
+            self.size_limit(),
+        );
+
+        let mut input = Input::new(expr);
+        while let Some(c) = input.next() {
+            let start = input.pos - 1;
+            match c {
+                '|' => state.handle_union(start..input.pos)?,
+                '&' if self.extended_operators() => state.handle_intersection(start..input.pos)?,
+                '~' if self.extended_operators() => state.handle_complement(start..input.pos)?,
+                '*' => state.handle_quantifier(Operator::KleeneStar, c, start..input.pos)?,
+                '+' => state.handle_quantifier(Operator::Plus, c, start..input.pos)?,
+                '?' => state.handle_quantifier(Operator::Optional, c, start..input.pos)?,
+                '(' => {
+                    let group = parse_group_kind(&mut input, start)?;
+                    state.handle_left_paren(group, start..input.pos)?;
+                }
+                ')' => state.handle_right_paren(start..input.pos)?,
+                '[' => {
+                    // The class is nested within the open groups.
+                    let depth = state.nesting_limit - state.paren_count_stack.len();
+                    let cc = parse_bracket_class(&mut input, start, depth)?;
+                    state.handle_char_class(cc, start..input.pos)?;
+                }
+                '\\' => match parse_escape(&mut input, start)? {
+                    Escape::Literal(c) => state.handle_literal_char(c, start..input.pos)?,
+                    Escape::Class(cc) => state.handle_char_class(cc, start..input.pos)?,
+                    Escape::Backreference(group) => {
+                        state.handle_backreference(group, start..input.pos)?
+                    }
+                    Escape::Quote(text) => {
+                        for c in text.chars() {
+                            state.handle_literal_char(c, start..input.pos)?;
+                        }
+                    }
+                    Escape::Linebreak => state.handle_linebreak(start..input.pos)?,
+                },
+                // Any character except newline.
+                '.' => state.handle_char_class(CharClass::all_but_newline(), start..input.pos)?,
+                // Everything else, including ^ and ] outside of brackets, is a literal.
+                _ => state.handle_literal_char(c, start..input.pos)?,
+            }
+        }
+
+        if expr.is_empty() {
+            state.op_stack.push((Operator::EmptyPlaceholder, 0..0));
+        }
+        state.check_operand()?;
+
+        while let Some((op, span)) = state.op_stack.pop() {
+            state.reduce(op, span)?;
+        }
+
+        let head = state.stack.into_iter().last();
+        Ok(head)
+    }
+}
+
+/// Set of valid operators.
+#[derive(Debug, PartialEq)]
+pub enum Operator {
+    Union,
+    /// Matches the strings matched by both operands.
+    Intersection,
+    Concatenation,
+    /// Matches the strings not matched by the following operand.
+    Complement,
+    KleeneStar,
+    Plus,
+    Optional,
+    LeftParen,
+    EmptyPlaceholder,
+    /// Marks the most recent operand as capture group with the given index and optional name.
+    Capture(usize, Option<String>),
+    /// Matches the text last captured by the group with the given index.
+    Backreference(usize),
+    /// Asserts that the most recent operand matches, or doesn't, next to the current position.
+    Lookaround(Look),
+}
+
+/// The kinds of lookaround assertions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Look {
+    /// `(?=...)`.
+    Ahead,
+    /// `(?!...)`.
+    NegativeAhead,
+    /// `(?<=...)`.
+    Behind,
+    /// `(?<!...)`.
+    NegativeBehind,
+}
+
+impl Look {
+    /// Determine if the assertion succeeds when its operand doesn't match.
+    #[inline]
+    pub fn is_negative(self) -> bool {
+        self == Self::NegativeAhead || self == Self::NegativeBehind
+    }
+
+    /// Determine if the operand must match text ending at the current position.
+    #[inline]
+    pub fn is_behind(self) -> bool {
+        self == Self::Behind || self == Self::NegativeBehind
+    }
+}
+
+/// The kind of group opened by a left parenthesis.
+#[derive(Debug)]
+enum GroupKind {
+    /// `(...)` or `(?<name>...)`.
+    Capture(Option<String>),
+    /// `(?:...)`.
+    NonCapture,
+    /// `(?=...)`, `(?!...)`, `(?<=...)` or `(?<!...)`.
+    Lookaround(Look),
+}
+
+/// Parse the group syntax following a left parenthesis at `start`, if any.
+#[inline]
+fn parse_group_kind(input: &mut Input, start: usize) -> Result<GroupKind> {
+    if !input.next_if_eq('?') {
+        return Ok(GroupKind::Capture(None));
+    }
+
+    if input.next_if_eq(':') {
+        return Ok(GroupKind::NonCapture);
+    }
+
+    let look = match (input.peek(), input.peek_nth(1)) {
+        (Some('='), _) => Some((Look::Ahead, 1)),
+        (Some('!'), _) => Some((Look::NegativeAhead, 1)),
+        (Some('<'), Some('=')) => Some((Look::Behind, 2)),
+        (Some('<'), Some('!')) => Some((Look::NegativeBehind, 2)),
+        _ => None,
+    };
+    if let Some((look, len)) = look {
+        input.pos += len;
+        return Ok(GroupKind::Lookaround(look));
+    }
+
+    // Named groups may be written as (?<name>...) or (?P<name>...).
+    input.next_if_eq('P');
+    if !input.next_if_eq('<') {
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidGroup,
+            start..input.pos,
+        ));
+    }
+
+    let name = parse_group_name(input, start, ParseErrorKind::InvalidGroup)?;
+    Ok(GroupKind::Capture(Some(name)))
+}
+
+/// Parse a group name terminated by `>`, the opening `<` of which has been consumed, for the
+/// construct beginning at `start`. If the input ends before the `>`, an error of the given kind is
+/// returned.
+#[inline]
+fn parse_group_name(
+    input: &mut Input,
+    start: usize,
+    unterminated: ParseErrorKind,
+) -> Result<String> {
+    let mut name = String::new();
+    loop {
+        match input.next() {
+            Some('>') => break,
+            Some(c) => name.push(c),
+            None => return Err(ParseError::new(unterminated, start..input.pos)),
+        }
+    }
+
+    let mut chars = name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if !valid {
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidGroupName(name),
+            start..input.pos,
+        ));
+    }
+
+    Ok(name)
+}
+
+/// The chars of an expression being parsed, along with the offset of the next char.
+#[derive(Debug)]
+struct Input {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Input {
+    #[inline]
+    fn new(expr: &str) -> Self {
+        Self {
+            chars: expr.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<char> {
+        self.peek_nth(0)
+    }
+
+    #[inline]
+    fn peek_nth(&self, n: usize) -> Option<char> {
+        self.chars.get(self.pos + n).copied()
+    }
+
+    #[inline]
+    fn next_if_eq(&mut self, c: char) -> bool {
+        let eq = self.peek() == Some(c);
+        if eq {
+            self.pos += 1;
+        }
+        eq
+    }
+}
+
+impl Iterator for Input {
+    type Item = char;
+
+    #[inline]
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+}
+
+/// The result of parsing an escape sequence.
+#[derive(Debug)]
+enum Escape {
+    Literal(char),
+    Class(CharClass),
+    Backreference(GroupRef),
+    /// The text between `\Q` and `\E`, taken literally.
+    Quote(String),
+    /// `\R`, which matches any line break, including `\r\n`.
+    Linebreak,
+}
+
+/// A reference to a capture group by index (`\1`) or by name (`\k<name>`).
+#[derive(Debug)]
+enum GroupRef {
+    Index(usize),
+    Name(String),
+}
+
+/// Parse the body of a bracketed character class, the opening bracket of which is at `start`. The
+/// body is a sequence of operands separated by the set operators `&&` (intersection), `--`
+/// (difference) and `~~` (symmetric difference), which are applied from left to right. At most
+/// `depth` levels of classes, including this one, may be nested.
+#[inline]
+fn parse_bracket_class(input: &mut Input, start: usize, depth: usize) -> Result<CharClass> {
+    if depth == 0 {
+        return Err(ParseError::new(
+            ParseErrorKind::NestingLimitExceeded,
+            start..input.pos,
+        ));
+    }
+
+    // A ^ immediately after the opening bracket negates the whole class.
+    let negated = input.next_if_eq('^');
+
+    let mut class = parse_class_operand(input, start, depth - 1)?;
+    while let Some(op) = peek_set_operator(input) {
+        input.pos += 2;
+        let operand = parse_class_operand(input, start, depth - 1)?;
+        class = match op {
+            '&' => class.intersection(&operand),
+            '-' => class.difference(&operand),
+            _ => class.symmetric_difference(&operand),
+        };
+    }
+    // The operand ended at the closing bracket.
+    input.pos += 1;
+
+    if negated {
+        class = class.complement();
+    }
+    Ok(class)
+}
+
+/// Return the first char of the set operator at the current position of the input, if any.
+#[inline]
+fn peek_set_operator(input: &Input) -> Option<char> {
+    match (input.peek(), input.peek_nth(1)) {
+        (Some(c), Some(d)) if c == d && (c == '&' || c == '-' || c == '~') => Some(c),
+        _ => None,
+    }
+}
+
+/// Parse the union of ranges, escapes and nested classes up to the next set operator or the
+/// closing bracket of the class opened at `start`, neither of which is consumed. At most `depth`
+/// levels of classes may be nested within it.
+#[inline]
+fn parse_class_operand(input: &mut Input, start: usize, depth: usize) -> Result<CharClass> {
+    let mut class = CharClass::new();
+    let mut empty = true;
+    loop {
+        let end = input.peek() == Some(']');
+        if end || peek_set_operator(input).is_some() {
+            if empty {
+                input.pos += if end { 1 } else { 2 };
+                return Err(ParseError::new(
+                    ParseErrorKind::EmptyCharacterClass,
+                    start..input.pos,
+                ));
+            }
+            return Ok(class);
+        }
+
+        let item_start = input.pos;
+        let item = match input.next() {
+            Some('[') => {
+                let nested = match parse_posix_class(input, item_start)? {
+                    Some(posix) => posix,
+                    None => parse_bracket_class(input, item_start, depth)?,
+                };
+                class.copy_from(&nested);
+                empty = false;
+                continue;
+            }
+            Some('\\') => parse_escape(input, item_start)?,
+            Some(c) => Escape::Literal(c),
+            None => {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnterminatedCharacterClass,
+                    start..input.pos,
+                ))
+            }
+        };
+        empty = false;
+
+        let lo = match item {
+            Escape::Literal(c) => c,
+            Escape::Class(cc) => {
+                class.copy_from(&cc);
+                continue;
+            }
+            Escape::Backreference(_) => {
+                return Err(ParseError::new(
+                    ParseErrorKind::BackreferenceInClass,
+                    item_start..input.pos,
+                ))
+            }
+            Escape::Linebreak => {
+                return Err(ParseError::new(
+                    ParseErrorKind::LinebreakInClass,
+                    item_start..input.pos,
+                ))
+            }
+            // Quoted characters are never the start of a range.
+            Escape::Quote(text) => {
+                for c in text.chars() {
+                    class.add_range(CharRange::new_single(c));
+                }
+                empty = empty && text.is_empty();
+                continue;
+            }
+        };
+
+        // A dash between two characters denotes a range, unless it is the last character in the
+        // class or is followed by a nested class or another dash.
+        let hi = match (input.peek(), input.peek_nth(1)) {
+            (Some('-'), Some(c)) if c != ']' && c != '[' && c != '-' => {
+                input.pos += 2;
+                if c == '\\' {
+                    let hi_start = input.pos - 1;
+                    match parse_escape(input, hi_start)? {
+                        Escape::Backreference(_) => {
+                            return Err(ParseError::new(
+                                ParseErrorKind::BackreferenceInClass,
+                                hi_start..input.pos,
+                            ))
+                        }
+                        Escape::Linebreak => {
+                            return Err(ParseError::new(
+                                ParseErrorKind::LinebreakInClass,
+                                hi_start..input.pos,
+                            ))
+                        }
+                        hi => hi,
+                    }
+                } else {
+                    Escape::Literal(c)
+                }
+            }
+            _ => {
+                class.add_range(CharRange::new_single(lo));
+                continue;
+            }
+        };
+
+        match hi {
+            Escape::Literal(hi) => {
+                if hi < lo {
+                    return Err(ParseError::new(
+                        ParseErrorKind::InvalidRange(lo, hi),
+                        item_start..input.pos,
+                    ));
+                }
+                class.add_range(CharRange::new(lo, hi));
+            }
+            // A range may not end in a class, so the dash is taken literally.
+            Escape::Class(cc) => {
+                class.add_range(CharRange::new_single(lo));
+                class.add_range(CharRange::new_single('-'));
+                class.copy_from(&cc);
+            }
+            // Neither may it end in quoted text.
+            Escape::Quote(text) => {
+                class.add_range(CharRange::new_single(lo));
+                class.add_range(CharRange::new_single('-'));
+                for c in text.chars() {
+                    class.add_range(CharRange::new_single(c));
+                }
+            }
+            Escape::Backreference(_) | Escape::Linebreak => unreachable!(),
+        }
+    }
+}
+
+/// Parse a POSIX class such as `[:alpha:]` or `[:^alpha:]` inside a bracketed class, the opening
+/// bracket of which is at `start` and has been consumed. If the bracket doesn't begin a POSIX
+/// class, nothing is consumed and `None` is returned, so that it may be parsed as a nested class.
+#[inline]
+fn parse_posix_class(input: &mut Input, start: usize) -> Result<Option<CharClass>> {
+    if input.peek() != Some(':') {
+        return Ok(None);
+    }
+
+    let negated = input.peek_nth(1) == Some('^');
+    let name_start = input.pos + if negated { 2 } else { 1 };
+    let name: String = input.chars[name_start..]
+        .iter()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    let name_end = name_start + name.len();
+    if name.is_empty()
+        || input.chars.get(name_end) != Some(&':')
+        || input.chars.get(name_end + 1) != Some(&']')
+    {
+        return Ok(None);
+    }
+
+    input.pos = name_end + 2;
+    let class = CharClass::posix_class(&name).ok_or_else(|| {
+        ParseError::new(ParseErrorKind::InvalidPosixClass(name), start..input.pos)
+    })?;
+    if negated {
+        Ok(Some(class.complement()))
+    } else {
+        Ok(Some(class))
+    }
+}
+
+/// Parse an escape sequence, the backslash of which is at `start`. Escaped characters without a
+/// special meaning are taken literally.
+#[inline]
+fn parse_escape(input: &mut Input, start: usize) -> Result<Escape> {
+    let c = input
+        .next()
+        .ok_or_else(|| ParseError::new(ParseErrorKind::DanglingEscape, start..input.pos))?;
+
+    let class = match c {
+        'd' => CharClass::decimal_number(),
+        'D' => CharClass::decimal_number().complement(),
+        'w' => CharClass::word(),
+        'W' => CharClass::word().complement(),
+        's' => CharClass::whitespace(),
+        'S' => CharClass::whitespace().complement(),
+        'h' => CharClass::horizontal_whitespace(),
+        'H' => CharClass::horizontal_whitespace().complement(),
+        'v' => CharClass::vertical_whitespace(),
+        'V' => CharClass::vertical_whitespace().complement(),
+        'R' => return Ok(Escape::Linebreak),
+        'n' => return Ok(Escape::Literal('\n')),
+        'r' => return Ok(Escape::Literal('\r')),
+        't' => return Ok(Escape::Literal('\t')),
+        // \0 may be followed by up to two more octal digits.
+        '0' => {
+            let mut value = 0;
+            for _ in 0..2 {
+                match input.peek().and_then(|d| d.to_digit(8)) {
+                    Some(d) => {
+                        value = value * 8 + d;
+                        input.pos += 1;
+                    }
+                    None => break,
+                }
+            }
+            return Ok(Escape::Literal(char::from(value as u8)));
+        }
+        'x' | 'u' => return parse_code_point(input, start, c).map(Escape::Literal),
+        'Q' => {
+            let mut text = String::new();
+            // Quoted text runs until \E or the end of the expression.
+            while let Some(c) = input.next() {
+                if c == '\\' && input.next_if_eq('E') {
+                    break;
+                }
+                text.push(c);
+            }
+            return Ok(Escape::Quote(text));
+        }
+        '1'..='9' => {
+            let mut index = c.to_digit(10).unwrap() as usize;
+            while let Some(d) = input.peek().and_then(|d| d.to_digit(10)) {
+                index = index.saturating_mul(10).saturating_add(d as usize);
+                input.pos += 1;
+            }
+            return Ok(Escape::Backreference(GroupRef::Index(index)));
+        }
+        'k' => {
+            if !input.next_if_eq('<') {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnterminatedEscape,
+                    start..input.pos,
+                ));
+            }
+            let name = parse_group_name(input, start, ParseErrorKind::UnterminatedEscape)?;
+            return Ok(Escape::Backreference(GroupRef::Name(name)));
+        }
+        'p' | 'P' => {
+            let class = parse_unicode_class(input, start)?;
+            if c == 'P' {
+                class.complement()
+            } else {
+                class
+            }
+        }
+        c => return Ok(Escape::Literal(c)),
+    };
+
+    Ok(Escape::Class(class))
+}
+
+/// Parse the code point of a hexadecimal escape, the backslash of which is at `start`. The code
+/// point is given either by a fixed number of digits (`\x41`, `\u00e9`) or by one to six digits
+/// between braces (`\x{41}`, `\u{1F600}`).
+#[inline]
+fn parse_code_point(input: &mut Input, start: usize, kind: char) -> Result<char> {
+    let unterminated =
+        |input: &Input| ParseError::new(ParseErrorKind::UnterminatedEscape, start..input.pos);
+
+    let digits = if input.next_if_eq('{') {
+        let mut digits = String::new();
+        loop {
+            match input.next() {
+                Some('}') => break digits,
+                Some(c) => digits.push(c),
+                None => return Err(unterminated(input)),
+            }
+        }
+    } else {
+        let len = if kind == 'x' { 2 } else { 4 };
+        let mut digits = String::new();
+        for _ in 0..len {
+            match input.peek() {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    digits.push(c);
+                    input.pos += 1;
+                }
+                _ => return Err(unterminated(input)),
+            }
+        }
+        digits
+    };
+
+    let valid =
+        !digits.is_empty() && digits.len() <= 6 && digits.chars().all(|c| c.is_ascii_hexdigit());
+    valid
+        .then(|| u32::from_str_radix(&digits, 16).ok())
+        .flatten()
+        .and_then(char::from_u32)
+        .ok_or_else(|| ParseError::new(ParseErrorKind::InvalidCodePoint(digits), start..input.pos))
+}
+
+/// Parse the name of a Unicode property class, given either as a single letter (`\pL`) or a braced
+/// name (`\p{Greek}`), the backslash of which is at `start`.
+#[inline]
+fn parse_unicode_class(input: &mut Input, start: usize) -> Result<CharClass> {
+    let name = match input.next() {
+        Some('{') => {
+            let mut name = String::new();
+            loop {
+                match input.next() {
+                    Some('}') => break name,
+                    Some(c) => name.push(c),
+                    None => {
+                        return Err(ParseError::new(
+                            ParseErrorKind::UnterminatedEscape,
+                            start..input.pos,
+                        ))
+                    }
+                }
+            }
+        }
+        Some(c) => c.to_string(),
+        None => {
+            return Err(ParseError::new(
+                ParseErrorKind::UnterminatedEscape,
+                start..input.pos,
+            ))
+        }
+    };
+
+    CharClass::unicode_class(&name).ok_or_else(|| {
+        ParseError::new(
+            ParseErrorKind::InvalidUnicodeProperty(name),
+            start..input.pos,
+        )
+    })
+}
+
+#[derive(Debug)]
+struct ParserState<T, SF, RF>
+where
+    SF: Copy + FnMut(&mut Vec<T>, CharClass) -> ActionResult,
+    RF: Copy + FnMut(&mut Vec<T>, Operator) -> ActionResult,
+{
+    stack: Vec<T>,
+    /// Operators yet to be reduced, along with the span of the symbol each was parsed from.
+    op_stack: Vec<(Operator, Range<usize>)>,
+    /// For each open group, the number of nodes on the stack before it and the operator, if any,
+    /// to apply to its contents when it is closed, along with the offset of its left parenthesis.
+    paren_count_stack: Vec<(usize, Option<(Operator, usize)>)>,
+    /// The names of the capture groups seen so far, indexed by capture index - 1.
+    capture_names: Vec<Option<String>>,
+
+    insert_concat: bool,
+    /// The maximum depth to which groups and bracketed classes may be nested.
+    nesting_limit: usize,
+    /// The maximum number of nodes, and the number of nodes shifted and reduced so far.
+    size_limit: usize,
+    size: usize,
+
+    shift_action: SF,
+    reduce_action: RF,
+}
+
+impl<T, SF, RF> ParserState<T, SF, RF>
+where
+    SF: Copy + FnMut(&mut Vec<T>, CharClass) -> ActionResult,
+    RF: Copy + FnMut(&mut Vec<T>, Operator) -> ActionResult,
+{
+    #[inline]
+    fn new(shift_action: SF, reduce_action: RF, nesting_limit: usize, size_limit: usize) -> Self {
+        Self {
+            stack: Vec::new(),
+            op_stack: Vec::new(),
+            paren_count_stack: Vec::new(),
+            capture_names: Vec::new(),
+
+            insert_concat: false,
+            nesting_limit,
+            size_limit,
+            size: 0,
+
+            shift_action,
+            reduce_action,
+        }
+    }
+
+    #[inline]
+    fn handle_literal_char(&mut self, c: char, span: Range<usize>) -> Result<()> {
+        let char_class = c.into();
+        self.handle_char_class(char_class, span)
+    }
+
+    #[inline]
+    fn handle_char_class(&mut self, c: CharClass, span: Range<usize>) -> Result<()> {
+        if self.insert_concat {
+            while self.precedence_reduce_stack(&Operator::Concatenation)? {}
+            self.op_stack.push((Operator::Concatenation, span.clone()));
+        }
+
+        self.count_node(&span)?;
+        (self.shift_action)(&mut self.stack, c).map_err(|kind| ParseError::new(kind, span))?;
+        self.insert_concat = true;
+
+        Ok(())
+    }
+
+    /// Handle `\R`, which is equivalent to `(?:\r\n|\v)`.
+    #[inline]
+    fn handle_linebreak(&mut self, span: Range<usize>) -> Result<()> {
+        self.handle_left_paren(GroupKind::NonCapture, span.clone())?;
+        self.handle_literal_char('\r', span.clone())?;
+        self.handle_literal_char('\n', span.clone())?;
+        self.handle_union(span.clone())?;
+        self.handle_char_class(CharClass::vertical_whitespace(), span.clone())?;
+        self.handle_right_paren(span)
+    }
+
+    /// Handle a backreference, which must refer to a capture group opened before it.
+    #[inline]
+    fn handle_backreference(&mut self, group: GroupRef, span: Range<usize>) -> Result<()> {
+        let index = match &group {
+            GroupRef::Index(index) => Some(*index).filter(|&i| i <= self.capture_names.len()),
+            GroupRef::Name(name) => self
+                .capture_names
+                .iter()
+                .position(|n| n.as_ref() == Some(name))
+                .map(|i| i + 1),
+        };
+        let index = index.ok_or_else(|| {
+            let name = match group {
+                GroupRef::Index(index) => index.to_string(),
+                GroupRef::Name(name) => name,
+            };
+            ParseError::new(ParseErrorKind::UndefinedGroup(name), span.clone())
+        })?;
+
+        if self.insert_concat {
+            while self.precedence_reduce_stack(&Operator::Concatenation)? {}
+            self.op_stack.push((Operator::Concatenation, span.clone()));
+        }
+
+        self.reduce(Operator::Backreference(index), span)?;
+        self.insert_concat = true;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn handle_union(&mut self, span: Range<usize>) -> Result<()> {
+        // The union operator must have a left operand.
+        if !self.insert_concat {
+            return Err(ParseError::new(ParseErrorKind::UnbalancedOperators, span));
+        }
+
+        let op = Operator::Union;
+        while self.precedence_reduce_stack(&op)? {}
+
+        self.op_stack.push((op, span));
+        self.insert_concat = false;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn handle_intersection(&mut self, span: Range<usize>) -> Result<()> {
+        // The intersection operator must have a left operand.
+        if !self.insert_concat {
+            return Err(ParseError::new(ParseErrorKind::UnbalancedOperators, span));
+        }
+
+        let op = Operator::Intersection;
+        while self.precedence_reduce_stack(&op)? {}
+
+        self.op_stack.push((op, span));
+        self.insert_concat = false;
+
+        Ok(())
+    }
+
+    /// Handle the complement operator, which applies to the following operand and any quantifiers
+    /// on it.
+    #[inline]
+    fn handle_complement(&mut self, span: Range<usize>) -> Result<()> {
+        if self.insert_concat {
+            while self.precedence_reduce_stack(&Operator::Concatenation)? {}
+            self.op_stack.push((Operator::Concatenation, span.clone()));
+        }
+
+        self.op_stack.push((Operator::Complement, span));
+        self.insert_concat = false;
+
+        Ok(())
+    }
+
+    /// Check that a complement operator isn't missing its operand before the end of a group or of
+    /// the expression.
+    #[inline]
+    fn check_operand(&self) -> Result<()> {
+        match self.op_stack.last() {
+            Some((Operator::Complement, span)) if !self.insert_concat => Err(ParseError::new(
+                ParseErrorKind::UnbalancedOperators,
+                span.clone(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle the kleene star, plus, or optional operator.
+    #[inline]
+    fn handle_quantifier(&mut self, op: Operator, c: char, span: Range<usize>) -> Result<()> {
+        // Quantifiers must follow something to repeat.
+        if !self.insert_concat {
+            return Err(ParseError::new(
+                ParseErrorKind::MisplacedQuantifier(c),
+                span,
+            ));
+        }
+
+        self.precedence_reduce_stack(&op)?;
+
+        self.op_stack.push((op, span));
+        self.insert_concat = true;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn handle_left_paren(&mut self, group: GroupKind, span: Range<usize>) -> Result<()> {
+        if self.paren_count_stack.len() >= self.nesting_limit {
+            return Err(ParseError::new(ParseErrorKind::NestingLimitExceeded, span));
+        }
+
+        // Capture groups are numbered by the order of their left parentheses, starting from 1.
+        let group_op = match group {
+            GroupKind::Capture(name) => {
+                if let Some(name) = &name {
+                    if self.capture_names.iter().flatten().any(|n| n == name) {
+                        return Err(ParseError::new(
+                            ParseErrorKind::DuplicateGroupName(name.clone()),
+                            span,
+                        ));
+                    }
+                }
+
+                self.capture_names.push(name.clone());
+                Some(Operator::Capture(self.capture_names.len(), name))
+            }
+            GroupKind::NonCapture => None,
+            GroupKind::Lookaround(look) => Some(Operator::Lookaround(look)),
+        };
+
+        let op = Operator::LeftParen;
+        if self.insert_concat {
+            while self.precedence_reduce_stack(&op)? {}
+            self.op_stack.push((Operator::Concatenation, span.clone()));
+        }
+
+        self.paren_count_stack
+            .push((self.stack.len(), group_op.map(|op| (op, span.start))));
+        self.op_stack.push((op, span));
+        self.insert_concat = false;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn handle_right_paren(&mut self, span: Range<usize>) -> Result<()> {
+        self.check_operand()?;
+        let (prev_node_count, group_op) = self
+            .paren_count_stack
+            .pop()
+            .ok_or_else(|| ParseError::new(ParseErrorKind::UnbalancedParentheses, span.clone()))?;
+
+        if self.last_op() == Some(&Operator::LeftParen) && prev_node_count == self.stack.len() {
+            // Nothing between the parentheses; replace them with an empty expression.
+            self.op_stack.pop();
+            self.reduce(Operator::EmptyPlaceholder, span.clone())?;
+        } else {
+            // Reduce everything since the matching left parenthesis, which must be on the stack.
+            while let Some((op, span)) = self.op_stack.pop() {
+                if op == Operator::LeftParen {
+                    break;
+                }
+                self.reduce(op, span)?;
+            }
+        }
+
+        if let Some((op, start)) = group_op {
+            self.reduce(op, start..span.end)?;
+        }
+
+        self.insert_concat = true;
+
+        Ok(())
+    }
+
+    #[inline]
+    fn last_op(&self) -> Option<&Operator> {
+        self.op_stack.last().map(|(op, _)| op)
+    }
+
+    /// Reduce an operator, attributing any error to the span of the operator.
+    #[inline]
+    fn reduce(&mut self, op: Operator, span: Range<usize>) -> Result<()> {
+        if op != Operator::EmptyPlaceholder {
+            self.count_node(&span)?;
+        }
+        (self.reduce_action)(&mut self.stack, op).map_err(|kind| ParseError::new(kind, span))
+    }
+
+    /// Count a node about to be shifted or reduced, failing if there would be more than the size
+    /// limit.
+    #[inline]
+    fn count_node(&mut self, span: &Range<usize>) -> Result<()> {
+        if self.size >= self.size_limit {
+            return Err(ParseError::new(
+                ParseErrorKind::SizeLimitExceeded,
+                span.clone(),
+            ));
+        }
+        self.size += 1;
+        Ok(())
+    }
+
+    #[inline]
+    fn precedence_reduce_stack(&mut self, op: &Operator) -> Result<bool> {
+        let reduce = match self.last_op() {
+            Some(last_op) => {
+                if last_op == op && *last_op != Operator::LeftParen {
+                    // If current op is the same as last, collapse the last.
+                    // If both of left parenthesis, do nothing
+                    true
+                } else if *op == Operator::Union {
+                    // If current op is alternation, collapse last if it is intersection, concat,
+                    // complement, kleene, plus, or optional.
+                    *last_op == Operator::Intersection
+                        || *last_op == Operator::Concatenation
+                        || *last_op == Operator::Complement
+                        || *last_op == Operator::KleeneStar
+                        || *last_op == Operator::Plus
+                        || *last_op == Operator::Optional
+                } else if *op == Operator::Intersection {
+                    // If current op is intersection, collapse last if it is concat, complement,
+                    // kleene, plus, or optional.
+                    *last_op == Operator::Concatenation
+                        || *last_op == Operator::Complement
+                        || *last_op == Operator::KleeneStar
+                        || *last_op == Operator::Plus
+                        || *last_op == Operator::Optional
+                } else if *op == Operator::Concatenation {
+                    // If current op is concat, collapse last if it is complement, kleene, plus, or
+                    // optional.
+                    *last_op == Operator::Complement
+                        || *last_op == Operator::KleeneStar
+                        || *last_op == Operator::Plus
+                        || *last_op == Operator::Optional
+                } else if *op == Operator::KleeneStar
+                    || *op == Operator::Plus
+                    || *op == Operator::Optional
+                {
+                    // If current op is kleene star, plus, or optional, collapse last only if it
+                    // is another of them, so that consecutive quantifiers apply in order; the
+                    // others bind more loosely.
+                    *last_op == Operator::KleeneStar
+                        || *last_op == Operator::Plus
+                        || *last_op == Operator::Optional
+                } else if *op == Operator::LeftParen {
+                    // If current op is left parenthesis, collapse last if it is complement, kleene
+                    // star, plus, or optional, which operate on a single node.
+                    *last_op == Operator::Complement
+                        || *last_op == Operator::KleeneStar
+                        || *last_op == Operator::Plus
+                        || *last_op == Operator::Optional
+                } else {
+                    false
+                }
+            }
+            None => false,
+        };
+
+        if reduce {
+            if let Some((op, span)) = self.op_stack.pop() {
+                self.reduce(op, span)?;
+            }
+        }
+
+        Ok(reduce)
+    }
+}
+
+/// Error returned when attempting to parse an invalid regular expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The kind of error.
+    pub kind: ParseErrorKind,
+    /// The range of char offsets in the expression at which the error occurred.
+    pub span: Range<usize>,
+}
+
+impl ParseError {
+    /// Create a new ParseError.
+    #[inline]
+    pub fn new(kind: ParseErrorKind, span: Range<usize>) -> Self {
+        Self { kind, span }
+    }
+
+    /// Return the char offset in the expression at which the error begins.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.span.start
+    }
+
+    /// Render the error against the expression it was produced from, underlining the offending
+    /// part of the expression with carets:
+    ///
+    /// ```text
+    /// invalid range 'z-a' at position 1
+    ///   |
+    ///   | [z-a]
+    ///   |  ^^^
+    /// ```
+    #[inline]
+    pub fn render(&self, expr: &str) -> String {
+        // Control characters are shown as spaces so that the carets stay aligned.
+        let line: String = expr
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .collect();
+        let carets = "^".repeat(self.span.len().max(1));
+
+        format!(
+            "{}\n  |\n  | {}\n  | {}{}",
+            self,
+            line,
+            " ".repeat(self.span.start),
+            carets
+        )
+    }
+
+    /// Find the byte range of the error within the source of a string literal containing the
+    /// expression, such as `"[z-a]"` or `r#"[z-a]"#`, for pointing at the error in the source of a
+    /// macro invocation. None is returned if the error can't be located, as when the literal
+    /// contains escapes, so that its contents differ from the expression.
+    #[inline]
+    pub fn literal_span(&self, expr: &str, literal: &str) -> Option<Range<usize>> {
+        // The contents of the literal start after the opening quote and any raw string prefix.
+        let start = literal.find('"')? + 1;
+        if literal.get(start..start + expr.len()) != Some(expr) {
+            return None;
+        }
+
+        let byte_offset = |i: usize| {
+            expr.char_indices()
+                .nth(i)
+                .map_or(expr.len(), |(offset, _)| offset)
+        };
+        let lo = start + byte_offset(self.span.start);
+        let hi = start + byte_offset(self.span.end).max(byte_offset(self.span.start) + 1);
+        Some(lo..hi)
+    }
+}
+
+impl fmt::Display for ParseError {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        write!(f, "{} at position {}", self.kind, self.span.start)
+    }
+}
+
+impl error::Error for ParseError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+/// The kinds of errors that may occur when parsing a regular expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// There are an invalid number of operators, or operands are missing.
+    UnbalancedOperators,
+    /// There are one or more sets of unclosed parentheses.
+    UnbalancedParentheses,
+    /// A quantifier (`*`, `+` or `?`) does not follow an expression to repeat.
+    MisplacedQuantifier(char),
+    /// Bracketed character classes may not empty.
+    EmptyCharacterClass,
+    /// A bracketed character class is missing its closing bracket.
+    UnterminatedCharacterClass,
+    /// The end of a character range is less than its start, as in `[z-a]`.
+    InvalidRange(char, char),
+    /// A backslash is the last character of the expression.
+    DanglingEscape,
+    /// A group starting with `(?` is not a non-capturing group, a named group or a lookaround
+    /// assertion.
+    InvalidGroup,
+    /// The name of a capture group is empty or not an identifier.
+    InvalidGroupName(String),
+    /// Two capture groups have the same name.
+    DuplicateGroupName(String),
+    /// An escape sequence that takes an argument, such as `\p{...}` or `\x..`, is incomplete.
+    UnterminatedEscape,
+    /// The name in a `\p{...}` or `\P{...}` class is not a known Unicode general category,
+    /// script or binary property.
+    InvalidUnicodeProperty(String),
+    /// A hexadecimal escape, such as `\x{...}` or `\u{...}`, doesn't give the hexadecimal value
+    /// of a Unicode scalar value.
+    InvalidCodePoint(String),
+    /// A backreference refers to a capture group that isn't defined before it.
+    UndefinedGroup(String),
+    /// A backreference appears in a bracketed character class.
+    BackreferenceInClass,
+    /// A `\R` line break, which may match two characters, appears in a bracketed character class.
+    LinebreakInClass,
+    /// The name in a POSIX class such as `[:alpha:]` is not a known class.
+    InvalidPosixClass(String),
+    /// The expression uses backreferences or lookaround assertions, which only the backtracking
+    /// engine supports.
+    BacktrackingRequired,
+    /// Groups or bracketed classes are nested more deeply than the parser's nesting limit.
+    NestingLimitExceeded,
+    /// The syntax tree of the expression has more nodes than the parser's size limit.
+    SizeLimitExceeded,
+}
+
+impl fmt::Display for ParseErrorKind {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        match self {
+            Self::UnbalancedOperators => write!(f, "unbalanced operators"),
+            Self::UnbalancedParentheses => write!(f, "unbalanced parentheses"),
+            Self::MisplacedQuantifier(c) => write!(f, "quantifier '{}' has nothing to repeat", c),
+            Self::EmptyCharacterClass => write!(f, "empty character class"),
+            Self::UnterminatedCharacterClass => write!(f, "unterminated character class"),
+            Self::InvalidRange(lo, hi) => write!(f, "invalid range '{}-{}'", lo, hi),
+            Self::DanglingEscape => write!(f, "dangling escape"),
+            Self::UnterminatedEscape => write!(f, "unterminated escape sequence"),
+            Self::InvalidGroup => write!(f, "invalid group syntax"),
+            Self::InvalidGroupName(name) => write!(f, "invalid capture group name '{}'", name),
+            Self::DuplicateGroupName(name) => {
+                write!(f, "duplicate capture group name '{}'", name)
+            }
+            Self::InvalidUnicodeProperty(name) => {
+                write!(f, "unknown Unicode property '{}'", name)
+            }
+            Self::InvalidCodePoint(digits) => write!(f, "invalid code point '{}'", digits),
+            Self::UndefinedGroup(name) => {
+                write!(f, "backreference to undefined group '{}'", name)
+            }
+            Self::BackreferenceInClass => {
+                write!(f, "backreference in character class")
+            }
+            Self::LinebreakInClass => write!(f, "line break escape in character class"),
+            Self::InvalidPosixClass(name) => write!(f, "unknown POSIX class '{}'", name),
+            Self::BacktrackingRequired => write!(
+                f,
+                "backreferences and lookaround require the backtracking engine"
+            ),
+            Self::NestingLimitExceeded => write!(f, "nesting limit exceeded"),
+            Self::SizeLimitExceeded => write!(f, "size limit exceeded"),
+        }
+    }
+}
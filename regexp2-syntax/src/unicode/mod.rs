@@ -0,0 +1,97 @@
+mod general_category;
+mod property_bool;
+mod property_names;
+mod property_values;
+mod script;
+
+pub use general_category::{DECIMAL_NUMBER, LETTER};
+
+/// A table of character ranges, as generated by `ucd-generate`.
+pub type Ranges = &'static [(char, char)];
+
+/// Look up the ranges of a Unicode general category by any of its names or aliases, such as `Lu`
+/// or `Uppercase_Letter`.
+#[inline]
+pub fn general_category(name: &str) -> Option<Ranges> {
+    let canonical = canonical_value("General_Category", name)?;
+    find_table(general_category::BY_NAME, canonical)
+}
+
+/// Look up the ranges of a Unicode script by any of its names or aliases, such as `Greek` or
+/// `Grek`.
+#[inline]
+pub fn script(name: &str) -> Option<Ranges> {
+    let canonical = canonical_value("Script", name)?;
+    find_table(script::BY_NAME, canonical)
+}
+
+/// Look up the ranges of a Unicode binary property by any of its names or aliases, such as
+/// `XID_Start` or `XIDS`.
+#[inline]
+pub fn binary_property(name: &str) -> Option<Ranges> {
+    let normalized = normalize(name);
+    let canonical = property_names::PROPERTY_NAMES
+        .binary_search_by_key(&normalized.as_str(), |&(alias, _)| alias)
+        .ok()
+        .map(|i| property_names::PROPERTY_NAMES[i].1)?;
+    find_table(property_bool::BY_NAME, canonical)
+}
+
+/// Look up the ranges described by the body of a `\p{...}` escape. The body is either a
+/// `property=value` (or `property:value`) pair, where the property is a general category or
+/// script, or a lone name. Lone names are tried as a general category, then as a script, then as
+/// a binary property.
+#[inline]
+pub fn property(query: &str) -> Option<Ranges> {
+    match query.find(['=', ':']) {
+        Some(i) => {
+            let (name, value) = (&query[..i], &query[i + 1..]);
+            match normalize(name).as_str() {
+                "gc" | "generalcategory" => general_category(value),
+                "sc" | "script" => script(value),
+                _ => None,
+            }
+        }
+        None => general_category(query)
+            .or_else(|| script(query))
+            .or_else(|| binary_property(query)),
+    }
+}
+
+/// Find the canonical name of a value of the given property.
+#[inline]
+fn canonical_value(property: &str, value: &str) -> Option<&'static str> {
+    let values = property_values::PROPERTY_VALUES
+        .binary_search_by_key(&property, |&(name, _)| name)
+        .ok()
+        .map(|i| property_values::PROPERTY_VALUES[i].1)?;
+
+    let normalized = normalize(value);
+    values
+        .binary_search_by_key(&normalized.as_str(), |&(alias, _)| alias)
+        .ok()
+        .map(|i| values[i].1)
+}
+
+#[inline]
+fn find_table(tables: &'static [(&'static str, Ranges)], canonical: &str) -> Option<Ranges> {
+    tables
+        .binary_search_by_key(&canonical, |&(name, _)| name)
+        .ok()
+        .map(|i| tables[i].1)
+}
+
+/// Normalize a property name or value for loose matching according to UAX #44 (LM3): case,
+/// spaces, underscores, hyphens and any leading `is` are ignored.
+#[inline]
+fn normalize(name: &str) -> String {
+    let name = match name.get(..2) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("is") => &name[2..],
+        _ => name,
+    };
+
+    name.chars()
+        .filter(|&c| c != ' ' && c != '_' && c != '-')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
@@ -0,0 +1,583 @@
+use crate::ast::{ASTNode, Node, Operator};
+use crate::class::CharClass;
+use crate::derivative::Derivative;
+use crate::parser::{self, ASTParser, Look, Parser};
+use crate::regexp::Engine;
+
+use std::collections::{HashMap, HashSet};
+use std::error;
+use std::fmt;
+
+/// A regular expression engine that matches by backtracking. It supports backreferences (`\1`,
+/// `\k<name>`) and lookaround assertions (`(?=...)`, `(?!...)`, `(?<=...)`, `(?<!...)`), which
+/// the automata-based engines can't express.
+///
+/// Unlike the automata-based engines, matches are found in priority order rather than by length:
+/// alternatives are tried from left to right and repetitions are greedy. The number of steps taken
+/// by each search is limited, so that expressions prone to catastrophic backtracking fail quickly
+/// instead of hanging; through the [Engine] trait, a search that exceeds the limit finds no match.
+#[derive(Debug, Clone)]
+pub struct Backtrack {
+    program: Program,
+    step_limit: usize,
+}
+
+impl Backtrack {
+    /// The default maximum number of steps taken by a single search.
+    pub const DEFAULT_STEP_LIMIT: usize = 1 << 20;
+
+    /// Compile a regular expression for the backtracking engine.
+    #[inline]
+    pub fn new(expr: &str) -> parser::Result<Self> {
+        let ast = ASTParser::new().parse(expr)?.unwrap();
+        Ok(Program::new(&ast).into())
+    }
+
+    /// Return the maximum number of steps taken by a single search.
+    #[inline]
+    pub fn step_limit(&self) -> usize {
+        self.step_limit
+    }
+
+    /// Set the maximum number of steps taken by a single search.
+    #[inline]
+    pub fn set_step_limit(&mut self, step_limit: usize) {
+        self.step_limit = step_limit;
+    }
+
+    /// Determine if the given input string is within the language described by the regular
+    /// expression, failing if the step limit is exceeded.
+    #[inline]
+    pub fn try_is_match(&self, input: &str) -> Result<bool, StepLimitExceeded> {
+        let end = self.exec(input).run(0, 0, Some(input.len()))?;
+        Ok(end.is_some())
+    }
+
+    /// Find the first match in priority order beginning at the given byte offset, returning the
+    /// byte offset of its end, failing if the step limit is exceeded.
+    #[inline]
+    pub fn try_find_at(
+        &self,
+        input: &str,
+        start: usize,
+    ) -> Result<Option<usize>, StepLimitExceeded> {
+        self.exec(input).run(0, start, None)
+    }
+
+    /// Find the shortest match beginning at the given byte offset, returning the byte offset of
+    /// its end, failing if the step limit is exceeded.
+    #[inline]
+    pub fn try_find_shortest_at(
+        &self,
+        input: &str,
+        start: usize,
+    ) -> Result<Option<usize>, StepLimitExceeded> {
+        let mut exec = self.exec(input);
+        let first = match exec.run(0, start, None)? {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+
+        // The shortest match is no longer than the first one found, so only the ends before it
+        // need to be tried.
+        for (b, _) in input[start..first].char_indices() {
+            if exec.run(0, start, Some(start + b))?.is_some() {
+                return Ok(Some(start + b));
+            }
+        }
+        Ok(Some(first))
+    }
+
+    #[inline]
+    fn exec<'a>(&'a self, input: &'a str) -> Exec<'a> {
+        Exec::new(&self.program, input, self.step_limit)
+    }
+}
+
+impl From<Program> for Backtrack {
+    #[inline]
+    fn from(program: Program) -> Self {
+        Self {
+            program,
+            step_limit: Self::DEFAULT_STEP_LIMIT,
+        }
+    }
+}
+
+impl Engine for Backtrack {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        self.try_is_match(input).unwrap_or(false)
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<usize> {
+        self.try_find_at(input, start).unwrap_or(None)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize> {
+        self.try_find_shortest_at(input, start).unwrap_or(None)
+    }
+}
+
+/// Error returned when a search by the backtracking engine exceeds its step limit.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepLimitExceeded;
+
+impl fmt::Display for StepLimitExceeded {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        write!(f, "backtracking step limit exceeded")
+    }
+}
+
+impl error::Error for StepLimitExceeded {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        None
+    }
+}
+
+/// A regular expression compiled into instructions for a backtracking matcher. This is used by
+/// the backtracking engine, and to find the positions of capture groups within a match already
+/// found by another engine.
+#[derive(Debug, Clone)]
+pub(crate) struct Program {
+    insts: Vec<Inst>,
+    /// The number of capture groups, including the implicit group of the whole match.
+    captures_len: usize,
+    /// The names of the named capture groups mapped to their indices.
+    capture_names: HashMap<String, usize>,
+    /// Whether the program contains backreferences.
+    backrefs: bool,
+    /// Whether the program contains lookaround assertions.
+    lookaround: bool,
+    /// The number of repetition marks, the slots of which follow the capture slots.
+    marks: usize,
+    /// The engines matching the intersections and complements in the expression.
+    subs: Vec<Derivative>,
+}
+
+#[derive(Debug, Clone)]
+enum Inst {
+    /// Consume a char in the class.
+    Char(CharClass),
+    /// Continue at the first instruction, then backtrack to the second.
+    Split(usize, usize),
+    Jump(usize),
+    /// Record the current position in a capture slot.
+    Save(usize),
+    /// Record the current position in a repetition mark.
+    Mark(usize),
+    /// Fail unless the current position has moved past a repetition mark.
+    Progress(usize),
+    /// Consume the text last captured by a group.
+    Backref(usize),
+    /// Assert that the sub-program starting at the next instruction matches, or doesn't, next to
+    /// the current position, then continue at the given instruction.
+    Look(Look, usize),
+    /// Consume the text matched by an intersection or complement, trying the longest text first.
+    Sub(usize),
+    Match,
+}
+
+/// The steps on the stack of the compiler.
+enum Step<'a> {
+    Compile(&'a ASTNode<CharClass>),
+    Push(Inst),
+    /// Jump over the second alternative of a union once the first is compiled, and compile the
+    /// second alternative where the split at the given instruction continues.
+    Alternative(usize, &'a ASTNode<CharClass>),
+    /// Loop back to the start of a one-or-more repetition with the given mark.
+    Repeat(usize, usize),
+    /// Replace a placeholder instruction with one continuing after the instructions emitted so
+    /// far.
+    Fill(usize, Hole),
+}
+
+/// The kinds of placeholder instructions.
+enum Hole {
+    Split,
+    Jump,
+    Look(Look),
+}
+
+/// The jobs on the stack of the backtracking matcher.
+enum Job {
+    Step(usize, usize),
+    /// Restore a capture slot to its previous value when backtracking past its Save.
+    Restore(usize, Option<usize>),
+}
+
+impl Program {
+    /// Compile an AST into a program.
+    #[inline]
+    pub(crate) fn new(ast: &ASTNode<CharClass>) -> Self {
+        let mut program = Self {
+            insts: Vec::new(),
+            captures_len: 1,
+            capture_names: HashMap::new(),
+            backrefs: false,
+            lookaround: false,
+            marks: 0,
+            subs: Vec::new(),
+        };
+        program.compile(ast);
+        program.insts.push(Inst::Match);
+        program
+    }
+
+    /// Return the number of capture groups, including the implicit group of the whole match.
+    #[inline]
+    pub(crate) fn captures_len(&self) -> usize {
+        self.captures_len
+    }
+
+    #[inline]
+    pub(crate) fn capture_names(&self) -> &HashMap<String, usize> {
+        &self.capture_names
+    }
+
+    /// Determine if the program uses features that only the backtracking engine supports.
+    #[inline]
+    pub(crate) fn needs_backtracking(&self) -> bool {
+        self.backrefs || self.lookaround
+    }
+
+    /// Compile a node, and the nodes below it, with an explicit stack of steps rather than by
+    /// recursion, since the syntax trees of long expressions are deep.
+    #[inline]
+    fn compile(&mut self, node: &ASTNode<CharClass>) {
+        let mut steps = vec![Step::Compile(node)];
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Compile(node) => self.compile_node(node, &mut steps),
+                Step::Push(inst) => self.insts.push(inst),
+                Step::Alternative(split, c2) => {
+                    let jump = self.push_hole();
+                    self.insts[split] = Inst::Split(split + 1, self.insts.len());
+                    steps.push(Step::Fill(jump, Hole::Jump));
+                    steps.push(Step::Compile(c2));
+                }
+                Step::Repeat(start, mark) => {
+                    let split = self.insts.len();
+                    self.insts.push(Inst::Split(split + 1, split + 3));
+                    self.insts.push(Inst::Progress(mark));
+                    self.insts.push(Inst::Jump(start));
+                }
+                Step::Fill(hole, kind) => {
+                    let next = self.insts.len();
+                    self.insts[hole] = match kind {
+                        Hole::Split => Inst::Split(hole + 1, next),
+                        Hole::Jump => Inst::Jump(next),
+                        Hole::Look(look) => Inst::Look(look, next),
+                    };
+                }
+            }
+        }
+    }
+
+    /// Emit the instructions that precede the operands of a node, and push the steps that compile
+    /// its operands and follow them. Steps are popped in the reverse order they are pushed.
+    #[inline]
+    fn compile_node<'a>(&mut self, node: &'a ASTNode<CharClass>, steps: &mut Vec<Step<'a>>) {
+        let (op, c1, c2) = match node {
+            Node::Leaf(c) => {
+                self.insts.push(Inst::Char(c.clone()));
+                return;
+            }
+            Node::None => return,
+            Node::Branch(op, c1, c2) => (op, c1, c2),
+        };
+
+        match op {
+            Operator::Concatenation => {
+                steps.push(Step::Compile(c2));
+                steps.push(Step::Compile(c1));
+            }
+            Operator::Union => {
+                let split = self.push_hole();
+                steps.push(Step::Alternative(split, c2));
+                steps.push(Step::Compile(c1));
+            }
+            // Repetitions are greedy; the body is preferred over skipping it. Without
+            // memoization, an iteration that doesn't consume any input is rejected, so that the
+            // matcher can't loop forever.
+            Operator::KleeneStar => {
+                let mark = self.push_mark();
+                let split = self.push_hole();
+                self.insts.push(Inst::Mark(mark));
+                steps.push(Step::Fill(split, Hole::Split));
+                steps.push(Step::Push(Inst::Jump(split)));
+                steps.push(Step::Push(Inst::Progress(mark)));
+                steps.push(Step::Compile(c1));
+            }
+            Operator::Plus => {
+                let mark = self.push_mark();
+                let start = self.insts.len();
+                self.insts.push(Inst::Mark(mark));
+                steps.push(Step::Repeat(start, mark));
+                steps.push(Step::Compile(c1));
+            }
+            Operator::Optional => {
+                let split = self.push_hole();
+                steps.push(Step::Fill(split, Hole::Split));
+                steps.push(Step::Compile(c1));
+            }
+            Operator::Capture(index, name) => {
+                self.captures_len = self.captures_len.max(index + 1);
+                if let Some(name) = name {
+                    self.capture_names.insert(name.clone(), *index);
+                }
+
+                self.insts.push(Inst::Save(2 * index));
+                steps.push(Step::Push(Inst::Save(2 * index + 1)));
+                steps.push(Step::Compile(c1));
+            }
+            Operator::Backreference(index) => {
+                self.backrefs = true;
+                self.insts.push(Inst::Backref(*index));
+            }
+            // The operand is compiled as a sub-program ending in its own Match.
+            Operator::Lookaround(look) => {
+                self.lookaround = true;
+                let hole = self.push_hole();
+                steps.push(Step::Fill(hole, Hole::Look(*look)));
+                steps.push(Step::Push(Inst::Match));
+                steps.push(Step::Compile(c1));
+            }
+            // Intersections and complements can't be matched by instructions, so they are matched
+            // by derivatives instead. Groups within them never participate in a match.
+            Operator::Intersection | Operator::Complement => {
+                self.declare_captures(node);
+                self.subs.push(Derivative::from_ast(node));
+                self.insts.push(Inst::Sub(self.subs.len() - 1));
+            }
+        }
+    }
+
+    /// Count the capture groups in an AST without compiling it.
+    #[inline]
+    fn declare_captures(&mut self, node: &ASTNode<CharClass>) {
+        let mut nodes = vec![node];
+        while let Some(node) = nodes.pop() {
+            if let Node::Branch(op, c1, c2) = node {
+                if let Operator::Capture(index, name) = op {
+                    self.captures_len = self.captures_len.max(index + 1);
+                    if let Some(name) = name {
+                        self.capture_names.insert(name.clone(), *index);
+                    }
+                }
+                nodes.push(c2);
+                nodes.push(c1);
+            }
+        }
+    }
+
+    /// Allocate a slot for marking the position at the start of each iteration of a repetition.
+    #[inline]
+    fn push_mark(&mut self) -> usize {
+        self.marks += 1;
+        self.marks - 1
+    }
+
+    /// Push a placeholder instruction to be replaced once its targets are known.
+    #[inline]
+    fn push_hole(&mut self) -> usize {
+        self.insts.push(Inst::Match);
+        self.insts.len() - 1
+    }
+
+    /// Find the positions of the capture groups for a match spanning the given byte offsets of
+    /// the input. Each group is given as a pair of byte offsets into the input, and groups that
+    /// didn't participate in the match are None.
+    ///
+    /// The step limit isn't enforced here: the match has already been found, either by an engine
+    /// that visits each (instruction, position) pair at most once or by the backtracking engine,
+    /// which reaches the same match in as many steps as it took to find it.
+    #[inline]
+    pub(crate) fn captures(
+        &self,
+        input: &str,
+        start: usize,
+        end: usize,
+    ) -> Option<Vec<Option<(usize, usize)>>> {
+        let mut exec = Exec::new(self, input, usize::MAX);
+        exec.run(0, start, Some(end)).ok()??;
+
+        exec.slots[0] = Some(start);
+        exec.slots[1] = Some(end);
+        let groups = exec.slots[..2 * self.captures_len]
+            .chunks(2)
+            .map(|s| match (s[0], s[1]) {
+                (Some(start), Some(end)) => Some((start, end)),
+                _ => None,
+            })
+            .collect();
+        Some(groups)
+    }
+}
+
+/// The state of a single search by the backtracking matcher.
+struct Exec<'a> {
+    program: &'a Program,
+    input: &'a str,
+    /// The byte offsets recorded by each capture slot.
+    slots: Vec<Option<usize>>,
+    /// The number of steps that may still be taken.
+    steps_left: usize,
+}
+
+impl<'a> Exec<'a> {
+    #[inline]
+    fn new(program: &'a Program, input: &'a str, step_limit: usize) -> Self {
+        Self {
+            program,
+            input,
+            slots: vec![None; 2 * program.captures_len + program.marks],
+            steps_left: step_limit,
+        }
+    }
+
+    /// Run the program from the given instruction and byte offset until a Match instruction is
+    /// reached, at the given end offset if any, returning the offset at which it was reached.
+    ///
+    /// Without backreferences, an (instruction, position) pair that has already failed to lead to
+    /// a match will fail again, so each pair is visited at most once. Backreferences make the
+    /// outcome depend on the captured text, so every path must be explored, bounded only by the
+    /// step limit.
+    #[inline]
+    fn run(
+        &mut self,
+        pc: usize,
+        pos: usize,
+        end: Option<usize>,
+    ) -> Result<Option<usize>, StepLimitExceeded> {
+        let memoize = !self.program.backrefs;
+        let mut visited = HashSet::new();
+        let mut stack = vec![Job::Step(pc, pos)];
+
+        while let Some(job) = stack.pop() {
+            let (mut pc, mut pos) = match job {
+                Job::Step(pc, pos) => (pc, pos),
+                Job::Restore(slot, value) => {
+                    self.slots[slot] = value;
+                    continue;
+                }
+            };
+
+            loop {
+                if self.steps_left == 0 {
+                    return Err(StepLimitExceeded);
+                }
+                self.steps_left -= 1;
+
+                if memoize && !visited.insert((pc, pos)) {
+                    break;
+                }
+
+                match &self.program.insts[pc] {
+                    Inst::Char(c) => match self.input[pos..].chars().next() {
+                        Some(is) if c.contains(is) => {
+                            pc += 1;
+                            pos += is.len_utf8();
+                        }
+                        _ => break,
+                    },
+                    Inst::Split(first, second) => {
+                        stack.push(Job::Step(*second, pos));
+                        pc = *first;
+                    }
+                    Inst::Jump(target) => pc = *target,
+                    Inst::Save(slot) => {
+                        stack.push(Job::Restore(*slot, self.slots[*slot]));
+                        self.slots[*slot] = Some(pos);
+                        pc += 1;
+                    }
+                    Inst::Mark(mark) => {
+                        let slot = 2 * self.program.captures_len + mark;
+                        stack.push(Job::Restore(slot, self.slots[slot]));
+                        self.slots[slot] = Some(pos);
+                        pc += 1;
+                    }
+                    Inst::Progress(mark) => {
+                        // Memoization already rules out endless loops, and the outcome of a pair
+                        // mustn't depend on the marks.
+                        let slot = 2 * self.program.captures_len + mark;
+                        if !memoize && self.slots[slot] == Some(pos) {
+                            break;
+                        }
+                        pc += 1;
+                    }
+                    Inst::Backref(index) => {
+                        let captured = match (self.slots[2 * index], self.slots[2 * index + 1]) {
+                            (Some(s), Some(e)) if s <= e => &self.input[s..e],
+                            // A group that hasn't participated in the match matches nothing.
+                            _ => break,
+                        };
+
+                        if !self.input[pos..].starts_with(captured) {
+                            break;
+                        }
+                        pc += 1;
+                        pos += captured.len();
+                    }
+                    Inst::Look(look, next) => {
+                        let (look, next) = (*look, *next);
+                        let saved = self.slots.clone();
+                        let matched = self.look(look, pc + 1, pos)?;
+
+                        if matched == look.is_negative() {
+                            self.slots = saved;
+                            break;
+                        }
+
+                        // Captures made by a positive assertion are kept, and restored when
+                        // backtracking past it.
+                        for (slot, value) in saved.into_iter().enumerate() {
+                            if self.slots[slot] != value {
+                                stack.push(Job::Restore(slot, value));
+                            }
+                        }
+                        pc = next;
+                    }
+                    Inst::Sub(i) => {
+                        // The longest text is pushed last, so that it is tried first.
+                        for end in self.program.subs[*i].match_ends(self.input, pos) {
+                            stack.push(Job::Step(pc + 1, end));
+                        }
+                        break;
+                    }
+                    Inst::Match => {
+                        if end.is_some_and(|end| end != pos) {
+                            break;
+                        }
+                        return Ok(Some(pos));
+                    }
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Determine if the sub-program of a lookaround assertion starting at the given instruction
+    /// matches next to the given byte offset.
+    #[inline]
+    fn look(&mut self, look: Look, pc: usize, pos: usize) -> Result<bool, StepLimitExceeded> {
+        if !look.is_behind() {
+            return Ok(self.run(pc, pos, None)?.is_some());
+        }
+
+        // Try each start position, nearest first, for a match ending at the current position, so
+        // that the assertion matches the shortest text it can.
+        let starts = self.input[..pos].char_indices().map(|(b, _)| b).rev();
+        for start in std::iter::once(pos).chain(starts) {
+            if self.run(pc, start, Some(pos))?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
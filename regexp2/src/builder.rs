@@ -0,0 +1,237 @@
+use crate::ast::ASTNode;
+use crate::backtrack::{Backtrack, Program};
+use crate::class::CharClass;
+use crate::literal::Prefilter;
+use crate::parser::{ASTParser, NFAParser, ParseError, Parser};
+use crate::regexp::{AutoEngine, RegExp};
+
+use std::error;
+use std::fmt;
+
+use automata::{DFA, NFA};
+
+/// A builder of regular expressions that bounds the resources used to compile them, so that
+/// untrusted expressions fail with a [BuildError] instead of exhausting time or memory. The same
+/// builder may be used to compile any number of expressions.
+#[derive(Debug, Clone)]
+pub struct RegExpBuilder {
+    nfa_state_limit: usize,
+    dfa_state_limit: usize,
+    nesting_limit: usize,
+    size_limit: usize,
+    step_limit: usize,
+}
+
+impl RegExpBuilder {
+    /// The default maximum number of states in the NFA of an expression.
+    pub const DEFAULT_NFA_STATE_LIMIT: usize = 1 << 16;
+    /// The default maximum number of states in the DFA of an expression.
+    pub const DEFAULT_DFA_STATE_LIMIT: usize = 1 << 12;
+    /// The default maximum depth to which groups and bracketed classes may be nested.
+    pub const DEFAULT_NESTING_LIMIT: usize = 128;
+    /// The default maximum number of nodes in the syntax tree of an expression.
+    pub const DEFAULT_SIZE_LIMIT: usize = 1 << 12;
+
+    /// Create a builder with the default limits.
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            nfa_state_limit: Self::DEFAULT_NFA_STATE_LIMIT,
+            dfa_state_limit: Self::DEFAULT_DFA_STATE_LIMIT,
+            nesting_limit: Self::DEFAULT_NESTING_LIMIT,
+            size_limit: Self::DEFAULT_SIZE_LIMIT,
+            step_limit: Backtrack::DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    /// Set the maximum number of states in the NFA of an expression. This applies to the engines
+    /// built from NFAs.
+    #[inline]
+    pub fn with_nfa_state_limit(mut self, nfa_state_limit: usize) -> Self {
+        self.nfa_state_limit = nfa_state_limit;
+        self
+    }
+
+    /// Set the maximum number of states in the DFA of an expression. The number of states may be
+    /// exponential in the length of the expression, so the subset construction stops once the
+    /// limit is reached.
+    #[inline]
+    pub fn with_dfa_state_limit(mut self, dfa_state_limit: usize) -> Self {
+        self.dfa_state_limit = dfa_state_limit;
+        self
+    }
+
+    /// Set the maximum depth to which groups and bracketed classes may be nested.
+    #[inline]
+    pub fn with_nesting_limit(mut self, nesting_limit: usize) -> Self {
+        self.nesting_limit = nesting_limit;
+        self
+    }
+
+    /// Set the maximum number of nodes in the syntax tree of an expression: each character class
+    /// and each operator, including the implicit concatenation of adjacent items, is a node. The
+    /// limit is checked while parsing, before anything is compiled, and bounds the size of the
+    /// NFA and of the other compiled forms of the expression.
+    #[inline]
+    pub fn with_size_limit(mut self, size_limit: usize) -> Self {
+        self.size_limit = size_limit;
+        self
+    }
+
+    /// Set the maximum number of steps the backtracking engine may take when trying to match at
+    /// each position of an input string.
+    #[inline]
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.step_limit = step_limit;
+        self
+    }
+
+    /// Compile a regular expression that uses an NFA to evaluate input strings.
+    #[inline]
+    pub fn build(&self, expr: &str) -> Result<RegExp<NFA<CharClass>>, BuildError> {
+        let nfa = self.nfa(expr)?;
+        let ast = self.ast(expr)?;
+        Ok(RegExp::from_parts(
+            expr,
+            nfa,
+            Program::new(&ast),
+            Prefilter::new(&ast),
+        ))
+    }
+
+    /// Compile a regular expression that uses a DFA to evaluate input strings.
+    #[inline]
+    pub fn build_with_dfa(&self, expr: &str) -> Result<RegExp<DFA<CharClass>>, BuildError> {
+        let dfa = self.dfa(self.nfa(expr)?)?;
+        let ast = self.ast(expr)?;
+        Ok(RegExp::from_parts(
+            expr,
+            dfa,
+            Program::new(&ast),
+            Prefilter::new(&ast),
+        ))
+    }
+
+    /// Compile a regular expression that uses a backtracking engine, limited to the step limit of
+    /// the builder, to evaluate input strings.
+    #[inline]
+    pub fn build_with_backtrack(&self, expr: &str) -> Result<RegExp<Backtrack>, BuildError> {
+        let ast = self.ast(expr)?;
+        let program = Program::new(&ast);
+        Ok(RegExp::from_parts(
+            expr,
+            self.backtrack(program.clone()),
+            program,
+            Prefilter::new(&ast),
+        ))
+    }
+
+    /// Compile a regular expression that uses a DFA to evaluate input strings, unless the
+    /// expression contains backreferences or lookaround assertions, in which case the
+    /// backtracking engine is used.
+    #[inline]
+    pub fn build_auto(&self, expr: &str) -> Result<RegExp<AutoEngine>, BuildError> {
+        let ast = self.ast(expr)?;
+        let program = Program::new(&ast);
+
+        let engine = if program.needs_backtracking() {
+            AutoEngine::Backtrack(self.backtrack(program.clone()))
+        } else {
+            AutoEngine::DFA(self.dfa(self.nfa(expr)?)?)
+        };
+
+        Ok(RegExp::from_parts(
+            expr,
+            engine,
+            program,
+            Prefilter::new(&ast),
+        ))
+    }
+
+    #[inline]
+    fn nfa(&self, expr: &str) -> Result<NFA<CharClass>, BuildError> {
+        let nfa = NFAParser::new()
+            .with_nesting_limit(self.nesting_limit)
+            .with_size_limit(self.size_limit)
+            .parse(expr)?
+            .unwrap();
+        if nfa.total_states > self.nfa_state_limit {
+            return Err(BuildError::NFAStateLimitExceeded(self.nfa_state_limit));
+        }
+        Ok(nfa)
+    }
+
+    #[inline]
+    fn dfa(&self, nfa: NFA<CharClass>) -> Result<DFA<CharClass>, BuildError> {
+        DFA::try_from_nfa(nfa, self.dfa_state_limit)
+            .map_err(|_| BuildError::DFAStateLimitExceeded(self.dfa_state_limit))
+    }
+
+    #[inline]
+    fn ast(&self, expr: &str) -> Result<ASTNode<CharClass>, BuildError> {
+        let ast = ASTParser::new()
+            .with_nesting_limit(self.nesting_limit)
+            .with_size_limit(self.size_limit)
+            .parse(expr)?
+            .unwrap();
+        Ok(ast)
+    }
+
+    #[inline]
+    fn backtrack(&self, program: Program) -> Backtrack {
+        let mut backtrack: Backtrack = program.into();
+        backtrack.set_step_limit(self.step_limit);
+        backtrack
+    }
+}
+
+impl Default for RegExpBuilder {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The errors that may occur when compiling a regular expression with a [RegExpBuilder].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    /// The expression is invalid, nested more deeply than the nesting limit or larger than the
+    /// size limit.
+    Parse(ParseError),
+    /// The NFA of the expression would have more states than the given limit.
+    NFAStateLimitExceeded(usize),
+    /// The DFA of the expression would have more states than the given limit.
+    DFAStateLimitExceeded(usize),
+}
+
+impl From<ParseError> for BuildError {
+    #[inline]
+    fn from(e: ParseError) -> Self {
+        Self::Parse(e)
+    }
+}
+
+impl fmt::Display for BuildError {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        match self {
+            Self::Parse(e) => e.fmt(f),
+            Self::NFAStateLimitExceeded(limit) => {
+                write!(f, "NFA would have more than {} states", limit)
+            }
+            Self::DFAStateLimitExceeded(limit) => {
+                write!(f, "DFA would have more than {} states", limit)
+            }
+        }
+    }
+}
+
+impl error::Error for BuildError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Parse(e) => Some(e),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use crate::matching::Match;
+
+use std::collections::HashMap;
+
+/// The capture groups of a single match of a regular expression. Group 0 is always the whole
+/// match; the other groups are numbered by the order of their left parentheses.
+#[derive(Debug, Clone)]
+pub struct Captures<'r, 't> {
+    groups: Vec<Option<Match<'t>>>,
+    names: &'r HashMap<String, usize>,
+}
+
+impl<'r, 't> Captures<'r, 't> {
+    #[inline]
+    pub(crate) fn new(groups: Vec<Option<Match<'t>>>, names: &'r HashMap<String, usize>) -> Self {
+        Self { groups, names }
+    }
+
+    /// Return the match of the capture group with the given index, or None if the group did not
+    /// participate in the match.
+    #[inline]
+    pub fn get(&self, i: usize) -> Option<Match<'t>> {
+        self.groups.get(i).copied().flatten()
+    }
+
+    /// Return the match of the capture group with the given name, or None if the group did not
+    /// participate in the match.
+    #[inline]
+    pub fn name(&self, name: &str) -> Option<Match<'t>> {
+        self.names.get(name).and_then(|&i| self.get(i))
+    }
+
+    /// Return the number of capture groups, including group 0.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Determine if there are no capture groups, which is never the case since group 0 is always
+    /// present.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Append the replacement string to `dst`, substituting each `$name` or `${name}` with the
+    /// text of the corresponding capture group. A name consisting only of digits refers to a group
+    /// by index. Groups that did not participate in the match are replaced by the empty string,
+    /// and `$$` is replaced by a literal `$`.
+    #[inline]
+    pub fn expand(&self, replacement: &str, dst: &mut String) {
+        let mut rest = replacement;
+        while let Some(i) = rest.find('$') {
+            dst.push_str(&rest[..i]);
+            rest = &rest[i + 1..];
+
+            if let Some(after) = rest.strip_prefix('$') {
+                dst.push('$');
+                rest = after;
+                continue;
+            }
+
+            let (name, after) = match rest.strip_prefix('{') {
+                Some(braced) => match braced.find('}') {
+                    Some(end) => (&braced[..end], &braced[end + 1..]),
+                    None => ("", rest),
+                },
+                None => {
+                    let end = rest
+                        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                        .unwrap_or(rest.len());
+                    (&rest[..end], &rest[end..])
+                }
+            };
+
+            // A $ not followed by a valid reference is taken literally.
+            if name.is_empty() {
+                dst.push('$');
+                continue;
+            }
+            rest = after;
+
+            let group = match name.parse() {
+                Ok(i) => self.get(i),
+                Err(_) => self.name(name),
+            };
+            if let Some(m) = group {
+                dst.push_str(m.as_str());
+            }
+        }
+
+        dst.push_str(rest);
+    }
+}
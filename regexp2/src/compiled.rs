@@ -0,0 +1,264 @@
+use crate::iter::{MatchPositions, Search};
+use crate::literal::Prefilter;
+use crate::matching::Match;
+use crate::regexp::Engine;
+use crate::stream::{self, StreamMatch};
+
+/// A DFA stored in static tables, as generated by the [`regex!`](crate::regex) macro. It is
+/// constructed without any allocation, so it may be placed in a `static`.
+#[derive(Debug, Clone, Copy)]
+pub struct CompiledDFA {
+    initial_state: usize,
+    /// Whether each state is accepting.
+    final_states: &'static [bool],
+    /// The transitions from each state as `(start, end, destination)` triples, sorted by
+    /// their disjoint char ranges.
+    transitions: &'static [&'static [(char, char, usize)]],
+}
+
+impl CompiledDFA {
+    /// Create a DFA from its tables. Every state index must be less than the number of entries in
+    /// `final_states` and `transitions`, and the ranges from each state must be sorted and
+    /// disjoint.
+    #[inline]
+    pub const fn from_tables(
+        initial_state: usize,
+        final_states: &'static [bool],
+        transitions: &'static [&'static [(char, char, usize)]],
+    ) -> Self {
+        Self {
+            initial_state,
+            final_states,
+            transitions,
+        }
+    }
+
+    /// Return the number of states in the DFA.
+    #[inline]
+    pub fn total_states(&self) -> usize {
+        self.final_states.len()
+    }
+
+    /// Return the state reached by the transition from the given state on the given char, if
+    /// there is one.
+    #[inline]
+    pub fn next_state(&self, state: usize, c: char) -> Option<usize> {
+        let row = self.transitions[state];
+        let i = row.partition_point(|&(_, end, _)| end < c);
+        row.get(i)
+            .filter(|&&(start, _, _)| start <= c)
+            .map(|&(_, _, dest)| dest)
+    }
+
+    /// Step through the input from the given byte offset, returning the byte offset of the end of
+    /// the longest or shortest match.
+    #[inline]
+    fn find(&self, input: &str, start: usize, shortest: bool) -> Option<usize> {
+        let mut state = self.initial_state;
+        let mut last_match = Some(start).filter(|_| self.final_states[state]);
+        if shortest && last_match.is_some() {
+            return last_match;
+        }
+
+        for (i, c) in input[start..].char_indices() {
+            state = match self.next_state(state, c) {
+                Some(s) => s,
+                None => break,
+            };
+
+            if self.final_states[state] {
+                last_match = Some(start + i + c.len_utf8());
+                if shortest {
+                    break;
+                }
+            }
+        }
+
+        last_match
+    }
+}
+
+impl Engine for CompiledDFA {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        input
+            .chars()
+            .try_fold(self.initial_state, |state, c| self.next_state(state, c))
+            .is_some_and(|state| self.final_states[state])
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<usize> {
+        self.find(input, start, false)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize> {
+        self.find(input, start, true)
+    }
+
+    #[inline]
+    fn is_match_chars<I>(&self, mut input: I) -> bool
+    where
+        I: Iterator<Item = char>,
+    {
+        input
+            .try_fold(self.initial_state, |state, c| self.next_state(state, c))
+            .is_some_and(|state| self.final_states[state])
+    }
+
+    #[inline]
+    fn find_chars<I>(&self, input: I) -> Option<StreamMatch>
+    where
+        I: Iterator<Item = char>,
+    {
+        stream::find_deterministic(
+            (self.initial_state, self.final_states[self.initial_state]),
+            |state, c| self.next_state(state, c).map(|s| (s, self.final_states[s])),
+            input,
+        )
+    }
+}
+
+/// A regular expression compiled to a DFA at build time by the [`regex!`](crate::regex) macro.
+/// Unlike [`RegExp`](crate::RegExp), it doesn't support capture groups.
+#[derive(Debug, Clone)]
+pub struct CompiledRegExp {
+    expr: &'static str,
+    dfa: CompiledDFA,
+    prefilter: Prefilter,
+}
+
+impl CompiledRegExp {
+    /// Create a compiled regular expression from its DFA and the literal prefix and required
+    /// text of its matches.
+    #[inline]
+    pub const fn from_parts(
+        expr: &'static str,
+        dfa: CompiledDFA,
+        literal_prefix: &'static str,
+        required_literal: &'static str,
+    ) -> Self {
+        Self {
+            expr,
+            dfa,
+            prefilter: Prefilter::from_static(literal_prefix, required_literal),
+        }
+    }
+
+    /// Return the regular expression this was compiled from.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        self.expr
+    }
+
+    /// Return the literal text that every match of the regular expression begins with, which may
+    /// be empty.
+    #[inline]
+    pub fn literal_prefix(&self) -> &str {
+        self.prefilter.prefix()
+    }
+
+    /// Return the longest literal text found that every match of the regular expression
+    /// contains, which may be empty.
+    #[inline]
+    pub fn required_literal(&self) -> &str {
+        self.prefilter.required()
+    }
+
+    /// Return the DFA used to evaluate input strings.
+    #[inline]
+    pub fn engine(&self) -> &CompiledDFA {
+        &self.dfa
+    }
+
+    /// Determine if the given input string is within the language described by the regular
+    /// expression.
+    #[inline]
+    pub fn is_match(&self, input: &str) -> bool {
+        self.prefilter.may_match(input) && self.dfa.is_match(input)
+    }
+
+    /// Determine if there is a match of the regular expression anywhere in the input string.
+    #[inline]
+    pub fn has_match(&self, input: &str) -> bool {
+        self.has_match_at(input, 0)
+    }
+
+    /// Determine if there is a match of the regular expression in the input string at or after
+    /// the given byte offset, which must lie on a char boundary.
+    #[inline]
+    pub fn has_match_at(&self, input: &str, start: usize) -> bool {
+        self.find_shortest_at(input, start).is_some()
+    }
+
+    /// Find the leftmost-longest match of the regular expression in the input string.
+    #[inline]
+    pub fn find<'t>(&self, input: &'t str) -> Option<Match<'t>> {
+        self.find_at(input, 0)
+    }
+
+    /// Find the leftmost-longest match of the regular expression in the input string, starting
+    /// the search at the given byte offset, which must lie on a char boundary.
+    #[inline]
+    pub fn find_at<'t>(&self, input: &'t str, start: usize) -> Option<Match<'t>> {
+        self.search(input, start, input[..start].chars().count())
+    }
+
+    /// Find the leftmost-shortest match of the regular expression in the input string.
+    #[inline]
+    pub fn find_shortest<'t>(&self, input: &'t str) -> Option<Match<'t>> {
+        self.find_shortest_at(input, 0)
+    }
+
+    /// Find the leftmost-shortest match of the regular expression in the input string, starting
+    /// the search at the given byte offset, which must lie on a char boundary.
+    #[inline]
+    pub fn find_shortest_at<'t>(&self, input: &'t str, start: usize) -> Option<Match<'t>> {
+        let char_start = input[..start].chars().count();
+        self.prefilter.candidates(input, start).find_map(|(b, c)| {
+            self.dfa
+                .find_shortest_at(input, b)
+                .map(|end| Match::new(input, char_start + c, b, end))
+        })
+    }
+
+    /// Return an iterator over the successive non-overlapping matches of the regular expression
+    /// in the input string.
+    #[inline]
+    pub fn find_iter<'r, 't>(&'r self, input: &'t str) -> CompiledMatches<'r, 't> {
+        CompiledMatches(MatchPositions::new(self, input))
+    }
+}
+
+impl Search for CompiledRegExp {
+    #[inline]
+    fn search<'t>(
+        &self,
+        input: &'t str,
+        byte_start: usize,
+        char_start: usize,
+    ) -> Option<Match<'t>> {
+        self.prefilter
+            .candidates(input, byte_start)
+            .find_map(|(b, c)| {
+                self.dfa
+                    .find_at(input, b)
+                    .map(|end| Match::new(input, char_start + c, b, end))
+            })
+    }
+}
+
+/// An iterator over the successive non-overlapping matches of a compiled regular expression in a
+/// string.
+#[derive(Debug)]
+pub struct CompiledMatches<'r, 't>(MatchPositions<'r, 't, CompiledRegExp>);
+
+impl<'r, 't> Iterator for CompiledMatches<'r, 't> {
+    type Item = Match<'t>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
@@ -0,0 +1,548 @@
+use crate::ast::{ASTNode, Node, Operator};
+use crate::class::CharClass;
+use crate::parser::{self, ASTParser, Parser};
+use crate::regexp::Engine;
+use crate::stream::{self, StreamMatch};
+
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+
+/// The term matching nothing.
+const EMPTY: usize = 0;
+/// The term matching only the empty string.
+const EPSILON: usize = 1;
+/// The term matching every string.
+const ANY: usize = 2;
+
+/// A regular expression engine that matches by taking the Brzozowski derivatives of the
+/// expression. Each distinct derivative is a state of a DFA, which is built lazily as input is
+/// matched, so only the states that are reached are ever constructed. Derivatives are taken as
+/// easily for the intersection (`&`) and complement (`~`) of expressions as for any other
+/// operator.
+///
+/// The states built are shared by all the threads matching with the engine, each of which locks
+/// them for the length of a search.
+#[derive(Debug)]
+pub struct Derivative {
+    initial_state: usize,
+    cache: Mutex<Cache>,
+}
+
+impl Derivative {
+    /// Compile a regular expression, which may use the intersection (`&`) and complement (`~`)
+    /// operators, but not backreferences or lookaround assertions.
+    #[inline]
+    pub fn new(expr: &str) -> parser::Result<Self> {
+        let ast = ASTParser::extended().parse(expr)?.unwrap();
+        Ok(Self::from_ast(&ast))
+    }
+
+    /// Create the engine for an AST, which mustn't contain backreferences or lookaround
+    /// assertions.
+    #[inline]
+    pub(crate) fn from_ast(ast: &ASTNode<CharClass>) -> Self {
+        let mut cache = Cache::new();
+        let initial_state = cache.term(ast);
+        Self {
+            initial_state,
+            cache: Mutex::new(cache),
+        }
+    }
+
+    /// Return the number of DFA states built so far.
+    #[inline]
+    pub fn total_states(&self) -> usize {
+        self.cache().transitions.len()
+    }
+
+    /// Lock the terms and states built so far. They are only locked by the methods of the
+    /// engine, none of which panic while holding the lock.
+    #[inline]
+    fn cache(&self) -> MutexGuard<'_, Cache> {
+        self.cache.lock().unwrap()
+    }
+
+    /// Return the byte offsets of the ends of all the matches beginning at the given byte offset,
+    /// in ascending order.
+    #[inline]
+    pub(crate) fn match_ends(&self, input: &str, start: usize) -> Vec<usize> {
+        let mut cache = self.cache();
+        let mut state = self.initial_state;
+        let mut ends = Vec::new();
+        if cache.nullable[state] {
+            ends.push(start);
+        }
+
+        for (i, c) in input[start..].char_indices() {
+            state = cache.next_state(state, c);
+            if state == EMPTY {
+                break;
+            }
+            if cache.nullable[state] {
+                ends.push(start + i + c.len_utf8());
+            }
+        }
+        ends
+    }
+
+    /// Step through the input from the given byte offset, returning the byte offset of the end of
+    /// the longest or shortest match.
+    #[inline]
+    fn find(&self, input: &str, start: usize, shortest: bool) -> Option<usize> {
+        let mut cache = self.cache();
+        let mut state = self.initial_state;
+        let mut last_match = Some(start).filter(|_| cache.nullable[state]);
+        if shortest && last_match.is_some() {
+            return last_match;
+        }
+
+        for (i, c) in input[start..].char_indices() {
+            state = cache.next_state(state, c);
+            // No match can be found once the derivative matches nothing.
+            if state == EMPTY {
+                break;
+            }
+
+            if cache.nullable[state] {
+                last_match = Some(start + i + c.len_utf8());
+                if shortest {
+                    break;
+                }
+            }
+        }
+
+        last_match
+    }
+}
+
+impl Clone for Derivative {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            initial_state: self.initial_state,
+            cache: Mutex::new(self.cache().clone()),
+        }
+    }
+}
+
+impl Engine for Derivative {
+    #[inline]
+    fn is_match(&self, input: &str) -> bool {
+        let mut cache = self.cache();
+        let state = input
+            .chars()
+            .fold(self.initial_state, |state, c| cache.next_state(state, c));
+        cache.nullable[state]
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<usize> {
+        self.find(input, start, false)
+    }
+
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize> {
+        self.find(input, start, true)
+    }
+
+    #[inline]
+    fn is_match_chars<I>(&self, mut input: I) -> bool
+    where
+        I: Iterator<Item = char>,
+    {
+        let mut cache = self.cache();
+        let state = input.try_fold(self.initial_state, |state, c| {
+            Some(cache.next_state(state, c)).filter(|&s| s != EMPTY)
+        });
+        state.is_some_and(|state| cache.nullable[state])
+    }
+
+    #[inline]
+    fn find_chars<I>(&self, input: I) -> Option<StreamMatch>
+    where
+        I: Iterator<Item = char>,
+    {
+        let mut cache = self.cache();
+        let initial = (self.initial_state, cache.nullable[self.initial_state]);
+        stream::find_deterministic(
+            initial,
+            |state, c| {
+                let state = cache.next_state(state, c);
+                Some((state, cache.nullable[state])).filter(|_| state != EMPTY)
+            },
+            input,
+        )
+    }
+}
+
+/// A term of a regular expression, the operands of which are indices of other terms. Terms are
+/// built in a canonical form, so that equivalent derivatives are usually identical and end up as
+/// the same DFA state.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum Term {
+    Empty,
+    Epsilon,
+    Class(CharClass),
+    /// The left operand is never itself a concatenation.
+    Concat(usize, usize),
+    Star(usize),
+    /// At least two operands, sorted and without duplicates or nested unions.
+    Or(Vec<usize>),
+    /// At least two operands, sorted and without duplicates or nested intersections.
+    And(Vec<usize>),
+    Not(usize),
+}
+
+/// The steps on the stack of the walk over a syntax tree.
+enum Step<'a> {
+    Term(&'a ASTNode<CharClass>),
+    /// Combine the terms of the given number of operands of an operator, once they're built.
+    Combine(&'a Operator, usize),
+}
+
+/// The terms built so far, and the transitions of the DFA states they form.
+#[derive(Debug, Clone)]
+struct Cache {
+    terms: Vec<Term>,
+    /// Whether each term matches the empty string.
+    nullable: Vec<bool>,
+    ids: HashMap<Term, usize>,
+    /// The transitions from each state built so far as `(start, end, destination)` triples,
+    /// sorted by their disjoint char ranges.
+    transitions: HashMap<usize, Vec<(char, char, usize)>>,
+}
+
+impl Cache {
+    #[inline]
+    fn new() -> Self {
+        let mut cache = Self {
+            terms: Vec::new(),
+            nullable: Vec::new(),
+            ids: HashMap::new(),
+            transitions: HashMap::new(),
+        };
+        cache.intern(Term::Empty);
+        cache.intern(Term::Epsilon);
+        cache.intern(Term::Not(EMPTY));
+        cache
+    }
+
+    /// Return the index of a term, adding it if it hasn't been seen before.
+    #[inline]
+    fn intern(&mut self, term: Term) -> usize {
+        if let Some(&id) = self.ids.get(&term) {
+            return id;
+        }
+
+        let nullable = match &term {
+            Term::Empty | Term::Class(_) => false,
+            Term::Epsilon | Term::Star(_) => true,
+            Term::Concat(a, b) => self.nullable[*a] && self.nullable[*b],
+            Term::Or(terms) => terms.iter().any(|&t| self.nullable[t]),
+            Term::And(terms) => terms.iter().all(|&t| self.nullable[t]),
+            Term::Not(t) => !self.nullable[*t],
+        };
+
+        let id = self.terms.len();
+        self.terms.push(term.clone());
+        self.nullable.push(nullable);
+        self.ids.insert(term, id);
+        id
+    }
+
+    /// Build the term of an AST. The tree is walked in post-order with an explicit stack rather
+    /// than by recursion, since the syntax trees of long expressions are deep.
+    #[inline]
+    fn term(&mut self, node: &ASTNode<CharClass>) -> usize {
+        let mut steps = vec![Step::Term(node)];
+        let mut terms = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Term(Node::Leaf(c)) => {
+                    let t = self.class(c.clone());
+                    terms.push(t);
+                }
+                Step::Term(Node::None) => terms.push(EPSILON),
+                Step::Term(node @ Node::Branch(op, c1, c2)) => {
+                    let operands = match op {
+                        Operator::Concatenation => concatenated(node),
+                        Operator::Union | Operator::Intersection => vec![&**c1, &**c2],
+                        _ => vec![&**c1],
+                    };
+                    steps.push(Step::Combine(op, operands.len()));
+                    steps.extend(operands.into_iter().rev().map(Step::Term));
+                }
+                Step::Combine(op, len) => {
+                    let operands = terms.split_off(terms.len() - len);
+                    let t = self.combine(op, operands);
+                    terms.push(t);
+                }
+            }
+        }
+        terms.pop().unwrap()
+    }
+
+    /// Build the term of an operator from the terms of its operands.
+    #[inline]
+    fn combine(&mut self, op: &Operator, operands: Vec<usize>) -> usize {
+        match op {
+            // The items of a chain of concatenations are joined from the last, so that each is
+            // prepended in constant time.
+            Operator::Concatenation => operands
+                .into_iter()
+                .rev()
+                .fold(EPSILON, |rest, a| self.concat(a, rest)),
+            Operator::Union => self.or(operands),
+            Operator::Intersection => self.and(operands),
+            Operator::Complement => self.not(operands[0]),
+            Operator::KleeneStar => self.star(operands[0]),
+            Operator::Plus => {
+                let star = self.star(operands[0]);
+                self.concat(operands[0], star)
+            }
+            Operator::Optional => self.or(vec![operands[0], EPSILON]),
+            Operator::Capture(_, _) => operands[0],
+            // The extended parser rejects these.
+            Operator::Backreference(_) | Operator::Lookaround(_) => unreachable!(),
+        }
+    }
+
+    #[inline]
+    fn class(&mut self, c: CharClass) -> usize {
+        if c.is_empty() {
+            EMPTY
+        } else {
+            self.intern(Term::Class(c))
+        }
+    }
+
+    #[inline]
+    fn concat(&mut self, a: usize, b: usize) -> usize {
+        if a == EMPTY || b == EMPTY {
+            return EMPTY;
+        }
+
+        // Concatenations are kept right-associative, so the items of `a` are prepended to `b`
+        // from the last.
+        let mut items = Vec::new();
+        let mut a = a;
+        while let Term::Concat(x, y) = self.terms[a] {
+            items.push(x);
+            a = y;
+        }
+        items.push(a);
+        items.into_iter().rev().fold(b, |rest, x| match (x, rest) {
+            (EPSILON, _) => rest,
+            (_, EPSILON) => x,
+            _ => self.intern(Term::Concat(x, rest)),
+        })
+    }
+
+    #[inline]
+    fn star(&mut self, a: usize) -> usize {
+        match self.terms[a] {
+            Term::Empty | Term::Epsilon => EPSILON,
+            Term::Star(_) => a,
+            _ => self.intern(Term::Star(a)),
+        }
+    }
+
+    #[inline]
+    fn or(&mut self, terms: Vec<usize>) -> usize {
+        let mut flat = Vec::new();
+        for t in terms {
+            match &self.terms[t] {
+                Term::Or(nested) => flat.extend(nested),
+                Term::Empty => {}
+                _ => flat.push(t),
+            }
+        }
+        if flat.contains(&ANY) {
+            return ANY;
+        }
+
+        flat.sort_unstable();
+        flat.dedup();
+        match flat.len() {
+            0 => EMPTY,
+            1 => flat[0],
+            _ => self.intern(Term::Or(flat)),
+        }
+    }
+
+    #[inline]
+    fn and(&mut self, terms: Vec<usize>) -> usize {
+        let mut flat = Vec::new();
+        for t in terms {
+            match &self.terms[t] {
+                Term::And(nested) => flat.extend(nested),
+                _ if t == ANY => {}
+                _ => flat.push(t),
+            }
+        }
+        if flat.contains(&EMPTY) {
+            return EMPTY;
+        }
+
+        flat.sort_unstable();
+        flat.dedup();
+        match flat.len() {
+            0 => ANY,
+            1 => flat[0],
+            _ => self.intern(Term::And(flat)),
+        }
+    }
+
+    #[inline]
+    fn not(&mut self, a: usize) -> usize {
+        match self.terms[a] {
+            Term::Not(t) => t,
+            _ => self.intern(Term::Not(a)),
+        }
+    }
+
+    /// Return the terms whose derivatives the derivative of a term is built from.
+    #[inline]
+    fn derivative_operands(&self, t: usize) -> Vec<usize> {
+        match &self.terms[t] {
+            Term::Empty | Term::Epsilon | Term::Class(_) => vec![],
+            // The second operand only begins a match if the first matches the empty string.
+            Term::Concat(a, b) if self.nullable[*a] => vec![*a, *b],
+            Term::Concat(a, _) | Term::Star(a) | Term::Not(a) => vec![*a],
+            Term::Or(terms) | Term::And(terms) => terms.clone(),
+        }
+    }
+
+    /// Return the derivative of a term with respect to a char: the term matching the rest of each
+    /// string matched by the term that begins with the char. The derivatives of the operands are
+    /// taken first with an explicit stack rather than by recursion, since terms may be deeply
+    /// nested.
+    #[inline]
+    fn derivative(&mut self, t: usize, c: char) -> usize {
+        let mut derivatives = HashMap::new();
+        let mut stack = vec![(t, false)];
+        while let Some((u, ready)) = stack.pop() {
+            if derivatives.contains_key(&u) {
+                continue;
+            }
+            if !ready {
+                stack.push((u, true));
+                stack.extend(self.derivative_operands(u).into_iter().map(|a| (a, false)));
+                continue;
+            }
+
+            // Classes can be large, so they aren't cloned with the other terms.
+            let d = if let Term::Class(cc) = &self.terms[u] {
+                if cc.contains(c) {
+                    EPSILON
+                } else {
+                    EMPTY
+                }
+            } else {
+                match self.terms[u].clone() {
+                    Term::Empty | Term::Epsilon | Term::Class(_) => EMPTY,
+                    Term::Concat(a, b) => {
+                        let first = self.concat(derivatives[&a], b);
+                        if self.nullable[a] {
+                            self.or(vec![first, derivatives[&b]])
+                        } else {
+                            first
+                        }
+                    }
+                    Term::Star(a) => self.concat(derivatives[&a], u),
+                    Term::Or(terms) => {
+                        let ds = terms.iter().map(|a| derivatives[a]).collect();
+                        self.or(ds)
+                    }
+                    Term::And(terms) => {
+                        let ds = terms.iter().map(|a| derivatives[a]).collect();
+                        self.and(ds)
+                    }
+                    Term::Not(a) => self.not(derivatives[&a]),
+                }
+            };
+            derivatives.insert(u, d);
+        }
+        derivatives[&t]
+    }
+
+    /// Partition the chars into classes, the chars of each of which all give the same derivative
+    /// of a term. The classes of the operands are found first, as for the derivative itself.
+    #[inline]
+    fn derivative_classes(&self, t: usize) -> Vec<CharClass> {
+        let mut partitions: HashMap<usize, Vec<CharClass>> = HashMap::new();
+        let mut stack = vec![(t, false)];
+        while let Some((u, ready)) = stack.pop() {
+            if partitions.contains_key(&u) {
+                continue;
+            }
+            if !ready {
+                stack.push((u, true));
+                stack.extend(self.derivative_operands(u).into_iter().map(|a| (a, false)));
+                continue;
+            }
+
+            let partition = match &self.terms[u] {
+                Term::Empty | Term::Epsilon => vec![CharClass::all()],
+                Term::Class(cc) => vec![cc.clone(), cc.complement()],
+                Term::Concat(a, b) if self.nullable[*a] => refine(&partitions[a], &partitions[b]),
+                Term::Concat(a, _) | Term::Star(a) | Term::Not(a) => partitions[a].clone(),
+                Term::Or(terms) | Term::And(terms) => terms
+                    .iter()
+                    .map(|a| partitions[a].clone())
+                    .reduce(|p, q| refine(&p, &q))
+                    .unwrap_or_else(|| vec![CharClass::all()]),
+            };
+            partitions.insert(u, partition);
+        }
+        partitions.remove(&t).unwrap()
+    }
+
+    /// Return the state reached from a state on a char, building the transitions of the state if
+    /// they haven't been already.
+    #[inline]
+    fn next_state(&mut self, state: usize, c: char) -> usize {
+        if !self.transitions.contains_key(&state) {
+            let mut row = Vec::new();
+            for class in self.derivative_classes(state) {
+                let first = match class.iter().next() {
+                    Some(range) => range.start,
+                    None => continue,
+                };
+                let dest = self.derivative(state, first);
+                row.extend(class.iter().map(|r| (r.start, r.end, dest)));
+            }
+            row.sort_unstable();
+            self.transitions.insert(state, row);
+        }
+
+        let row = &self.transitions[&state];
+        let i = row.partition_point(|&(_, end, _)| end < c);
+        row.get(i)
+            .filter(|&&(start, _, _)| start <= c)
+            .map_or(EMPTY, |&(_, _, dest)| dest)
+    }
+}
+
+/// Return the items of a chain of concatenations, in order.
+#[inline]
+fn concatenated(node: &ASTNode<CharClass>) -> Vec<&ASTNode<CharClass>> {
+    let mut items = Vec::new();
+    let mut node = node;
+    while let Node::Branch(Operator::Concatenation, c1, c2) = node {
+        items.push(&**c2);
+        node = c1;
+    }
+    items.push(node);
+    items.reverse();
+    items
+}
+
+/// Return the non-empty pairwise intersections of the classes of two partitions.
+#[inline]
+fn refine(p: &[CharClass], q: &[CharClass]) -> Vec<CharClass> {
+    p.iter()
+        .flat_map(|a| q.iter().map(move |b| a.intersection(b)))
+        .filter(|c| !c.is_empty())
+        .collect()
+}
@@ -0,0 +1,218 @@
+use crate::ast::{ASTNode, Node, Operator};
+use crate::class::CharClass;
+
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use automata::dfa::{Disjoin, Transition};
+use automata::DFA;
+
+/// Build a DFA directly from the syntax tree of a regular expression, without constructing an
+/// NFA first, using the `followpos` construction described by Algorithm 3.36. Each leaf of the
+/// tree is a position, and each DFA state is the set of positions that may match the next char.
+/// Returns `None` if the tree contains backreferences, lookaround assertions, intersections or
+/// complements, which the construction doesn't support.
+#[inline]
+pub fn dfa_from_ast(ast: &ASTNode<CharClass>) -> Option<DFA<CharClass>> {
+    let mut positions = Positions::default();
+    let root = positions.augment(ast)?;
+    Some(positions.into_dfa(root))
+}
+
+/// The `nullable`, `firstpos` and `lastpos` functions of a node.
+#[derive(Debug)]
+struct Functions {
+    nullable: bool,
+    firstpos: BTreeSet<usize>,
+    lastpos: BTreeSet<usize>,
+}
+
+impl Functions {
+    #[inline]
+    fn empty() -> Self {
+        Self {
+            nullable: true,
+            firstpos: BTreeSet::new(),
+            lastpos: BTreeSet::new(),
+        }
+    }
+}
+
+/// The steps on the stack of the walk over a syntax tree.
+enum Step<'a> {
+    Augment(&'a ASTNode<CharClass>),
+    /// Combine the functions of the operands of a node, once they're computed.
+    Combine(&'a Operator),
+}
+
+/// The positions of a syntax tree, with the class matched at each and its `followpos`.
+#[derive(Debug, Default)]
+struct Positions {
+    classes: Vec<CharClass>,
+    followpos: Vec<BTreeSet<usize>>,
+}
+
+impl Positions {
+    /// Compute the functions of a node, numbering its leaves as positions and adding to the
+    /// `followpos` of the positions within it. The tree is walked in post-order with an explicit
+    /// stack rather than by recursion, since the syntax trees of long expressions are deep.
+    #[inline]
+    fn augment(&mut self, node: &ASTNode<CharClass>) -> Option<Functions> {
+        let mut steps = vec![Step::Augment(node)];
+        let mut functions = Vec::new();
+        while let Some(step) = steps.pop() {
+            match step {
+                Step::Augment(Node::Leaf(class)) => {
+                    let pos = self.add(class.clone());
+                    functions.push(Functions {
+                        nullable: false,
+                        firstpos: [pos].iter().copied().collect(),
+                        lastpos: [pos].iter().copied().collect(),
+                    });
+                }
+                Step::Augment(Node::None) => functions.push(Functions::empty()),
+                Step::Augment(Node::Branch(op, c1, c2)) => match op {
+                    Operator::Union | Operator::Concatenation => {
+                        steps.push(Step::Combine(op));
+                        steps.push(Step::Augment(c2));
+                        steps.push(Step::Augment(c1));
+                    }
+                    Operator::KleeneStar
+                    | Operator::Plus
+                    | Operator::Optional
+                    | Operator::Capture(_, _) => {
+                        steps.push(Step::Combine(op));
+                        steps.push(Step::Augment(c1));
+                    }
+                    Operator::Backreference(_)
+                    | Operator::Lookaround(_)
+                    | Operator::Intersection
+                    | Operator::Complement => return None,
+                },
+                Step::Combine(op) => {
+                    let f = self.combine(op, &mut functions);
+                    functions.push(f);
+                }
+            }
+        }
+        functions.pop()
+    }
+
+    /// Compute the functions of a node from those of its operands, which are on top of the stack.
+    #[inline]
+    fn combine(&mut self, op: &Operator, functions: &mut Vec<Functions>) -> Functions {
+        let mut pop = || functions.pop().unwrap();
+        match op {
+            Operator::Union => {
+                let (f2, f1) = (pop(), pop());
+                Functions {
+                    nullable: f1.nullable || f2.nullable,
+                    firstpos: &f1.firstpos | &f2.firstpos,
+                    lastpos: &f1.lastpos | &f2.lastpos,
+                }
+            }
+            Operator::Concatenation => {
+                let (f2, f1) = (pop(), pop());
+                // Every position that may start the second operand may follow one that ends the
+                // first.
+                self.follow(&f1.lastpos, &f2.firstpos);
+                Functions {
+                    nullable: f1.nullable && f2.nullable,
+                    firstpos: if f1.nullable {
+                        &f1.firstpos | &f2.firstpos
+                    } else {
+                        f1.firstpos
+                    },
+                    lastpos: if f2.nullable {
+                        &f1.lastpos | &f2.lastpos
+                    } else {
+                        f2.lastpos
+                    },
+                }
+            }
+            Operator::KleeneStar | Operator::Plus => {
+                let f1 = pop();
+                // A repetition may start again once the operand ends.
+                self.follow(&f1.lastpos, &f1.firstpos);
+                Functions {
+                    nullable: *op == Operator::KleeneStar || f1.nullable,
+                    ..f1
+                }
+            }
+            Operator::Optional => Functions {
+                nullable: true,
+                ..pop()
+            },
+            _ => pop(),
+        }
+    }
+
+    /// Add a position matching the given class, returning its number.
+    #[inline]
+    fn add(&mut self, class: CharClass) -> usize {
+        self.classes.push(class);
+        self.followpos.push(BTreeSet::new());
+        self.classes.len() - 1
+    }
+
+    /// Add the positions in `first` to the `followpos` of every position in `last`.
+    #[inline]
+    fn follow(&mut self, last: &BTreeSet<usize>, first: &BTreeSet<usize>) {
+        for &i in last {
+            self.followpos[i].extend(first);
+        }
+    }
+
+    /// Build the DFA of a tree with the given functions at its root. The tree is augmented with an
+    /// end marker position, which follows its last positions; the states containing the end
+    /// marker are accepting.
+    #[inline]
+    fn into_dfa(mut self, root: Functions) -> DFA<CharClass> {
+        let end = self.add(CharClass::new());
+        self.follow(&root.lastpos, &[end].iter().copied().collect());
+        let mut initial = root.firstpos;
+        if root.nullable {
+            initial.insert(end);
+        }
+
+        let mut dfa = DFA::new();
+        if initial.contains(&end) {
+            dfa.final_states.insert(dfa.initial_state);
+        }
+
+        let mut labels = HashMap::new();
+        labels.insert(initial.clone(), dfa.initial_state);
+        let mut unmarked = VecDeque::new();
+        unmarked.push_back((dfa.initial_state, initial));
+
+        while let Some((label, state)) = unmarked.pop_front() {
+            // The classes of the positions may overlap, so the transitions are on the disjoint
+            // pieces of them.
+            let classes: Vec<&CharClass> = state
+                .iter()
+                .filter(|&&p| p != end)
+                .map(|&p| &self.classes[p])
+                .collect();
+
+            for t in CharClass::disjoin(classes) {
+                let next: BTreeSet<usize> = state
+                    .iter()
+                    .filter(|&&p| p != end && Disjoin::contains(&self.classes[p], &t))
+                    .flat_map(|&p| self.followpos[p].iter().copied())
+                    .collect();
+
+                let next_label = match labels.get(&next) {
+                    Some(&l) => l,
+                    None => {
+                        let l = dfa.add_state(next.contains(&end));
+                        labels.insert(next.clone(), l);
+                        unmarked.push_back((l, next));
+                        l
+                    }
+                };
+                dfa.add_transition(label, next_label, Transition(t));
+            }
+        }
+
+        dfa
+    }
+}
@@ -0,0 +1,169 @@
+use crate::captures::Captures;
+use crate::matching::Match;
+use crate::regexp::{Engine, RegExp};
+
+/// A compiled regular expression that can find the leftmost match at or after a position.
+pub(crate) trait Search {
+    /// Find the leftmost match at or after the given byte offset, which must correspond to the
+    /// given char offset.
+    fn search<'t>(&self, input: &'t str, byte_start: usize, char_start: usize)
+        -> Option<Match<'t>>;
+}
+
+/// An iterator over the successive non-overlapping matches in a string. An empty match immediately
+/// following a previous match is skipped.
+#[derive(Debug)]
+pub(crate) struct MatchPositions<'r, 't, S> {
+    re: &'r S,
+    text: &'t str,
+    /// The byte and char offsets at which to continue searching, or None if the search is done.
+    next: Option<(usize, usize)>,
+    /// The byte offset of the end of the previous match.
+    last_end: Option<usize>,
+}
+
+impl<'r, 't, S: Search> MatchPositions<'r, 't, S> {
+    #[inline]
+    pub(crate) fn new(re: &'r S, text: &'t str) -> Self {
+        Self {
+            re,
+            text,
+            next: Some((0, 0)),
+            last_end: None,
+        }
+    }
+}
+
+impl<'r, 't, S: Search> Iterator for MatchPositions<'r, 't, S> {
+    type Item = Match<'t>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (byte_start, char_start) = self.next?;
+            let m = match self.re.search(self.text, byte_start, char_start) {
+                Some(found) => found,
+                None => {
+                    self.next = None;
+                    return None;
+                }
+            };
+
+            if m.is_empty() {
+                // Step past an empty match so that it isn't found again.
+                self.next = self.text[m.byte_end..]
+                    .chars()
+                    .next()
+                    .map(|c| (m.byte_end + c.len_utf8(), m.end + 1));
+                if self.last_end == Some(m.byte_end) {
+                    continue;
+                }
+            } else {
+                self.next = Some((m.byte_end, m.end));
+            }
+
+            self.last_end = Some(m.byte_end);
+            return Some(m);
+        }
+    }
+}
+
+/// An iterator over the successive non-overlapping matches of a regular expression in a string.
+#[derive(Debug)]
+pub struct Matches<'r, 't, E: Engine>(pub(crate) MatchPositions<'r, 't, RegExp<E>>);
+
+impl<'r, 't, E: Engine> Iterator for Matches<'r, 't, E> {
+    type Item = Match<'t>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+}
+
+/// An iterator over the capture groups of the successive non-overlapping matches of a regular
+/// expression in a string.
+#[derive(Debug)]
+pub struct CaptureMatches<'r, 't, E: Engine>(pub(crate) MatchPositions<'r, 't, RegExp<E>>);
+
+impl<'r, 't, E: Engine> Iterator for CaptureMatches<'r, 't, E> {
+    type Item = Captures<'r, 't>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let re = self.0.re;
+        self.0.next().map(|m| re.captures_from(m))
+    }
+}
+
+/// An iterator over the substrings of a string separated by the matches of a regular expression.
+#[derive(Debug)]
+pub struct Split<'r, 't, E: Engine> {
+    finder: MatchPositions<'r, 't, RegExp<E>>,
+    /// The byte offset of the start of the next substring, or None if all have been yielded.
+    last: Option<usize>,
+}
+
+impl<'r, 't, E: Engine> Split<'r, 't, E> {
+    #[inline]
+    pub(crate) fn new(finder: MatchPositions<'r, 't, RegExp<E>>) -> Self {
+        Self {
+            finder,
+            last: Some(0),
+        }
+    }
+}
+
+impl<'r, 't, E: Engine> Iterator for Split<'r, 't, E> {
+    type Item = &'t str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let text = self.finder.text;
+        let last = self.last?;
+        match self.finder.next() {
+            Some(m) => {
+                self.last = Some(m.byte_end);
+                Some(&text[last..m.byte_start])
+            }
+            None => {
+                self.last = None;
+                Some(&text[last..])
+            }
+        }
+    }
+}
+
+/// An iterator over at most a given number of substrings of a string separated by the matches of
+/// a regular expression. The last substring contains the remainder of the string.
+#[derive(Debug)]
+pub struct SplitN<'r, 't, E: Engine> {
+    splits: Split<'r, 't, E>,
+    n: usize,
+}
+
+impl<'r, 't, E: Engine> SplitN<'r, 't, E> {
+    #[inline]
+    pub(crate) fn new(splits: Split<'r, 't, E>, n: usize) -> Self {
+        Self { splits, n }
+    }
+}
+
+impl<'r, 't, E: Engine> Iterator for SplitN<'r, 't, E> {
+    type Item = &'t str;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.n == 0 {
+            return None;
+        }
+
+        self.n -= 1;
+        if self.n > 0 {
+            return self.splits.next();
+        }
+
+        let last = self.splits.last.take()?;
+        Some(&self.splits.finder.text[last..])
+    }
+}
@@ -1,14 +1,34 @@
 #![deny(rust_2018_idioms)]
 #![deny(future_incompatible)]
 
+mod captures;
+mod compiled;
+mod iter;
+mod matching;
 mod regexp;
+mod replace;
+mod set;
+mod stream;
 
-mod ast;
-mod disjoint;
-mod ranges;
-
-pub mod class;
-pub mod parser;
+mod backtrack;
+mod builder;
+mod derivative;
+mod followpos;
+mod literal;
 
 pub use automata;
+pub use backtrack::{Backtrack, StepLimitExceeded};
+pub use builder::{BuildError, RegExpBuilder};
+pub use captures::*;
+pub use compiled::{CompiledDFA, CompiledMatches, CompiledRegExp};
+pub use derivative::Derivative;
+pub use followpos::dfa_from_ast;
+pub use iter::{CaptureMatches, Matches, Split, SplitN};
+pub use matching::*;
 pub use regexp::*;
+pub use replace::*;
+pub use set::*;
+pub use stream::{ReadChars, StreamMatch};
+
+pub use regexp2_macro::regex;
+pub use regexp2_syntax::{ast, class, parser};
@@ -0,0 +1,85 @@
+use crate::ast::ASTNode;
+use crate::class::CharClass;
+
+use std::borrow::Cow;
+use std::iter;
+
+use regexp2_syntax::literal::Literals;
+
+/// Literal text that every match of a regular expression must contain, used to skip positions of
+/// an input string at which no match can start before running an engine.
+#[derive(Debug, Clone)]
+pub(crate) struct Prefilter {
+    /// Text that every match begins with.
+    prefix: Cow<'static, str>,
+    /// Text that every match contains.
+    required: Cow<'static, str>,
+}
+
+impl Prefilter {
+    /// Extract the literal prefix and the longest required literal substring of an AST.
+    #[inline]
+    pub(crate) fn new(ast: &ASTNode<CharClass>) -> Self {
+        let literals = Literals::new(ast);
+        Self {
+            prefix: Cow::Owned(literals.prefix().to_owned()),
+            required: Cow::Owned(literals.required().to_owned()),
+        }
+    }
+
+    /// Create a prefilter from literals extracted ahead of time.
+    #[inline]
+    pub(crate) const fn from_static(prefix: &'static str, required: &'static str) -> Self {
+        Self {
+            prefix: Cow::Borrowed(prefix),
+            required: Cow::Borrowed(required),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    #[inline]
+    pub(crate) fn required(&self) -> &str {
+        &self.required
+    }
+
+    /// Determine if the whole input string might be matched.
+    #[inline]
+    pub(crate) fn may_match(&self, input: &str) -> bool {
+        input.starts_with(&*self.prefix) && input.contains(&*self.required)
+    }
+
+    /// Return the byte offsets at or after the given byte offset at which a match might start,
+    /// along with the number of chars after the given offset.
+    #[inline]
+    pub(crate) fn candidates<'t>(
+        &'t self,
+        input: &'t str,
+        byte_start: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 't {
+        // Without the required text, there is no match anywhere.
+        let possible = input[byte_start..].contains(&*self.required);
+
+        let mut next = Some(byte_start).filter(|_| possible);
+        let mut last = (byte_start, 0);
+        iter::from_fn(move || {
+            let from = next?;
+            let b = match input[from..].find(&*self.prefix) {
+                Some(i) => from + i,
+                None => {
+                    next = None;
+                    return None;
+                }
+            };
+
+            // Count the chars skipped since the last candidate.
+            let c = last.1 + input[last.0..b].chars().count();
+            last = (b, c);
+            next = input[b..].chars().next().map(|ch| b + ch.len_utf8());
+            Some((b, c))
+        })
+    }
+}
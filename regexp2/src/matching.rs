@@ -0,0 +1,63 @@
+use std::ops::Range;
+
+/// A match of a regular expression in a string. The position of the match is given both as char
+/// offsets and as byte offsets into the string, so that the matched text can be sliced out of the
+/// string directly.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct Match<'t> {
+    text: &'t str,
+    /// Char offset of the start of the match.
+    pub start: usize,
+    /// Char offset of the last char matched + 1.
+    pub end: usize,
+    /// Byte offset of the start of the match.
+    pub byte_start: usize,
+    /// Byte offset of the last byte matched + 1.
+    pub byte_end: usize,
+}
+
+impl<'t> Match<'t> {
+    /// Create a new Match of `text[byte_start..byte_end]`, where `start` is the char offset of
+    /// `byte_start`.
+    #[inline]
+    pub(crate) fn new(text: &'t str, start: usize, byte_start: usize, byte_end: usize) -> Self {
+        let end = start + text[byte_start..byte_end].chars().count();
+        Self {
+            text,
+            start,
+            end,
+            byte_start,
+            byte_end,
+        }
+    }
+
+    /// Return the string the match was found in.
+    #[inline]
+    pub(crate) fn text(&self) -> &'t str {
+        self.text
+    }
+
+    /// Return the range of char offsets of the match.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Return the range of byte offsets of the match.
+    #[inline]
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_start..self.byte_end
+    }
+
+    /// Return the matched text.
+    #[inline]
+    pub fn as_str(&self) -> &'t str {
+        &self.text[self.byte_range()]
+    }
+
+    /// Determine if the match is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_start == self.byte_end
+    }
+}
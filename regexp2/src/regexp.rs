@@ -1,10 +1,20 @@
-use crate::class::{CharClass, CharRange};
-use crate::parser::{self, NFAParser, Parser};
+use crate::backtrack::{Backtrack, Program, StepLimitExceeded};
+use crate::captures::Captures;
+use crate::class::CharClass;
+use crate::derivative::Derivative;
+use crate::followpos;
+use crate::iter::{CaptureMatches, MatchPositions, Matches, Search, Split, SplitN};
+use crate::literal::Prefilter;
+use crate::parser::{self, ASTParser, NFAParser, Parser};
+use crate::replace::Replacer;
+use crate::stream::{self, StreamMatch};
 
-use std::convert::TryInto;
+use std::collections::HashMap;
+use std::io;
 
-pub use automata::Match;
-use automata::{dfa::Disjoin, nfa::Transition, DFA, NFA};
+use crate::matching::Match;
+
+use automata::{DFA, NFA};
 
 /// A compiled regular expression for matching strings. It may be used to determine if given
 /// strings are within the language described by the regular expression.
@@ -14,57 +24,320 @@ pub struct RegExp<E: Engine> {
     expr: String,
     /// The compiled backend of the regular expression used to evaluate input strings.
     engine: E,
+    /// The program used to find the positions of capture groups within matches.
+    program: Program,
+    /// The literal text used to skip positions at which no match can start.
+    prefilter: Prefilter,
 }
 
 impl<E: Engine> RegExp<E> {
+    /// Create a compiled regular expression from its engine, capture program and prefilter.
+    #[inline]
+    pub(crate) fn from_parts(
+        expr: &str,
+        engine: E,
+        program: Program,
+        prefilter: Prefilter,
+    ) -> Self {
+        RegExp {
+            expr: expr.to_owned(),
+            engine,
+            program,
+            prefilter,
+        }
+    }
+
+    /// Return the regular expression this was compiled from.
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    /// Return the literal text that every match of the regular expression begins with, which may
+    /// be empty. Only positions at which it occurs are tried when searching for a match.
+    #[inline]
+    pub fn literal_prefix(&self) -> &str {
+        self.prefilter.prefix()
+    }
+
+    /// Return the longest literal text found that every match of the regular expression
+    /// contains, which may be empty. Inputs that don't contain it aren't searched.
+    #[inline]
+    pub fn required_literal(&self) -> &str {
+        self.prefilter.required()
+    }
+
+    /// Return the engine used to evaluate input strings.
+    #[inline]
+    pub fn engine(&self) -> &E {
+        &self.engine
+    }
+
     /// Determine if the given input string is within the language described by the regular
     /// expression.
     #[inline]
     pub fn is_match(&self, input: &str) -> bool {
-        self.engine.is_match(input)
+        self.prefilter.may_match(input) && self.engine.is_match(input)
     }
 
+    /// Determine if there is a match of the regular expression anywhere in the input string.
     #[inline]
     pub fn has_match(&self, input: &str) -> bool {
         self.has_match_at(input, 0)
     }
 
+    /// Determine if there is a match of the regular expression in the input string at or after
+    /// the given byte offset, which must lie on a char boundary.
     #[inline]
     pub fn has_match_at(&self, input: &str, start: usize) -> bool {
         self.find_shortest_at(input, start).is_some()
     }
 
+    /// Find the leftmost-longest match of the regular expression in the input string.
     #[inline]
-    pub fn find(&self, input: &str) -> Option<Match<char>> {
+    pub fn find<'t>(&self, input: &'t str) -> Option<Match<'t>> {
         self.find_at(input, 0)
     }
 
+    /// Find the leftmost-longest match of the regular expression in the input string, starting
+    /// the search at the given byte offset, which must lie on a char boundary.
     #[inline]
-    pub fn find_at(&self, input: &str, start: usize) -> Option<Match<char>> {
-        self.engine.find_at(input, start)
+    pub fn find_at<'t>(&self, input: &'t str, start: usize) -> Option<Match<'t>> {
+        self.search(input, start, char_offset(input, start))
     }
 
+    /// Find the leftmost-shortest match of the regular expression in the input string.
     #[inline]
-    pub fn find_shortest(&self, input: &str) -> Option<Match<char>> {
+    pub fn find_shortest<'t>(&self, input: &'t str) -> Option<Match<'t>> {
         self.find_shortest_at(input, 0)
     }
 
+    /// Find the leftmost-shortest match of the regular expression in the input string, starting
+    /// the search at the given byte offset, which must lie on a char boundary.
+    #[inline]
+    pub fn find_shortest_at<'t>(&self, input: &'t str, start: usize) -> Option<Match<'t>> {
+        let char_start = char_offset(input, start);
+        self.positions(input, start).find_map(|(b, c)| {
+            self.engine
+                .find_shortest_at(input, b)
+                .map(|end| Match::new(input, char_start + c, b, end))
+        })
+    }
+
+    /// Return an iterator over the successive non-overlapping matches of the regular expression
+    /// in the input string.
+    #[inline]
+    pub fn find_iter<'r, 't>(&'r self, input: &'t str) -> Matches<'r, 't, E> {
+        Matches(MatchPositions::new(self, input))
+    }
+
+    /// Find the leftmost-longest match of the regular expression in the input string, along with
+    /// the positions of its capture groups.
+    #[inline]
+    pub fn captures<'t>(&self, input: &'t str) -> Option<Captures<'_, 't>> {
+        self.find(input).map(|m| self.captures_from(m))
+    }
+
+    /// Return an iterator over the capture groups of the successive non-overlapping matches of the
+    /// regular expression in the input string.
+    #[inline]
+    pub fn captures_iter<'r, 't>(&'r self, input: &'t str) -> CaptureMatches<'r, 't, E> {
+        CaptureMatches(MatchPositions::new(self, input))
+    }
+
+    /// Return the number of capture groups in the regular expression, including the implicit
+    /// group 0 of the whole match.
+    #[inline]
+    pub fn captures_len(&self) -> usize {
+        self.program.captures_len()
+    }
+
+    /// Return the names of the named capture groups mapped to their indices.
+    #[inline]
+    pub fn capture_names(&self) -> &HashMap<String, usize> {
+        self.program.capture_names()
+    }
+
+    /// Replace the leftmost-longest match of the regular expression in the input string. The
+    /// replacement may be a string, in which `$name` and `${name}` refer to capture groups (see
+    /// [Captures::expand]), or a closure that receives the captures of the match.
+    #[inline]
+    pub fn replace<R: Replacer>(&self, input: &str, rep: R) -> String {
+        self.replacen(input, 1, rep)
+    }
+
+    /// Replace all non-overlapping matches of the regular expression in the input string.
+    #[inline]
+    pub fn replace_all<R: Replacer>(&self, input: &str, rep: R) -> String {
+        self.replacen(input, 0, rep)
+    }
+
+    /// Replace at most `limit` non-overlapping matches of the regular expression in the input
+    /// string. If `limit` is 0, all matches are replaced.
+    #[inline]
+    pub fn replacen<R: Replacer>(&self, input: &str, limit: usize, mut rep: R) -> String {
+        let limit = if limit == 0 { usize::MAX } else { limit };
+
+        let mut replaced = String::with_capacity(input.len());
+        let mut last = 0;
+        for m in MatchPositions::new(self, input).take(limit) {
+            replaced.push_str(&input[last..m.byte_start]);
+            rep.replace_append(&self.captures_from(m), &mut replaced);
+            last = m.byte_end;
+        }
+        replaced.push_str(&input[last..]);
+
+        replaced
+    }
+
+    /// Return an iterator over the substrings of the input string separated by matches of the
+    /// regular expression.
+    #[inline]
+    pub fn split<'r, 't>(&'r self, input: &'t str) -> Split<'r, 't, E> {
+        Split::new(MatchPositions::new(self, input))
+    }
+
+    /// Return an iterator over at most `limit` substrings of the input string separated by
+    /// matches of the regular expression. The last substring is the remainder of the input.
+    #[inline]
+    pub fn splitn<'r, 't>(&'r self, input: &'t str, limit: usize) -> SplitN<'r, 't, E> {
+        SplitN::new(self.split(input), limit)
+    }
+
+    /// Determine if the whole stream of chars is within the language described by the regular
+    /// expression.
+    #[inline]
+    pub fn is_match_chars<I>(&self, input: I) -> bool
+    where
+        I: IntoIterator<Item = char>,
+    {
+        self.engine.is_match_chars(input.into_iter())
+    }
+
+    /// Find the leftmost-longest match of the regular expression in a stream of chars.
+    #[inline]
+    pub fn find_chars<I>(&self, input: I) -> Option<StreamMatch>
+    where
+        I: IntoIterator<Item = char>,
+    {
+        self.engine.find_chars(input.into_iter())
+    }
+
+    /// Determine if the text stored in the given chunks, taken in order, is within the language
+    /// described by the regular expression. Matches may span chunk boundaries.
+    #[inline]
+    pub fn is_match_chunks<'c, I>(&self, chunks: I) -> bool
+    where
+        I: IntoIterator<Item = &'c str>,
+    {
+        self.is_match_chars(chunks.into_iter().flat_map(str::chars))
+    }
+
+    /// Find the leftmost-longest match of the regular expression in the text stored in the given
+    /// chunks, taken in order. The offsets of the match are relative to the start of the first
+    /// chunk.
+    #[inline]
+    pub fn find_chunks<'c, I>(&self, chunks: I) -> Option<StreamMatch>
+    where
+        I: IntoIterator<Item = &'c str>,
+    {
+        self.find_chars(chunks.into_iter().flat_map(str::chars))
+    }
+
+    /// Determine if the UTF-8 text read from the given source is within the language described
+    /// by the regular expression. The source is only read as far as needed to decide; errors
+    /// from it and invalid UTF-8 in the text read are returned.
+    #[inline]
+    pub fn is_match_read<R: io::Read>(&self, reader: R) -> io::Result<bool> {
+        stream::with_read_chars(reader, |chars| self.engine.is_match_chars(chars))
+    }
+
+    /// Find the leftmost-longest match of the regular expression in the UTF-8 text read from the
+    /// given source. The source is only read as far as needed to find the match; errors from it
+    /// and invalid UTF-8 in the text read are returned.
+    #[inline]
+    pub fn find_read<R: io::Read>(&self, reader: R) -> io::Result<Option<StreamMatch>> {
+        stream::with_read_chars(reader, |chars| self.engine.find_chars(chars))
+    }
+
+    /// Return the byte offsets of the char boundaries at or after the given byte offset, including
+    /// the end of the input, at which a match might start, along with the number of chars after
+    /// the given offset.
+    #[inline]
+    fn positions<'a>(
+        &'a self,
+        input: &'a str,
+        byte_start: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.prefilter.candidates(input, byte_start)
+    }
+
+    /// Find the positions of the capture groups within a match.
+    #[inline]
+    pub(crate) fn captures_from<'t>(&self, m: Match<'t>) -> Captures<'_, 't> {
+        let input = m.text();
+        let groups = match self.program.captures(input, m.byte_start, m.byte_end) {
+            Some(groups) => groups,
+            // The program and the engine describe the same language.
+            None => unreachable!(),
+        };
+
+        // Groups captured by lookaround assertions may lie outside of the match.
+        let char_start = |b: usize| {
+            if b >= m.byte_start {
+                m.start + input[m.byte_start..b].chars().count()
+            } else {
+                m.start - input[b..m.byte_start].chars().count()
+            }
+        };
+        let groups = groups
+            .into_iter()
+            .map(|g| g.map(|(s, e)| Match::new(input, char_start(s), s, e)))
+            .collect();
+        Captures::new(groups, self.program.capture_names())
+    }
+}
+
+impl<E: Engine> Search for RegExp<E> {
     #[inline]
-    pub fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match<char>> {
-        self.engine.find_shortest_at(input, start)
+    fn search<'t>(
+        &self,
+        input: &'t str,
+        byte_start: usize,
+        char_start: usize,
+    ) -> Option<Match<'t>> {
+        self.positions(input, byte_start).find_map(|(b, c)| {
+            self.engine
+                .find_at(input, b)
+                .map(|end| Match::new(input, char_start + c, b, end))
+        })
     }
 }
 
+/// Return the number of chars before the given byte offset, which must lie on a char boundary.
+#[inline]
+fn char_offset(input: &str, byte_offset: usize) -> usize {
+    input[..byte_offset].chars().count()
+}
+
+/// Return the byte offset of the end of a match returned by an automaton for the input starting
+/// at the given byte offset.
+#[inline]
+fn byte_end(byte_start: usize, m: &automata::Match<char>) -> usize {
+    byte_start + m.span.iter().map(|c| c.len_utf8()).sum::<usize>()
+}
+
 impl RegExp<NFA<CharClass>> {
     /// Create a compiled regular expression that uses an NFA to evaluate input strings.
     #[inline]
     pub fn new(expr: &str) -> parser::Result<Self> {
-        let parser = NFAParser::new();
-        let nfa: NFA<CharClass> = parser.parse(expr)?.unwrap();
+        let (nfa, program, prefilter) = compile(expr)?;
 
         Ok(RegExp {
             expr: expr.to_owned(),
             engine: nfa,
+            program,
+            prefilter,
         })
     }
 }
@@ -73,38 +346,174 @@ impl RegExp<DFA<CharClass>> {
     /// Create a compiled regular expression that uses a DFA to evaluate input strings.
     #[inline]
     pub fn new_with_dfa(expr: &str) -> parser::Result<Self> {
-        let parser = NFAParser::new();
-        let nfa: NFA<CharClass> = parser.parse(expr)?.unwrap();
+        let (nfa, program, prefilter) = compile(expr)?;
         let dfa = nfa.into();
 
         Ok(RegExp {
             expr: expr.to_owned(),
             engine: dfa,
+            program,
+            prefilter,
+        })
+    }
+
+    /// Create a compiled regular expression that uses a DFA to evaluate input strings. The DFA
+    /// is built directly from the syntax tree of the expression with the `followpos`
+    /// construction, rather than from an NFA, but recognizes the same language.
+    #[inline]
+    pub fn new_with_direct_dfa(expr: &str) -> parser::Result<Self> {
+        let ast = ASTParser::new().parse(expr)?.unwrap();
+        let dfa = match followpos::dfa_from_ast(&ast) {
+            Some(dfa) => dfa,
+            // Backreferences and lookaround are the only unsupported nodes produced by the
+            // parser; the NFA parser rejects them with the span at which they occur.
+            None => return Err(NFAParser::<CharClass>::new().parse(expr).unwrap_err()),
+        };
+
+        Ok(RegExp {
+            expr: expr.to_owned(),
+            engine: dfa,
+            program: Program::new(&ast),
+            prefilter: Prefilter::new(&ast),
+        })
+    }
+}
+
+impl RegExp<Backtrack> {
+    /// Create a compiled regular expression that uses a backtracking engine to evaluate input
+    /// strings. Unlike the other engines, this supports backreferences and lookaround assertions,
+    /// but finds the first match in priority order rather than the longest (see [Backtrack]).
+    #[inline]
+    pub fn new_with_backtrack(expr: &str) -> parser::Result<Self> {
+        let ast = ASTParser::new().parse(expr)?.unwrap();
+        let program = Program::new(&ast);
+
+        Ok(RegExp {
+            expr: expr.to_owned(),
+            engine: program.clone().into(),
+            program,
+            prefilter: Prefilter::new(&ast),
         })
     }
+
+    /// Set the maximum number of steps the engine may take when trying to match at each position
+    /// of an input string.
+    #[inline]
+    pub fn with_step_limit(mut self, step_limit: usize) -> Self {
+        self.engine.set_step_limit(step_limit);
+        self
+    }
+
+    /// Determine if the given input string is within the language described by the regular
+    /// expression, failing if the step limit is exceeded.
+    #[inline]
+    pub fn try_is_match(&self, input: &str) -> Result<bool, StepLimitExceeded> {
+        if !self.prefilter.may_match(input) {
+            return Ok(false);
+        }
+        self.engine.try_is_match(input)
+    }
+
+    /// Find the leftmost match of the regular expression in the input string, failing if the step
+    /// limit is exceeded.
+    #[inline]
+    pub fn try_find<'t>(&self, input: &'t str) -> Result<Option<Match<'t>>, StepLimitExceeded> {
+        for (b, c) in self.positions(input, 0) {
+            if let Some(end) = self.engine.try_find_at(input, b)? {
+                return Ok(Some(Match::new(input, c, b, end)));
+            }
+        }
+        Ok(None)
+    }
 }
 
-impl PartialEq<char> for CharClass {
+impl RegExp<AutoEngine> {
+    /// Create a compiled regular expression that uses a DFA to evaluate input strings, or a
+    /// backtracking engine if the expression contains backreferences or lookaround assertions.
     #[inline]
-    fn eq(&self, other: &char) -> bool {
-        self.contains(*other)
+    pub fn new_auto(expr: &str) -> parser::Result<Self> {
+        let ast = ASTParser::new().parse(expr)?.unwrap();
+        let program = Program::new(&ast);
+
+        let engine = if program.needs_backtracking() {
+            AutoEngine::Backtrack(program.clone().into())
+        } else {
+            let nfa = NFAParser::new().parse(expr)?.unwrap();
+            AutoEngine::DFA(nfa.into())
+        };
+
+        Ok(RegExp {
+            expr: expr.to_owned(),
+            engine,
+            program,
+            prefilter: Prefilter::new(&ast),
+        })
     }
 }
 
-impl From<CharClass> for Transition<CharClass> {
+impl RegExp<Derivative> {
+    /// Create a compiled regular expression that evaluates input strings by taking derivatives
+    /// of the expression. The expression may use the intersection (`&`) and complement (`~`)
+    /// operators, which are literals for the other engines: `a&b` matches the strings matched by
+    /// both `a` and `b`, and `~a` matches the strings not matched by `a`. Groups within
+    /// intersections and complements don't capture.
     #[inline]
-    fn from(c: CharClass) -> Self {
-        Transition::Some(c)
+    pub fn new_with_derivatives(expr: &str) -> parser::Result<Self> {
+        let ast = ASTParser::extended().parse(expr)?.unwrap();
+
+        Ok(RegExp {
+            expr: expr.to_owned(),
+            engine: Derivative::from_ast(&ast),
+            program: Program::new(&ast),
+            prefilter: Prefilter::new(&ast),
+        })
     }
 }
 
-/// A trait implemented by regular expression backends, used to evaluate input strings.
+/// Parse a regular expression into an NFA, a program for finding capture groups and a prefilter.
+#[inline]
+fn compile(expr: &str) -> parser::Result<(NFA<CharClass>, Program, Prefilter)> {
+    let nfa = NFAParser::new().parse(expr)?.unwrap();
+    let ast = ASTParser::new().parse(expr)?.unwrap();
+    Ok((nfa, Program::new(&ast), Prefilter::new(&ast)))
+}
+
+/// A trait implemented by regular expression backends, used to evaluate input strings. Offsets
+/// are in bytes and must lie on char boundaries.
 pub trait Engine {
+    /// Determine if the whole input string is matched.
     fn is_match(&self, input: &str) -> bool;
 
-    fn find_at(&self, input: &str, start: usize) -> Option<Match<char>>;
+    /// Find a match beginning exactly at the given offset, returning the offset of its end. The
+    /// automata-based engines find the longest such match.
+    fn find_at(&self, input: &str, start: usize) -> Option<usize>;
+
+    /// Find the shortest match beginning exactly at the given offset, returning the offset of its
+    /// end.
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize>;
 
-    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match<char>>;
+    /// Determine if the whole stream of chars is matched. By default, the chars are collected
+    /// into a string first.
+    #[inline]
+    fn is_match_chars<I>(&self, input: I) -> bool
+    where
+        Self: Sized,
+        I: Iterator<Item = char>,
+    {
+        self.is_match(&input.collect::<String>())
+    }
+
+    /// Find the leftmost-longest match in a stream of chars. By default, the chars are collected
+    /// into a string first; the DFA-based engines read the stream only once, without storing
+    /// it.
+    #[inline]
+    fn find_chars<I>(&self, input: I) -> Option<StreamMatch>
+    where
+        Self: Sized,
+        I: Iterator<Item = char>,
+    {
+        stream::find_collected(self, input)
+    }
 }
 
 impl Engine for NFA<CharClass> {
@@ -114,13 +523,21 @@ impl Engine for NFA<CharClass> {
     }
 
     #[inline]
-    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match<char>> {
-        NFA::find_shortest_at(self, input.chars(), start)
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize> {
+        NFA::find_shortest_at(self, input[start..].chars(), 0).map(|m| byte_end(start, &m))
     }
 
     #[inline]
-    fn find_at(&self, input: &str, start: usize) -> Option<Match<char>> {
-        NFA::find_at(self, input.chars(), start)
+    fn find_at(&self, input: &str, start: usize) -> Option<usize> {
+        NFA::find_at(self, input[start..].chars(), 0).map(|m| byte_end(start, &m))
+    }
+
+    #[inline]
+    fn is_match_chars<I>(&self, input: I) -> bool
+    where
+        I: Iterator<Item = char>,
+    {
+        NFA::is_match(self, input)
     }
 }
 
@@ -131,48 +548,89 @@ impl Engine for DFA<CharClass> {
     }
 
     #[inline]
-    fn find_shortest_at(&self, input: &str, start: usize) -> Option<Match<char>> {
-        DFA::find_shortest_at(self, input.chars(), start).map(|(m, _)| m)
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize> {
+        DFA::find_shortest_at(self, input[start..].chars(), 0).map(|(m, _)| byte_end(start, &m))
+    }
+
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<usize> {
+        DFA::find_at(self, input[start..].chars(), 0).map(|(m, _)| byte_end(start, &m))
+    }
+
+    #[inline]
+    fn is_match_chars<I>(&self, input: I) -> bool
+    where
+        I: Iterator<Item = char>,
+    {
+        DFA::is_match(self, input)
     }
 
     #[inline]
-    fn find_at(&self, input: &str, start: usize) -> Option<Match<char>> {
-        DFA::find_at(self, input.chars(), start).map(|(m, _)| m)
+    fn find_chars<I>(&self, input: I) -> Option<StreamMatch>
+    where
+        I: Iterator<Item = char>,
+    {
+        let is_final = |s| self.final_states.contains(&s);
+        stream::find_deterministic(
+            (self.initial_state, is_final(self.initial_state)),
+            |state, c| self.next_state(state, &c).map(|s| (s, is_final(s))),
+            input,
+        )
     }
 }
 
-impl Disjoin for CharClass {
-    /// Create a set of disjoint CharClass from a set of CharClass. Algorithm inspired by [this
-    /// Stack Overflow answer](https://stackoverflow.com/a/55482655/8955108).
+/// An engine chosen according to the features used by a regular expression: a DFA, unless the
+/// expression contains backreferences or lookaround assertions.
+#[derive(Debug, Clone)]
+pub enum AutoEngine {
+    DFA(DFA<CharClass>),
+    Backtrack(Backtrack),
+}
+
+impl Engine for AutoEngine {
     #[inline]
-    fn disjoin(vec: Vec<&Self>) -> Vec<Self> {
-        let ranges: Vec<_> = vec.iter().flat_map(|cc| cc.ranges.clone()).collect();
+    fn is_match(&self, input: &str) -> bool {
+        match self {
+            Self::DFA(dfa) => Engine::is_match(dfa, input),
+            Self::Backtrack(backtrack) => backtrack.is_match(input),
+        }
+    }
 
-        let mut starts: Vec<_> = ranges.iter().map(|r| (r.start as u32, 1)).collect();
-        let mut ends: Vec<_> = ranges.iter().map(|r| (r.end as u32 + 1, -1)).collect();
-        starts.append(&mut ends);
-        starts.sort_by(|a, b| a.0.cmp(&b.0));
+    #[inline]
+    fn find_shortest_at(&self, input: &str, start: usize) -> Option<usize> {
+        match self {
+            Self::DFA(dfa) => Engine::find_shortest_at(dfa, input, start),
+            Self::Backtrack(backtrack) => backtrack.find_shortest_at(input, start),
+        }
+    }
 
-        let mut prev = 0;
-        let mut count = 0;
-        starts
-            .into_iter()
-            .filter_map(|(x, c)| {
-                let ret = if x > prev && count != 0 {
-                    let ret = CharRange::new(prev.try_into().unwrap(), (x - 1).try_into().unwrap());
-                    Some(ret.into())
-                } else {
-                    None
-                };
-                prev = x;
-                count += c;
-                ret
-            })
-            .collect()
-    }
-
-    #[inline]
-    fn contains(&self, other: &Self) -> bool {
-        !self.intersection(other).is_empty()
+    #[inline]
+    fn find_at(&self, input: &str, start: usize) -> Option<usize> {
+        match self {
+            Self::DFA(dfa) => Engine::find_at(dfa, input, start),
+            Self::Backtrack(backtrack) => backtrack.find_at(input, start),
+        }
+    }
+
+    #[inline]
+    fn is_match_chars<I>(&self, input: I) -> bool
+    where
+        I: Iterator<Item = char>,
+    {
+        match self {
+            Self::DFA(dfa) => dfa.is_match_chars(input),
+            Self::Backtrack(backtrack) => backtrack.is_match_chars(input),
+        }
+    }
+
+    #[inline]
+    fn find_chars<I>(&self, input: I) -> Option<StreamMatch>
+    where
+        I: Iterator<Item = char>,
+    {
+        match self {
+            Self::DFA(dfa) => dfa.find_chars(input),
+            Self::Backtrack(backtrack) => backtrack.find_chars(input),
+        }
     }
 }
@@ -0,0 +1,41 @@
+use crate::captures::Captures;
+
+/// Implemented by types that produce the replacement text for matches of a regular expression.
+pub trait Replacer {
+    /// Append the replacement text for a match to `dst`.
+    fn replace_append(&mut self, caps: &Captures<'_, '_>, dst: &mut String);
+}
+
+/// Strings are expanded as described by [Captures::expand].
+impl Replacer for &str {
+    #[inline]
+    fn replace_append(&mut self, caps: &Captures<'_, '_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+}
+
+impl Replacer for &String {
+    #[inline]
+    fn replace_append(&mut self, caps: &Captures<'_, '_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+}
+
+impl Replacer for String {
+    #[inline]
+    fn replace_append(&mut self, caps: &Captures<'_, '_>, dst: &mut String) {
+        caps.expand(self, dst);
+    }
+}
+
+/// Closures are called with the captures of each match and return the replacement text.
+impl<F, T> Replacer for F
+where
+    F: FnMut(&Captures<'_, '_>) -> T,
+    T: AsRef<str>,
+{
+    #[inline]
+    fn replace_append(&mut self, caps: &Captures<'_, '_>, dst: &mut String) {
+        dst.push_str((*self)(caps).as_ref());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::class::CharClass;
+use crate::matching::Match;
+use crate::parser::{NFAParser, ParseError, Parser};
+
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+use std::iter;
+
+use automata::{dfa::DFAFromNFAs, DFA, NFA};
+
+/// A set of regular expressions compiled into a single DFA, used to determine which of the
+/// expressions match an input in one pass over it.
+#[derive(Debug, Clone)]
+pub struct RegExpSet {
+    /// The regular expressions represented by this structure.
+    exprs: Vec<String>,
+    /// The combined DFA of all the expressions.
+    dfa: DFA<CharClass>,
+    /// The indices of the expressions accepted in each final state of the DFA, in ascending order.
+    accepting: HashMap<usize, Vec<usize>>,
+}
+
+impl RegExpSet {
+    /// Compile a set of regular expressions. The index of each expression in the set is its
+    /// position in the given sequence.
+    #[inline]
+    pub fn new<I, S>(exprs: I) -> Result<Self, RegExpSetError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let parser = NFAParser::new();
+        let exprs: Vec<String> = exprs.into_iter().map(|e| e.as_ref().to_owned()).collect();
+        let nfas: Vec<NFA<CharClass>> = exprs
+            .iter()
+            .enumerate()
+            .map(|(index, expr)| {
+                parser
+                    .parse(expr)
+                    .map(Option::unwrap)
+                    .map_err(|error| RegExpSetError { index, error })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let nfa_refs: Vec<_> = nfas.iter().collect();
+        let DFAFromNFAs { dfa, accepting } = DFAFromNFAs::new(&nfa_refs);
+
+        Ok(Self {
+            exprs,
+            dfa,
+            accepting,
+        })
+    }
+
+    /// Return the regular expressions this was compiled from.
+    #[inline]
+    pub fn patterns(&self) -> &[String] {
+        &self.exprs
+    }
+
+    /// Return the number of regular expressions in the set.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.exprs.len()
+    }
+
+    /// Determine if the set contains no regular expressions.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.exprs.is_empty()
+    }
+
+    /// Determine if the given input string is within the language described by any of the
+    /// regular expressions.
+    #[inline]
+    pub fn is_match(&self, input: &str) -> bool {
+        !self.matches(input).is_empty()
+    }
+
+    /// Return the indices, in ascending order, of every regular expression whose language
+    /// contains the given input string.
+    #[inline]
+    pub fn matches(&self, input: &str) -> Vec<usize> {
+        let mut state = self.dfa.initial_state;
+        for c in input.chars() {
+            state = match self.dfa.next_state(state, &c) {
+                Some(s) => s,
+                None => return Vec::new(),
+            };
+        }
+
+        self.accepting.get(&state).cloned().unwrap_or_default()
+    }
+
+    /// Find the leftmost-longest match of any of the regular expressions in the input string,
+    /// returning the index of the expression along with the match. If several expressions match
+    /// the same span, the one with the lowest index wins.
+    #[inline]
+    pub fn find<'t>(&self, input: &'t str) -> Option<(usize, Match<'t>)> {
+        self.find_at(input, 0)
+    }
+
+    /// Find the leftmost-longest match of any of the regular expressions in the input string,
+    /// starting the search at the given byte offset, which must lie on a char boundary.
+    #[inline]
+    pub fn find_at<'t>(&self, input: &'t str, start: usize) -> Option<(usize, Match<'t>)> {
+        let char_start = input[..start].chars().count();
+        input[start..]
+            .char_indices()
+            .map(|(b, _)| start + b)
+            .chain(iter::once(input.len()))
+            .enumerate()
+            .find_map(|(c, b)| self.longest_at(input, char_start + c, b))
+    }
+
+    /// Find the longest match of any of the regular expressions beginning exactly at the given
+    /// char and byte offsets.
+    #[inline]
+    fn longest_at<'t>(
+        &self,
+        input: &'t str,
+        char_start: usize,
+        byte_start: usize,
+    ) -> Option<(usize, Match<'t>)> {
+        let mut state = self.dfa.initial_state;
+        let mut last = self.winner(state).map(|index| (index, byte_start));
+
+        for (b, c) in input[byte_start..].char_indices() {
+            state = match self.dfa.next_state(state, &c) {
+                Some(s) => s,
+                None => break,
+            };
+
+            if let Some(index) = self.winner(state) {
+                last = Some((index, byte_start + b + c.len_utf8()));
+            }
+        }
+
+        last.map(|(index, byte_end)| (index, Match::new(input, char_start, byte_start, byte_end)))
+    }
+
+    /// Return the lowest index of the regular expressions accepted in the given DFA state.
+    #[inline]
+    fn winner(&self, state: usize) -> Option<usize> {
+        self.accepting
+            .get(&state)
+            .and_then(|indices| indices.first().copied())
+    }
+}
+
+/// Error returned when one of the regular expressions in a [RegExpSet] fails to parse.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegExpSetError {
+    /// The index of the invalid regular expression in the set.
+    pub index: usize,
+    /// The error produced when parsing the regular expression.
+    pub error: ParseError,
+}
+
+impl fmt::Display for RegExpSetError {
+    #[inline]
+    fn fmt<'a>(&self, f: &mut fmt::Formatter<'a>) -> fmt::Result {
+        write!(f, "regular expression {}: {}", self.index, self.error)
+    }
+}
+
+impl error::Error for RegExpSetError {
+    #[inline]
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
@@ -0,0 +1,236 @@
+use crate::regexp::Engine;
+
+use std::io::{self, BufReader, Bytes, Read};
+use std::iter;
+use std::ops::Range;
+use std::str;
+
+/// A match of a regular expression in a stream of chars, such as text read from an
+/// [io::Read] source or stored in chunks. The position of the match is given both as char offsets
+/// and as byte offsets of the UTF-8 encoding of the stream.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct StreamMatch {
+    /// Char offset of the start of the match.
+    pub start: usize,
+    /// Char offset of the last char matched + 1.
+    pub end: usize,
+    /// Byte offset of the start of the match.
+    pub byte_start: usize,
+    /// Byte offset of the last byte matched + 1.
+    pub byte_end: usize,
+}
+
+impl StreamMatch {
+    #[inline]
+    pub(crate) fn new(start: usize, end: usize, byte_start: usize, byte_end: usize) -> Self {
+        Self {
+            start,
+            end,
+            byte_start,
+            byte_end,
+        }
+    }
+
+    /// Return the range of char offsets of the match.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Return the range of byte offsets of the match.
+    #[inline]
+    pub fn byte_range(&self) -> Range<usize> {
+        self.byte_start..self.byte_end
+    }
+
+    /// Determine if the match is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_start == self.byte_end
+    }
+}
+
+/// An iterator over the chars decoded from the UTF-8 bytes of an [io::Read] source. Invalid UTF-8
+/// is reported as an error of kind [io::ErrorKind::InvalidData], after which the iterator ends.
+#[derive(Debug)]
+pub struct ReadChars<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    failed: bool,
+}
+
+impl<R: Read> ReadChars<R> {
+    /// Decode the chars of a source, which is buffered internally.
+    #[inline]
+    pub fn new(reader: R) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            failed: false,
+        }
+    }
+
+    #[inline]
+    fn decode(&mut self, first: u8) -> io::Result<char> {
+        // The number of bytes in the encoding is given by the leading bits of the first.
+        let len = match first {
+            0x00..=0x7f => return Ok(first as char),
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            0xf0..=0xf7 => 4,
+            _ => return Err(invalid_utf8()),
+        };
+
+        let mut buf = [first, 0, 0, 0];
+        for b in buf[1..len].iter_mut() {
+            *b = self.bytes.next().ok_or_else(invalid_utf8)??;
+        }
+        str::from_utf8(&buf[..len])
+            .ok()
+            .and_then(|s| s.chars().next())
+            .ok_or_else(invalid_utf8)
+    }
+}
+
+impl<R: Read> Iterator for ReadChars<R> {
+    type Item = io::Result<char>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+
+        let c = match self.bytes.next()? {
+            Ok(first) => self.decode(first),
+            Err(e) => Err(e),
+        };
+        self.failed = c.is_err();
+        Some(c)
+    }
+}
+
+#[inline]
+fn invalid_utf8() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        "stream did not contain valid UTF-8",
+    )
+}
+
+/// Run a function on the chars decoded from an [io::Read] source, returning the first error
+/// encountered while reading them, if any, instead of its result.
+#[inline]
+pub(crate) fn with_read_chars<R, T, F>(reader: R, f: F) -> io::Result<T>
+where
+    R: Read,
+    F: FnOnce(&mut dyn Iterator<Item = char>) -> T,
+{
+    let mut error = None;
+    let mut chars = ReadChars::new(reader).scan((), |_, c| match c {
+        Ok(c) => Some(c),
+        Err(e) => {
+            error = Some(e);
+            None
+        }
+    });
+
+    let res = f(&mut chars);
+    drop(chars);
+    match error {
+        Some(e) => Err(e),
+        None => Ok(res),
+    }
+}
+
+/// Find the leftmost-longest match in a stream of chars by collecting them into a string, for
+/// engines that need the whole input at once.
+#[inline]
+pub(crate) fn find_collected<E, I>(engine: &E, input: I) -> Option<StreamMatch>
+where
+    E: Engine,
+    I: Iterator<Item = char>,
+{
+    let input: String = input.collect();
+    let starts = input
+        .char_indices()
+        .map(|(b, _)| b)
+        .chain(iter::once(input.len()));
+
+    starts.enumerate().find_map(|(c, b)| {
+        engine.find_at(&input, b).map(|end| {
+            let len = input[b..end].chars().count();
+            StreamMatch::new(c, c + len, b, end)
+        })
+    })
+}
+
+/// Find the leftmost-longest match in a stream of chars with a deterministic automaton, given by
+/// its initial state and a function returning the state reached on a char, if any, and whether it
+/// is accepting. The input is read once, without backtracking, by following the automaton from
+/// every start position at once; runs that reach the same state are merged, keeping the earliest
+/// start.
+#[inline]
+pub(crate) fn find_deterministic<S, I, F>(
+    initial: (S, bool),
+    mut next: F,
+    mut input: I,
+) -> Option<StreamMatch>
+where
+    S: Copy + Eq,
+    I: Iterator<Item = char>,
+    F: FnMut(S, char) -> Option<(S, bool)>,
+{
+    // The state of each run and the char and byte offsets at which it started, in order of start.
+    let mut runs: Vec<(S, usize, usize)> = Vec::new();
+    let mut best: Option<StreamMatch> = None;
+    let (mut pos, mut byte_pos) = (0, 0);
+
+    loop {
+        // A run starts at every position until a match is found, after which later starts can't
+        // be leftmost.
+        if best.is_none() {
+            if !runs.iter().any(|&(s, _, _)| s == initial.0) {
+                runs.push((initial.0, pos, byte_pos));
+            }
+            if initial.1 {
+                best = Some(StreamMatch::new(pos, pos, byte_pos, byte_pos));
+            }
+        }
+
+        let c = match input.next() {
+            Some(c) => c,
+            None => break,
+        };
+        pos += 1;
+        byte_pos += c.len_utf8();
+
+        let mut stepped: Vec<(S, usize, usize)> = Vec::with_capacity(runs.len());
+        for &(state, start, byte_start) in runs.iter() {
+            let (state, is_final) = match next(state, c) {
+                Some(next) => next,
+                None => continue,
+            };
+            if stepped.iter().any(|&(s, _, _)| s == state) {
+                continue;
+            }
+            stepped.push((state, start, byte_start));
+
+            let better = match best {
+                Some(m) => start < m.start || (start == m.start && pos > m.end),
+                None => true,
+            };
+            if is_final && better {
+                best = Some(StreamMatch::new(start, pos, byte_start, byte_pos));
+            }
+        }
+
+        if let Some(m) = best {
+            stepped.retain(|&(_, start, _)| start <= m.start);
+            if stepped.is_empty() {
+                break;
+            }
+        }
+        runs = stepped;
+    }
+
+    best
+}
@@ -1,10 +1,15 @@
 #[allow(unused_macros)]
-
 macro_rules! run_tests {
     ($exprs:expr, $valids:expr, $invalids:expr) => {{
         $exprs.iter().for_each(|&expr| {
             let nfa_re = RegExp::new(expr).unwrap();
             let dfa_re = RegExp::new_with_dfa(expr).unwrap();
+            let direct_re = RegExp::new_with_direct_dfa(expr).unwrap();
+            let backtrack_re = RegExp::new_with_backtrack(expr).unwrap();
+            // & and ~ are operators for the derivative engine.
+            let derivative_re = Some(expr)
+                .filter(|expr| !expr.contains(|c| c == '&' || c == '~'))
+                .map(|expr| RegExp::new_with_derivatives(expr).unwrap());
             $valids.iter().for_each(|s| {
                 assert!(
                     nfa_re.is_match(s),
@@ -19,6 +24,29 @@ macro_rules! run_tests {
                     expr,
                     s
                 );
+
+                assert!(
+                    direct_re.is_match(s),
+                    r#""{}" failed to match "{}" using direct dfa"#,
+                    expr,
+                    s
+                );
+
+                assert!(
+                    backtrack_re.is_match(s),
+                    r#""{}" failed to match "{}" using backtracking"#,
+                    expr,
+                    s
+                );
+
+                if let Some(derivative_re) = &derivative_re {
+                    assert!(
+                        derivative_re.is_match(s),
+                        r#""{}" failed to match "{}" using derivatives"#,
+                        expr,
+                        s
+                    );
+                }
             });
             $invalids.iter().for_each(|s| {
                 assert_eq!(
@@ -35,6 +63,29 @@ macro_rules! run_tests {
                     expr,
                     s
                 );
+                assert_eq!(
+                    direct_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using direct dfa"#,
+                    expr,
+                    s
+                );
+                assert_eq!(
+                    backtrack_re.is_match(s),
+                    false,
+                    r#""{}" matched "{}" using backtracking"#,
+                    expr,
+                    s
+                );
+                if let Some(derivative_re) = &derivative_re {
+                    assert_eq!(
+                        derivative_re.is_match(s),
+                        false,
+                        r#""{}" matched "{}" using derivatives"#,
+                        expr,
+                        s
+                    );
+                }
             });
         })
     }};
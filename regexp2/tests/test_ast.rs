@@ -0,0 +1,215 @@
+use regexp2::ast::ASTNode;
+use regexp2::class::CharClass;
+use regexp2::parser::{ASTParser, Parser};
+use regexp2::RegExp;
+
+fn parse(expr: &str) -> ASTNode<CharClass> {
+    ASTParser::new().parse(expr).unwrap().unwrap()
+}
+
+#[test]
+fn test_display() {
+    let exprs = [
+        "",
+        "abc",
+        "a|b|c",
+        "(?:a|b)c",
+        "a*b+c?",
+        r"\d+\.\d*",
+        r"\w\W\s\S\h\v",
+        ".",
+        "[^a]",
+        "[_a-z]",
+        r"[\-\]\^]",
+        r"a\|b\(\)\[\*\+\?\.",
+        r"\n\t\x{0}\x{1}\x{a0}",
+        "()",
+        "(a)(?<name>b)",
+        r"(a)\1(?:)0",
+        "(?=a)(?!b)(?<=c)(?<!d)",
+        "(?:a*)*",
+        "(?:a?)+",
+        "(?:(?:a+)?)*",
+        "x{1}$^]",
+    ];
+    for expr in exprs.iter() {
+        assert_eq!(*expr, parse(expr).to_string());
+    }
+
+    let cases = [
+        ("(?:a)", "a"),
+        ("(?:ab)c", "abc"),
+        ("a**", "(?:a*)*"),
+        ("a?+", "(?:a?)+"),
+        ("a+?*", "(?:(?:a+)?)*"),
+        ("(?:a|b)|c", "a|b|c"),
+        ("[a]", "a"),
+        ("[ab]", "[ab]"),
+        ("[abcx]", "[a-cx]"),
+        ("[^\n]", "."),
+        (r"[\s\S]", r"[\s\S]"),
+        (r"[\d\D]", r"[\s\S]"),
+        ("[0-9a-zA-Z_]", r"\w"),
+        (r"[^\w]", r"\W"),
+        (r"\R", r"\r\n|\v"),
+        (r"\Q.*\E", r"\.\*"),
+        ("\u{feff}", r"\x{feff}"),
+        ("a\0b", r"a\x{0}b"),
+    ];
+    for (expr, display) in cases.iter() {
+        assert_eq!(*display, parse(expr).to_string());
+    }
+
+    let extended = |expr| {
+        ASTParser::<CharClass>::extended()
+            .parse(expr)
+            .unwrap()
+            .unwrap()
+            .to_string()
+    };
+    assert_eq!("~a*b", extended("~a*b"));
+    assert_eq!("(?:~a)*", extended("(?:~a)*"));
+    assert_eq!("~(?:a*)?", extended("~a*?"));
+    assert_eq!("a&b|c", extended("a&b|c"));
+    assert_eq!("(?:a|b)&c", extended("(?:a|b)&c"));
+    assert_eq!(r"a\&\~b", extended(r"a\&\~b"));
+}
+
+#[test]
+fn test_display_reparses() {
+    let exprs = [
+        r"(?:\w+\.)*\w+@\w+(?:\.\w+)+",
+        r"(a|b)*a(a|b)(a|b)",
+        r"[\p{Greek}\d]+",
+        r"(?<x>[^\s\d])\k<x>",
+        "a(?:)b",
+    ];
+    for expr in exprs.iter() {
+        let display = parse(expr).to_string();
+        assert_eq!(display, parse(&display).to_string());
+    }
+}
+
+#[test]
+fn test_display_round_trip() {
+    // Printing a tree and parsing it again gives the same tree.
+    let exprs = [
+        "a**",
+        "a?+",
+        "a+?",
+        "a*?+",
+        "(?:a*)*",
+        "(?:a|b)?+c",
+        "(a+)?",
+        r"(a)\1+?",
+        "(?:ab)+?|c*+",
+        "a(?:)b",
+        r"(?:\w+\.)*\w+@\w+(?:\.\w+)+",
+    ];
+    for expr in exprs.iter() {
+        let ast = parse(expr);
+        assert_eq!(ast, parse(&ast.to_string()), "{}", expr);
+        // Concatenations are associated differently once simplified.
+        let simplified = ast.simplify();
+        assert_eq!(
+            simplified,
+            parse(&simplified.to_string()).simplify(),
+            "{}",
+            expr
+        );
+    }
+
+    let extended = |expr: &str| {
+        ASTParser::<CharClass>::extended()
+            .parse(expr)
+            .unwrap()
+            .unwrap()
+    };
+    for expr in ["~a*?", "(?:~a)+?", "a&b*?|~c"].iter() {
+        let ast = extended(expr);
+        assert_eq!(ast, extended(&ast.to_string()), "{}", expr);
+    }
+}
+
+#[test]
+fn test_long_expression() {
+    // The syntax trees of long expressions are as deep as they are long; printing and simplifying
+    // them must not overflow the stack.
+    let literal = "a".repeat(15_000);
+    let ast = parse(&literal);
+    assert_eq!(literal, ast.to_string());
+    assert_eq!(literal, ast.simplify().to_string());
+
+    let expr = format!("{0}b|{0}c", "a".repeat(5_000));
+    assert_eq!(
+        format!("{}[bc]", "a".repeat(5_000)),
+        parse(&expr).simplify().to_string()
+    );
+}
+
+#[test]
+fn test_simplify() {
+    let cases = [
+        ("abc|abd", "ab[cd]"),
+        ("(?:ab|ac)|ad", "a[b-d]"),
+        ("a|b|c", "[a-c]"),
+        ("[a-z]|[A-Z]|_", "[A-Z_a-z]"),
+        ("(?:a|b)c", "[ab]c"),
+        ("x|x|y", "[xy]"),
+        ("ab|a", "ab?"),
+        ("abc|ab|a", "a(?:bc?)?"),
+        ("(?:a*)*", "a*"),
+        ("(?:a+)*", "a*"),
+        ("(?:a+)+", "a+"),
+        ("(?:a?)?", "a?"),
+        ("(?:a+)?", "a*"),
+        ("(?:a?)+", "a*"),
+        ("a(?:)b", "ab"),
+        ("(?:)*", ""),
+        ("(?:(?:)|b)", "b?"),
+        ("a|(?:)|b", "[ab]?"),
+        // Alternatives are only merged or factored when adjacent, which keeps their order.
+        ("a|bc|b", "a|bc?"),
+        ("ab|b|ac", "ab|b|ac"),
+        ("ab|c|ad", "ab|c|ad"),
+        ("a|ab", "a|ab"),
+        // Capture groups are kept.
+        ("(a*)*", "(a*)*"),
+        ("(ab|ac)", "(a[bc])"),
+    ];
+    for (expr, simplified) in cases.iter() {
+        assert_eq!(*simplified, parse(expr).simplify().to_string());
+    }
+
+    let ast = ASTParser::<CharClass>::extended()
+        .parse("~~(?:a|b)")
+        .unwrap()
+        .unwrap();
+    assert_eq!("[ab]", ast.simplify().to_string());
+}
+
+#[test]
+fn test_simplify_equivalent() {
+    let exprs = [
+        "foo|foobar|fox|bar",
+        "(?:ab|ac|a)*d",
+        "(?:x+)*y|(?:x+)+z",
+        "(a|b)c|ad",
+    ];
+    let inputs = [
+        "foo", "foobar", "fox", "bar", "fo", "d", "abacad", "xxy", "z", "bc", "ad",
+    ];
+    for expr in exprs.iter() {
+        let simplified = parse(expr).simplify().to_string();
+        let re = RegExp::new(expr).unwrap();
+        let simplified_re = RegExp::new_with_backtrack(&simplified).unwrap();
+        let backtrack_re = RegExp::new_with_backtrack(expr).unwrap();
+        for input in inputs.iter() {
+            assert_eq!(re.is_match(input), simplified_re.is_match(input));
+            assert_eq!(
+                backtrack_re.find(input).map(|m| m.as_str()),
+                simplified_re.find(input).map(|m| m.as_str())
+            );
+        }
+    }
+}
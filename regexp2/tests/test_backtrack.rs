@@ -0,0 +1,173 @@
+use regexp2::{parser::ParseErrorKind, AutoEngine, RegExp, StepLimitExceeded};
+
+fn find_str<'t>(re: &RegExp<impl regexp2::Engine>, input: &'t str) -> Option<&'t str> {
+    re.find(input).map(|m| m.as_str())
+}
+
+#[test]
+fn test_backreferences() {
+    let re = RegExp::new_with_backtrack(r"(a|b)\1").unwrap();
+    assert!(re.is_match("aa"));
+    assert!(re.is_match("bb"));
+    assert!(!re.is_match("ab"));
+    assert!(!re.is_match("ba"));
+
+    let re = RegExp::new_with_backtrack(r"(?<word>\w+) \k<word>").unwrap();
+    assert_eq!(Some("the the"), find_str(&re, "in the the end"));
+    assert_eq!(None, find_str(&re, "in the thing"));
+
+    // Backreferences match the text of the last iteration of a repeated group.
+    let re = RegExp::new_with_backtrack(r"(?:(\d),)*\1").unwrap();
+    assert!(re.is_match("1,2,2"));
+    assert!(!re.is_match("1,2,1"));
+
+    // A backreference to a group that hasn't participated in the match fails.
+    let re = RegExp::new_with_backtrack(r"(?:(a)|b)\1").unwrap();
+    assert!(re.is_match("aa"));
+    assert!(!re.is_match("b"));
+
+    let re = RegExp::new_with_backtrack(r"<(\w+)>.*</\1>").unwrap();
+    let caps = re.captures("x <em>é</em> y").unwrap();
+    assert_eq!("<em>é</em>", caps.get(0).unwrap().as_str());
+    assert_eq!("em", caps.get(1).unwrap().as_str());
+    assert_eq!(3..5, caps.get(1).unwrap().range());
+}
+
+#[test]
+fn test_lookaround() {
+    let re = RegExp::new_with_backtrack(r"\w+(?=!)").unwrap();
+    assert_eq!(Some("world"), find_str(&re, "hello world!"));
+
+    let re = RegExp::new_with_backtrack(r"\d+(?!px|\d)").unwrap();
+    assert_eq!(Some("20"), find_str(&re, "10px 20em"));
+
+    let re = RegExp::new_with_backtrack(r"(?<=\$)\d+").unwrap();
+    assert_eq!(Some("30"), find_str(&re, "10 €20 $30"));
+    let m = re.find("10 €20 $30").unwrap();
+    assert_eq!((8..10, 10..12), (m.range(), m.byte_range()));
+
+    let re = RegExp::new_with_backtrack(r"(?<!-|\d)\d+").unwrap();
+    assert_eq!(
+        vec!["1", "3"],
+        re.find_iter("1 -2 3")
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    // Lookaround assertions don't consume input, so they may be combined.
+    let re = RegExp::new_with_backtrack(r"(?=\w*\d)(?=\w*[a-z])\w+").unwrap();
+    assert!(re.is_match("abc123"));
+    assert!(!re.is_match("abcdef"));
+    assert!(!re.is_match("123456"));
+
+    // Groups captured by a positive assertion are kept, even outside of the match. Lookbehind
+    // assertions match the shortest text they can.
+    let re = RegExp::new_with_backtrack(r"(?<=(\w+) )\w+").unwrap();
+    assert_eq!(
+        "o",
+        re.captures("hello world").unwrap().get(1).unwrap().as_str()
+    );
+    let re = RegExp::new_with_backtrack(r"(?<=(h\w+) )\w+").unwrap();
+    let caps = re.captures("hello world").unwrap();
+    assert_eq!("world", caps.get(0).unwrap().as_str());
+    assert_eq!("hello", caps.get(1).unwrap().as_str());
+    assert_eq!(0..5, caps.get(1).unwrap().range());
+}
+
+#[test]
+fn test_priority() {
+    // The backtracking engine finds the first match in priority order, not the longest.
+    let re = RegExp::new_with_backtrack("a|ab").unwrap();
+    assert_eq!(Some("a"), find_str(&re, "ab"));
+    let re = RegExp::new("a|ab").unwrap();
+    assert_eq!(Some("ab"), find_str(&re, "ab"));
+
+    let re = RegExp::new_with_backtrack("a+").unwrap();
+    assert_eq!(Some("aaa"), find_str(&re, "aaa"));
+    assert_eq!(Some("a"), re.find_shortest("aaa").map(|m| m.as_str()));
+}
+
+#[test]
+fn test_auto() {
+    // Without backreferences or lookaround, the leftmost-longest match is found by a DFA.
+    let re = RegExp::new_auto("(a|ab)b*").unwrap();
+    assert!(matches!(re.engine(), AutoEngine::DFA(_)));
+    assert_eq!(Some("abb"), find_str(&re, "abb"));
+
+    let re = RegExp::new_auto(r"(a|b)\1").unwrap();
+    assert!(matches!(re.engine(), AutoEngine::Backtrack(_)));
+    assert_eq!(Some("bb"), find_str(&re, "abba"));
+
+    let re = RegExp::new_auto("a(?=b)").unwrap();
+    assert!(matches!(re.engine(), AutoEngine::Backtrack(_)));
+    assert_eq!(Some(1..2), re.find("aab").map(|m| m.range()));
+
+    // The automata-based engines reject these features.
+    for expr in [r"(a)\1", "a(?=b)", "a(?!b)", "(?<=a)b", "(?<!a)b"] {
+        let e = RegExp::new(expr).unwrap_err();
+        assert_eq!(ParseErrorKind::BacktrackingRequired, e.kind, "{}", expr);
+        let e = RegExp::new_with_dfa(expr).unwrap_err();
+        assert_eq!(ParseErrorKind::BacktrackingRequired, e.kind, "{}", expr);
+    }
+}
+
+#[test]
+fn test_step_limit() {
+    // Exponential backtracking fails quickly instead of hanging.
+    let re = RegExp::new_with_backtrack(r"(a*)*\1b").unwrap();
+    let input = "a".repeat(40) + "!b";
+    assert_eq!(Err(StepLimitExceeded), re.try_find(&input));
+    assert_eq!(Err(StepLimitExceeded), re.try_is_match(&input));
+    assert!(!re.is_match(&input));
+    assert_eq!(None, re.find(&input));
+
+    let re = re.with_step_limit(usize::MAX);
+    assert_eq!(Ok(true), re.try_is_match("aab"));
+
+    let re = RegExp::new_with_backtrack(r"(a|b)\1")
+        .unwrap()
+        .with_step_limit(4);
+    assert_eq!(Err(StepLimitExceeded), re.try_is_match("aa"));
+}
+
+#[test]
+fn test_invalid() {
+    let errors = [
+        (r"\1", ParseErrorKind::UndefinedGroup("1".to_owned()), 0..2),
+        (
+            r"(a)\2",
+            ParseErrorKind::UndefinedGroup("2".to_owned()),
+            3..5,
+        ),
+        (
+            r"\1(a)",
+            ParseErrorKind::UndefinedGroup("1".to_owned()),
+            0..2,
+        ),
+        (
+            r"(a)\k<b>",
+            ParseErrorKind::UndefinedGroup("b".to_owned()),
+            3..8,
+        ),
+        (r"\k<a", ParseErrorKind::UnterminatedEscape, 0..4),
+        (r"\ka", ParseErrorKind::UnterminatedEscape, 0..2),
+        (
+            r"(a)\k<1>",
+            ParseErrorKind::InvalidGroupName("1".to_owned()),
+            3..8,
+        ),
+        (r"(a)[\1]", ParseErrorKind::BackreferenceInClass, 4..6),
+        (r"(a)[b-\1]", ParseErrorKind::BackreferenceInClass, 6..8),
+        ("(?<a)", ParseErrorKind::InvalidGroup, 0..5),
+        ("(?=a", ParseErrorKind::UnbalancedParentheses, 0..3),
+    ];
+
+    for (expr, kind, span) in errors.iter().cloned() {
+        let e = RegExp::new_with_backtrack(expr).unwrap_err();
+        assert_eq!((kind, span), (e.kind, e.span), "{}", expr);
+    }
+
+    let e = RegExp::new("x(?<=a)").unwrap_err();
+    assert_eq!(ParseErrorKind::BacktrackingRequired, e.kind);
+    assert_eq!(1..7, e.span);
+}
@@ -0,0 +1,130 @@
+use regexp2::{parser::ParseErrorKind, AutoEngine, BuildError, RegExpBuilder, StepLimitExceeded};
+
+#[test]
+fn test_default_limits() {
+    let builder = RegExpBuilder::new();
+    assert!(builder.build(r"\w+@\w+\.com").unwrap().is_match("a@b.com"));
+    assert!(builder.build_with_dfa(r"(a|b)*c").unwrap().is_match("abac"));
+    assert!(builder
+        .build_with_backtrack(r"(a)\1")
+        .unwrap()
+        .is_match("aa"));
+    assert!(builder.build_auto(r"x(?=y)").unwrap().has_match("xy"));
+
+    let err = builder.build(r"a)").unwrap_err();
+    assert!(matches!(err, BuildError::Parse(e) if e.kind == ParseErrorKind::UnbalancedParentheses));
+}
+
+#[test]
+fn test_dfa_state_limit() {
+    // The DFA has a state for each combination of the last 21 chars.
+    let expr = format!("(a|b)*a{}", "(a|b)".repeat(20));
+    let builder = RegExpBuilder::new();
+    assert_eq!(
+        BuildError::DFAStateLimitExceeded(RegExpBuilder::DEFAULT_DFA_STATE_LIMIT),
+        builder.build_with_dfa(&expr).unwrap_err()
+    );
+    assert_eq!(
+        BuildError::DFAStateLimitExceeded(RegExpBuilder::DEFAULT_DFA_STATE_LIMIT),
+        builder.build_auto(&expr).unwrap_err()
+    );
+
+    // The NFA doesn't need to be converted.
+    assert!(builder.build(&expr).is_ok());
+
+    let builder = RegExpBuilder::new().with_dfa_state_limit(3);
+    assert!(builder.build_with_dfa("abc").is_err());
+    assert!(builder.build_with_dfa("ab").is_ok());
+}
+
+#[test]
+fn test_nfa_state_limit() {
+    let builder = RegExpBuilder::new().with_nfa_state_limit(10);
+    assert!(builder.build("abc").is_ok());
+    assert_eq!(
+        BuildError::NFAStateLimitExceeded(10),
+        builder.build(&"a".repeat(20)).unwrap_err()
+    );
+    assert_eq!(
+        BuildError::NFAStateLimitExceeded(10),
+        builder.build_with_dfa(&"a".repeat(20)).unwrap_err()
+    );
+}
+
+#[test]
+fn test_size_limit() {
+    // Three classes and two concatenations.
+    let builder = RegExpBuilder::new().with_size_limit(5);
+    assert!(builder.build("abc").is_ok());
+    assert!(builder.build("(a|b)*").is_ok());
+
+    let size_error = |err| match err {
+        BuildError::Parse(e) if e.kind == ParseErrorKind::SizeLimitExceeded => e.span,
+        e => panic!("unexpected error {:?}", e),
+    };
+    assert_eq!(3..4, size_error(builder.build("abcd").unwrap_err()));
+    assert_eq!(
+        3..4,
+        size_error(builder.build_with_dfa("abcd").unwrap_err())
+    );
+    assert_eq!(
+        3..4,
+        size_error(builder.build_with_backtrack("abcd").unwrap_err())
+    );
+    assert_eq!(4..5, size_error(builder.build_auto("(a)bc").unwrap_err()));
+
+    // Long expressions fail without being parsed to the end, so their NFAs and syntax trees are
+    // never built.
+    let builder = RegExpBuilder::new();
+    let expr = "a".repeat(1_000_000);
+    let limit = RegExpBuilder::DEFAULT_SIZE_LIMIT;
+    assert!(size_error(builder.build(&expr).unwrap_err()).start < limit);
+    assert!(size_error(builder.build_auto(&expr).unwrap_err()).start < limit);
+}
+
+#[test]
+fn test_nesting_limit() {
+    let builder = RegExpBuilder::new().with_nesting_limit(3);
+    assert!(builder.build("((a))").is_ok());
+    assert!(builder.build("(([a]))").is_ok());
+    assert!(builder.build("[[[a]]]").is_ok());
+
+    let err = match builder.build("((((a))))").unwrap_err() {
+        BuildError::Parse(e) => e,
+        e => panic!("unexpected error {:?}", e),
+    };
+    assert_eq!(ParseErrorKind::NestingLimitExceeded, err.kind);
+    assert_eq!(3..4, err.span);
+
+    // Groups and classes are counted together.
+    let err = match builder.build_with_backtrack("a((b[[c]]))").unwrap_err() {
+        BuildError::Parse(e) => e,
+        e => panic!("unexpected error {:?}", e),
+    };
+    assert_eq!(ParseErrorKind::NestingLimitExceeded, err.kind);
+    assert_eq!(5..6, err.span);
+
+    // Deeply nested expressions fail without overflowing the stack.
+    let builder = RegExpBuilder::new();
+    let expr = "[".repeat(100_000);
+    assert!(matches!(
+        builder.build(&expr),
+        Err(BuildError::Parse(e)) if e.kind == ParseErrorKind::NestingLimitExceeded
+    ));
+    let expr = format!("{}a{}", "(".repeat(1000), ")".repeat(1000));
+    assert!(builder.build_auto(&expr).is_err());
+}
+
+#[test]
+fn test_builder_step_limit() {
+    let builder = RegExpBuilder::new().with_step_limit(10_000);
+    let re = builder.build_with_backtrack(r"(a*)*\1b").unwrap();
+    let input = "a".repeat(40) + "!b";
+    assert_eq!(Err(StepLimitExceeded), re.try_is_match(&input));
+
+    let re = builder.build_auto(r"(a*)*\1b").unwrap();
+    match re.engine() {
+        AutoEngine::Backtrack(backtrack) => assert_eq!(10_000, backtrack.step_limit()),
+        AutoEngine::DFA(_) => panic!("expected the backtracking engine"),
+    }
+}
@@ -0,0 +1,90 @@
+use regexp2::{parser::ParseErrorKind, RegExp};
+
+include!("macros.rs");
+
+fn group_strs(re: &RegExp<impl regexp2::Engine>, input: &str) -> Vec<Option<String>> {
+    let caps = re.captures(input).unwrap();
+    (0..caps.len())
+        .map(|i| caps.get(i).map(|m| m.as_str().to_owned()))
+        .collect()
+}
+
+#[test]
+fn test_groups() {
+    let exprs = ["(a|b)*c", "(?:a|b)*c", "(?<x>a|b)*c", "(?P<x>a|b)*c"];
+    let valids = ["c", "abc", "bbac"];
+    let invalids = ["", "ab", "cc"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_captures() {
+    let s = |v: &str| Some(v.to_owned());
+
+    let re = RegExp::new(r"(\d+)-(\d+)").unwrap();
+    assert_eq!(3, re.captures_len());
+    assert_eq!(
+        vec![s("12-345"), s("12"), s("345")],
+        group_strs(&re, "ab 12-345 cd")
+    );
+
+    let caps = re.captures("ab 12-345 cd").unwrap();
+    assert_eq!(3..9, caps.get(0).unwrap().range());
+    assert_eq!(3..5, caps.get(1).unwrap().range());
+    assert_eq!(6..9, caps.get(2).unwrap().range());
+    assert!(caps.get(3).is_none());
+
+    // Groups that don't participate in the match are None; repeated groups capture their last
+    // iteration.
+    let re = RegExp::new_with_dfa("(a)|(b)").unwrap();
+    assert_eq!(vec![s("b"), None, s("b")], group_strs(&re, "b"));
+    let re = RegExp::new_with_dfa("(a|b)*").unwrap();
+    assert_eq!(vec![s("abb"), s("b")], group_strs(&re, "abb"));
+
+    // Non-capturing groups aren't numbered.
+    let re = RegExp::new("(?:a(b))(c)").unwrap();
+    assert_eq!(vec![s("abc"), s("b"), s("c")], group_strs(&re, "abc"));
+
+    // Nested groups are numbered by their left parentheses.
+    let re = RegExp::new("((a)(b()))").unwrap();
+    assert_eq!(
+        vec![s("ab"), s("ab"), s("a"), s("b"), s("")],
+        group_strs(&re, "ab")
+    );
+
+    // Captures are found within the leftmost-longest match.
+    let re = RegExp::new("(a|ab)(c|bcd)").unwrap();
+    assert_eq!(vec![s("abcd"), s("a"), s("bcd")], group_strs(&re, "abcd"));
+}
+
+#[test]
+fn test_named_captures() {
+    let re = RegExp::new(r"(?<year>\d+)-(?P<month>\d+)").unwrap();
+    assert_eq!(Some(&1), re.capture_names().get("year"));
+    assert_eq!(Some(&2), re.capture_names().get("month"));
+
+    let caps = re.captures("2020-08").unwrap();
+    assert_eq!("2020", caps.name("year").unwrap().as_str());
+    assert_eq!(5..7, caps.name("month").unwrap().range());
+    assert!(caps.name("day").is_none());
+}
+
+#[test]
+fn test_invalid_groups() {
+    let err = RegExp::new("a(?x)").unwrap_err();
+    assert_eq!(ParseErrorKind::InvalidGroup, err.kind);
+    assert_eq!(1..3, err.span);
+
+    let err = RegExp::new("(?<a").unwrap_err();
+    assert_eq!(ParseErrorKind::InvalidGroup, err.kind);
+
+    let err = RegExp::new("(?<1a>b)").unwrap_err();
+    assert_eq!(ParseErrorKind::InvalidGroupName("1a".into()), err.kind);
+
+    let err = RegExp::new("(?<>b)").unwrap_err();
+    assert_eq!(ParseErrorKind::InvalidGroupName("".into()), err.kind);
+
+    let err = RegExp::new("(?<a>b)(?<a>c)").unwrap_err();
+    assert_eq!(ParseErrorKind::DuplicateGroupName("a".into()), err.kind);
+    assert_eq!(7..12, err.span);
+}
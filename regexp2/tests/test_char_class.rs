@@ -116,3 +116,130 @@ fn test_special_classes() {
     let invalids = ["", "a", "A", "5", "_"];
     run_tests!(&exprs, &valids, &invalids);
 }
+
+#[test]
+fn test_overlapping_ranges() {
+    let exprs = ["[a-cm-nx-yb-z]", "[x-ym-na-cb-z]", "[b-zx-ya-c]"];
+    let valids = ["a", "b", "m", "x", "y", "z"];
+    let invalids = ["", "A", "{", "ab"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_nested_classes() {
+    let exprs = ["[a[bc]]", "[[a-b]c]", "[[a][b][c]]", "[[[abc]]]"];
+    let valids = ["a", "b", "c"];
+    let invalids = ["", "d", "[", "]", "ab"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[^[a-c]x]", "[^x[a-c]]"];
+    let valids = ["d", "y", "z", "["];
+    let invalids = ["", "a", "c", "x", "ad"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_set_operations() {
+    let exprs = [
+        r"[\w&&[^0-9]]",
+        r"[\w--\d]",
+        r"[\w--[0-9]]",
+        r"[[\w]&&[^\d]]",
+    ];
+    let valids = ["a", "Z", "_"];
+    let invalids = ["", "0", "9", "-", " ", "é", "a1"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"[\pL&&\p{Greek}]", r"[\p{Greek}--\P{L}]"];
+    let valids = ["α", "Ω"];
+    let invalids = ["", "a", "͵", "α1"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[a-f~~d-k]", "[[a-f]~~[d-k]]"];
+    let valids = ["a", "c", "g", "k"];
+    let invalids = ["", "d", "e", "f", "l"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    // Operators are applied from left to right, and a leading ^ negates the result.
+    let exprs = ["[a-z--aeiou&&a-m]", "[^a-z--aeiou&&a-m]"];
+    run_tests!(&exprs[..1], &["b", "c", "m"], &["a", "e", "n", "z"]);
+    run_tests!(&exprs[1..], &["a", "e", "n", "z", "A"], &["b", "c", "m"]);
+
+    // Single &, - and ~ are literal.
+    let exprs = ["[&~-]", "[-~&]"];
+    let valids = ["&", "~", "-"];
+    let invalids = ["", "a", "&&"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    // Intersections may be empty.
+    let exprs = ["x[a&&b]?", "x[^a&&b]"];
+    run_tests!(&exprs[..1], &["x"], &["xa", "xb"]);
+    run_tests!(&exprs[1..], &["xa", "xb", "x\n"], &["x"]);
+}
+
+#[test]
+fn test_posix_classes() {
+    let exprs = ["[[:alpha:]]", "[[:upper:][:lower:]]", "[^[:^alpha:]]"];
+    let valids = ["a", "Z"];
+    let invalids = ["", "0", "_", "é", ":", "["];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[[:xdigit:]]+", "[[:digit:]a-fA-F]+"];
+    let valids = ["0", "ff", "C0FFEE"];
+    let invalids = ["", "g", "0x1"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[[:punct:]]"];
+    let valids = ["!", "-", "[", "~", "_"];
+    let invalids = ["", "a", "0", " ", "«"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[[:alnum:]_]+", "[[:word:]]+"];
+    let valids = ["snake_case1"];
+    let invalids = ["", "kebab-case", "é"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[[:space:]]", "[[:blank:]\n\u{b}\u{c}\r]"];
+    let valids = [" ", "\t", "\n", "\u{b}", "\u{c}", "\r"];
+    let invalids = ["", "a", "\u{a0}"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = ["[[:cntrl:]]"];
+    run_tests!(&exprs, &["\0", "\n", "\u{7f}"], &["", " ", "a"]);
+    let exprs = ["[[:graph:]]", "[[:print:]--[:space:]]"];
+    run_tests!(&exprs, &["!", "a", "~"], &["", " ", "\t", "\u{7f}", "é"]);
+
+    // Outside of an enclosing class, or without the closing colon, the brackets form a class.
+    let exprs = ["[:alpha:]", "[[:alpha]]"];
+    let valids = ["a", ":", "h"];
+    let invalids = ["", "b", "[", "]", "a]"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_line_classes() {
+    let exprs = [r"a\hb", r"a[\h]b"];
+    let valids = ["a b", "a\tb", "a\u{a0}b", "a\u{3000}b"];
+    let invalids = ["", "ab", "a\nb", "a\u{b}b"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"a\vb", r"a[\v]b"];
+    let valids = ["a\nb", "a\rb", "a\u{b}b", "a\u{85}b", "a\u{2028}b"];
+    let invalids = ["", "avb", "a b", "a\tb", "a\r\nb"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\H+", r"[^\h]+"];
+    run_tests!(&exprs, &["a", "a\nb"], &["", " ", "a b"]);
+    let exprs = [r"\V+", r"[^\v]+"];
+    run_tests!(&exprs, &["a", "a b"], &["", "\n", "a\rb"]);
+
+    let exprs = [r"a\Rb", r"a(?:\r\n|\v)b"];
+    let valids = ["a\nb", "a\rb", "a\r\nb", "a\u{c}b", "a\u{2029}b"];
+    let invalids = ["", "ab", "a\n\nb", "a\n\rb", "aRb"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"(\w+\R)+"];
+    let valids = ["a\n", "a\r\nb\n", "a\rb\r\n"];
+    let invalids = ["", "a", "a\n\n"];
+    run_tests!(&exprs, &valids, &invalids);
+}
@@ -14,3 +14,21 @@ fn test_concat() {
     let invalids = ["a", " ", " a", "a  ", " a "];
     run_tests!(&exprs, &valids, &invalids);
 }
+
+#[test]
+fn test_long_literal() {
+    // The syntax tree of a long literal is as deep as it is long; compiling it must not overflow
+    // the stack.
+    let expr = "a".repeat(2500);
+
+    let re = RegExp::new(&expr).unwrap();
+    assert!(re.is_match(&expr));
+    assert_eq!(re.literal_prefix(), expr);
+
+    let re = RegExp::new_with_dfa(&expr).unwrap();
+    assert!(re.is_match(&expr));
+
+    let re = RegExp::new_with_backtrack(&expr).unwrap();
+    assert!(re.is_match(&expr));
+    assert_eq!(re.captures(&expr).unwrap().get(0).unwrap().as_str(), expr);
+}
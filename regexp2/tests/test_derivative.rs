@@ -0,0 +1,155 @@
+use regexp2::automata::{DFA, NFA};
+use regexp2::class::CharClass;
+use regexp2::{
+    parser::ParseErrorKind, AutoEngine, Backtrack, Derivative, Engine, RegExp, RegExpSet,
+};
+
+include!("macros.rs");
+
+fn find_str<'t>(re: &RegExp<impl Engine>, input: &'t str) -> Option<&'t str> {
+    re.find(input).map(|m| m.as_str())
+}
+
+#[test]
+fn test_intersection() {
+    let re = RegExp::new_with_derivatives(r".*a.*&.*b.*").unwrap();
+    assert!(re.is_match("ab"));
+    assert!(re.is_match("xbya"));
+    assert!(!re.is_match("aa"));
+    assert!(!re.is_match(""));
+
+    let re = RegExp::new_with_derivatives(r"\w+&[^\d]+").unwrap();
+    assert_eq!(Some("abc"), find_str(&re, "12 abc3"));
+
+    // Intersection binds looser than concatenation and tighter than union.
+    let re = RegExp::new_with_derivatives(r"ab&a.|c").unwrap();
+    assert!(re.is_match("ab"));
+    assert!(re.is_match("c"));
+    assert!(!re.is_match("ac"));
+}
+
+#[test]
+fn test_complement() {
+    // A C comment, which doesn't contain the closing delimiter before its end.
+    let re = RegExp::new_with_derivatives(r"/\*~([\s\S]*\*/[\s\S]*)\*/").unwrap();
+    assert!(re.is_match("/* a * / b */"));
+    assert!(re.is_match("/**/"));
+    assert!(!re.is_match("/* a */ b */"));
+    assert_eq!(
+        vec!["/* a */", "/* b */"],
+        re.find_iter("x /* a */ y /* b */")
+            .map(|m| m.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    // Complement binds tighter than concatenation, but looser than quantifiers.
+    let re = RegExp::new_with_derivatives(r"~a*b").unwrap();
+    assert!(re.is_match("cb"));
+    assert!(re.is_match("bab"));
+    assert!(!re.is_match("aab"));
+    assert!(!re.is_match("b"));
+
+    let re = RegExp::new_with_derivatives(r"~(abc)").unwrap();
+    assert!(re.is_match(""));
+    assert!(re.is_match("ab"));
+    assert!(!re.is_match("abc"));
+
+    let re = RegExp::new_with_derivatives(r"~~a").unwrap();
+    assert!(re.is_match("a"));
+    assert!(!re.is_match("b"));
+}
+
+#[test]
+fn test_derivative_captures() {
+    let re = RegExp::new_with_derivatives(r"(\w+)=(~(.*;.*))").unwrap();
+    let caps = re.captures("key=a b").unwrap();
+    assert_eq!("key", caps.get(1).unwrap().as_str());
+    assert_eq!("a b", caps.get(2).unwrap().as_str());
+}
+
+#[test]
+fn test_derivative_states() {
+    // States are only built as they are reached.
+    let engine = Derivative::new(r"[a-z]+\d").unwrap();
+    assert_eq!(0, engine.total_states());
+    assert!(engine.is_match("ab1"));
+    assert_eq!(2, engine.total_states());
+    assert!(!engine.is_match("abc"));
+    assert_eq!(2, engine.total_states());
+    assert!(!engine.is_match("ab1c"));
+    assert_eq!(3, engine.total_states());
+}
+
+#[test]
+fn test_extended_operators() {
+    // & and ~ are literals for the other engines.
+    let exprs = [r"a&b", r"~a"];
+    let valids = ["a&b", "~a"];
+    exprs.iter().zip(valids.iter()).for_each(|(&expr, &valid)| {
+        assert!(RegExp::new(expr).unwrap().is_match(valid));
+        assert!(RegExp::new_with_dfa(expr).unwrap().is_match(valid));
+        assert!(RegExp::new_with_backtrack(expr).unwrap().is_match(valid));
+    });
+
+    // They may be escaped for the derivative engine.
+    let re = RegExp::new_with_derivatives(r"a\&\~b").unwrap();
+    assert!(re.is_match("a&~b"));
+
+    let exprs = [r"ab*|c"];
+    let valids = ["a", "abb", "c"];
+    let invalids = ["ac", "abc", "b"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_derivative_errors() {
+    let err = RegExp::new_with_derivatives(r"a~").unwrap_err();
+    assert_eq!(ParseErrorKind::UnbalancedOperators, err.kind);
+    assert_eq!(1..2, err.span);
+
+    let err = RegExp::new_with_derivatives(r"(~)").unwrap_err();
+    assert_eq!(ParseErrorKind::UnbalancedOperators, err.kind);
+
+    let err = RegExp::new_with_derivatives(r"(a)\1").unwrap_err();
+    assert_eq!(ParseErrorKind::BacktrackingRequired, err.kind);
+    let err = RegExp::new_with_derivatives(r"a(?=b)").unwrap_err();
+    assert_eq!(ParseErrorKind::BacktrackingRequired, err.kind);
+}
+
+#[test]
+fn test_sync() {
+    // Each engine keeps the regular expressions shareable between threads, including the lazily
+    // built states of the derivative engine.
+    fn assert_sync<T: Sync + Send>() {}
+    assert_sync::<RegExp<NFA<CharClass>>>();
+    assert_sync::<RegExp<DFA<CharClass>>>();
+    assert_sync::<RegExp<Backtrack>>();
+    assert_sync::<RegExp<AutoEngine>>();
+    assert_sync::<RegExp<Derivative>>();
+    assert_sync::<RegExpSet>();
+
+    let re = RegExp::new_with_derivatives(r"\w+&~(.*\d.*)").unwrap();
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                assert!(re.is_match("abc"));
+                assert!(!re.is_match("ab1"));
+            });
+        }
+    });
+}
+
+#[test]
+fn test_derivatives_long_expression() {
+    // The syntax tree of a long literal is as deep as it is long; building its terms and taking
+    // their derivatives must not overflow the stack.
+    let expr = "a".repeat(15_000);
+    let re = RegExp::new_with_derivatives(&expr).unwrap();
+    assert!(re.is_match(&expr));
+    assert!(!re.is_match(&expr[1..]));
+
+    let expr = "(?:ab|c)*d".repeat(2_000) + "&~(?:.*ccc.*)";
+    let re = RegExp::new_with_derivatives(&expr).unwrap();
+    assert!(re.is_match(&"abccd".repeat(2_000)));
+    assert!(!re.is_match(&"abcccd".repeat(2_000)));
+}
@@ -0,0 +1,123 @@
+use regexp2::{parser::ParseErrorKind, RegExp};
+
+include!("macros.rs");
+
+#[test]
+fn test_control_escapes() {
+    let exprs = [
+        r"\n", r"[\n]", r"\x0a", r"\x{A}", r"\u000A", r"\u{a}", r"\012",
+    ];
+    let valids = ["\n"];
+    let invalids = ["", "n", "\r", r"\n"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"a\tb", r"a[\t]b", r"a\x09b", r"a\011b"];
+    let valids = ["a\tb"];
+    let invalids = ["", "atb", "a b", "a\\tb"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\r\n", r"[\r][\n]", r"\x{d}\x{a}"];
+    let valids = ["\r\n"];
+    let invalids = ["", "\n", "\r", "rn"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\0", r"[\0]", r"\x00", r"\00"];
+    let valids = ["\0"];
+    let invalids = ["", "0", "\\0"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    // At most two octal digits follow \0.
+    let exprs = [r"\0012"];
+    let valids = ["\u{1}2"];
+    let invalids = ["", "\n", "\u{1}"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_hex_escapes() {
+    let exprs = [r"\x41", r"\x{41}", r"\u0041", r"\u{41}", r"[\x41]"];
+    let valids = ["A"];
+    let invalids = ["", "a", "x41", "AA"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\u{1F600}", r"[\u{1F600}]", r"\u{01f600}"];
+    let valids = ["😀"];
+    let invalids = ["", "u", "😁"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"[\x{3b1}-\u{3c9}]+"];
+    let valids = ["α", "ωψ"];
+    let invalids = ["", "a", "Α"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_quoting() {
+    let exprs = [r"\Q(a|b)*\E", r"\Q(a|b)*"];
+    let valids = ["(a|b)*"];
+    let invalids = ["", "a", "ab", "(a|b)"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"x\Q.+\E+", r"x\Q.\E\+*"];
+    run_tests!(&exprs[..1], &["x.+", "x.++"], &["", "x", "x.", "x.+.+"]);
+    run_tests!(&exprs[1..], &["x.", "x.+", "x.++"], &["", "x", "xa+"]);
+
+    // Backslashes other than \E are quoted too.
+    let exprs = [r"\Q\d\\E"];
+    let valids = [r"\d\"];
+    let invalids = ["", "1", r"\d"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"[\Q]-^\E]", r"[\Q^\E\Q]\E-]"];
+    let valids = ["]", "-", "^"];
+    let invalids = ["", "a", "\\", "_"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    // Quoted text is not a range.
+    let exprs = [r"[\Qa-z\E]", r"[a-\Qz\E]"];
+    let valids = ["a", "-", "z"];
+    let invalids = ["", "b", "y"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"a\Q\Eb"];
+    let valids = ["ab"];
+    let invalids = ["", "a", "b"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_invalid_escapes() {
+    let errors = [
+        (r"\x4", ParseErrorKind::UnterminatedEscape, 0..3),
+        (r"\xg1", ParseErrorKind::UnterminatedEscape, 0..2),
+        (r"a\u12", ParseErrorKind::UnterminatedEscape, 1..5),
+        (r"\u{41", ParseErrorKind::UnterminatedEscape, 0..5),
+        (r"\u{}", ParseErrorKind::InvalidCodePoint("".into()), 0..4),
+        (
+            r"\u{zz}",
+            ParseErrorKind::InvalidCodePoint("zz".into()),
+            0..6,
+        ),
+        (
+            r"\u{d800}",
+            ParseErrorKind::InvalidCodePoint("d800".into()),
+            0..8,
+        ),
+        (
+            r"[\u{110000}]",
+            ParseErrorKind::InvalidCodePoint("110000".into()),
+            1..11,
+        ),
+        (
+            r"\x{1234567}",
+            ParseErrorKind::InvalidCodePoint("1234567".into()),
+            0..11,
+        ),
+        (r"[\Qab]", ParseErrorKind::UnterminatedCharacterClass, 0..6),
+    ];
+
+    for (expr, kind, span) in errors.iter().cloned() {
+        let e = RegExp::new(expr).unwrap_err();
+        assert_eq!((kind, span), (e.kind, e.span), "{}", expr);
+    }
+}
@@ -0,0 +1,81 @@
+use regexp2::class::CharClass;
+use regexp2::dfa_from_ast;
+use regexp2::parser::{ASTParser, ParseErrorKind, Parser};
+use regexp2::RegExp;
+
+#[test]
+fn test_direct_dfa_states() {
+    // The DFA of Example 3.37 has four states, one fewer than the subset construction.
+    let re = RegExp::new_with_direct_dfa("(a|b)*abb").unwrap();
+    assert_eq!(4, re.engine().total_states);
+    assert_eq!(1, re.engine().final_states.len());
+    assert!(re.is_match("babb"));
+    assert!(!re.is_match("abba"));
+
+    let re = RegExp::new_with_direct_dfa("").unwrap();
+    assert_eq!(1, re.engine().total_states);
+    assert!(re.is_match(""));
+    assert!(!re.is_match("a"));
+}
+
+#[test]
+fn test_direct_dfa_agrees() {
+    let exprs = [
+        r"\w+@\w+(\.\w+)+",
+        "[a-f][c-h]|[e-z]x",
+        r"(\p{Greek}|\d)+",
+        "(?:ab|a)(?:bc|c)*",
+        "a?b+c*",
+        "[^x]*x",
+        "()",
+    ];
+    let inputs = [
+        "", "a@b.com", "dc", "fx", "ex", "αβ1", "abbcc", "ac", "b", "yyx", "x", "aab", "a@b",
+    ];
+    for expr in exprs.iter() {
+        let dfa_re = RegExp::new_with_dfa(expr).unwrap();
+        let direct_re = RegExp::new_with_direct_dfa(expr).unwrap();
+        for input in inputs.iter() {
+            assert_eq!(dfa_re.is_match(input), direct_re.is_match(input));
+            assert_eq!(
+                dfa_re.find(input).map(|m| m.range()),
+                direct_re.find(input).map(|m| m.range())
+            );
+        }
+    }
+
+    let re = RegExp::new_with_direct_dfa(r"(?<key>\w+)=(?<value>\d+)").unwrap();
+    let caps = re.captures("x: width=80").unwrap();
+    assert_eq!("width", caps.name("key").unwrap().as_str());
+    assert_eq!("80", caps.name("value").unwrap().as_str());
+}
+
+#[test]
+fn test_direct_dfa_unsupported() {
+    let err = RegExp::new_with_direct_dfa(r"(a)\1").unwrap_err();
+    assert_eq!(ParseErrorKind::BacktrackingRequired, err.kind);
+    assert!(RegExp::new_with_direct_dfa("a(?=b)").is_err());
+
+    let ast = ASTParser::<CharClass>::extended()
+        .parse("a&b")
+        .unwrap()
+        .unwrap();
+    assert!(dfa_from_ast(&ast).is_none());
+    let ast = ASTParser::<CharClass>::new().parse("a&b").unwrap().unwrap();
+    assert!(dfa_from_ast(&ast).unwrap().is_match("a&b".chars()));
+}
+
+#[test]
+fn test_direct_dfa_long_literal() {
+    // The syntax tree of a long literal is as deep as it is long; building its DFA must not
+    // overflow the stack.
+    let expr = "a".repeat(15_000);
+    let re = RegExp::new_with_direct_dfa(&expr).unwrap();
+    assert!(re.is_match(&expr));
+    assert!(!re.is_match(&expr[1..]));
+
+    let expr = "(?:ab|c)*d".repeat(2_000);
+    let re = RegExp::new_with_direct_dfa(&expr).unwrap();
+    assert!(re.is_match(&"abcd".repeat(2_000)));
+    assert!(!re.is_match(&"abc".repeat(2_000)));
+}
@@ -0,0 +1,69 @@
+use regexp2::RegExp;
+
+#[test]
+fn test_extraction() {
+    let literals = [
+        ("abc", "abc", "abc"),
+        ("foo(bar|baz)+", "fooba", "fooba"),
+        ("(foo|fob)x", "fo", "fo"),
+        (r"\d+px", "", "px"),
+        (r"\w+ = \d+;", "", " = "),
+        ("a*b", "", "b"),
+        ("(ab|cd)", "", ""),
+        ("(xab|ycab)", "", "ab"),
+        ("héllo|hélp", "hél", "hél"),
+        ("a?bc", "", "bc"),
+        ("[a]bc", "abc", "abc"),
+        ("[ab]c", "", "c"),
+        ("", "", ""),
+    ];
+
+    for &(expr, prefix, required) in literals.iter() {
+        let re = RegExp::new(expr).unwrap();
+        assert_eq!(prefix, re.literal_prefix(), "prefix of {}", expr);
+        assert_eq!(
+            required,
+            re.required_literal(),
+            "required literal of {}",
+            expr
+        );
+    }
+
+    // Lookaround assertions and backreferences don't contribute consumed text.
+    let re = RegExp::new_with_backtrack(r"(?<=\$)abc(\d)\1").unwrap();
+    assert_eq!("abc", re.literal_prefix());
+}
+
+#[test]
+fn test_prefiltered_search() {
+    let re = RegExp::new_with_dfa("ab+c").unwrap();
+    let m = re.find("aaabbc ab ç abc").unwrap();
+    assert_eq!((2..6, "abbc"), (m.range(), m.as_str()));
+    let m = re.find_at("aaabbc ab ç abc", 6).unwrap();
+    assert_eq!((12..15, 13..16), (m.range(), m.byte_range()));
+    assert!(re.find("ab ac abd").is_none());
+
+    // Occurrences of the prefix may overlap.
+    let re = RegExp::new("aab").unwrap();
+    assert_eq!(Some(1..4), re.find("aaab").map(|m| m.range()));
+
+    let re = RegExp::new(r"é\d+").unwrap();
+    let found: Vec<_> = re
+        .find_iter("é1 ée é23é4")
+        .map(|m| (m.range(), m.as_str()))
+        .collect();
+    assert_eq!(vec![(0..2, "é1"), (6..9, "é23"), (9..11, "é4")], found);
+    assert_eq!("x ée xx", re.replace_all("é1 ée é23é4", "x"));
+
+    // The required text is checked before searching.
+    let re = RegExp::new(r"\w+px").unwrap();
+    assert!(re.find("10em 20 em").is_none());
+    assert_eq!(Some("20px"), re.find("10em 20px").map(|m| m.as_str()));
+    assert!(!re.is_match("10em"));
+    assert!(re.is_match("10px"));
+    assert!(!re.has_match("pp x"));
+
+    // A prefix that is also a possible empty match is still found at the end of the input.
+    let re = RegExp::new("(ab)?").unwrap();
+    assert_eq!(Some(2..2), re.find_at("xy", 2).map(|m| m.range()));
+}
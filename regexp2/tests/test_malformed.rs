@@ -1,3 +1,4 @@
+use regexp2::parser::ParseErrorKind;
 use regexp2::RegExp;
 
 macro_rules! run_invalid_tests {
@@ -16,3 +17,60 @@ fn test_malformed() {
     ];
     run_invalid_tests!(&exprs);
 }
+
+macro_rules! assert_parse_error {
+    ($expr:expr, $kind:expr, $span:expr) => {{
+        let err = RegExp::new($expr).unwrap_err();
+        assert_eq!(err.kind, $kind, "{}", $expr);
+        assert_eq!(err.span, $span, "{}", $expr);
+    }};
+}
+
+#[test]
+fn test_error_positions() {
+    assert_parse_error!("ab(cd", ParseErrorKind::UnbalancedParentheses, 2..3);
+    assert_parse_error!("ab)", ParseErrorKind::UnbalancedParentheses, 2..3);
+    assert_parse_error!("a||b", ParseErrorKind::UnbalancedOperators, 2..3);
+    assert_parse_error!("ab|", ParseErrorKind::UnbalancedOperators, 2..3);
+    assert_parse_error!("a|*", ParseErrorKind::MisplacedQuantifier('*'), 2..3);
+    assert_parse_error!("(+a)", ParseErrorKind::MisplacedQuantifier('+'), 1..2);
+    assert_parse_error!("x[]", ParseErrorKind::EmptyCharacterClass, 1..3);
+    assert_parse_error!("x[^]", ParseErrorKind::EmptyCharacterClass, 1..4);
+    assert_parse_error!("x[abc", ParseErrorKind::UnterminatedCharacterClass, 1..5);
+    assert_parse_error!("[a&&]", ParseErrorKind::EmptyCharacterClass, 0..5);
+    assert_parse_error!("[--a]", ParseErrorKind::EmptyCharacterClass, 0..3);
+    assert_parse_error!("[a[]]", ParseErrorKind::EmptyCharacterClass, 2..4);
+    assert_parse_error!("[a[b]", ParseErrorKind::UnterminatedCharacterClass, 0..5);
+    assert_parse_error!("[0-9z-a]", ParseErrorKind::InvalidRange('z', 'a'), 4..7);
+    assert_parse_error!("ab\\", ParseErrorKind::DanglingEscape, 2..3);
+    assert_parse_error!("[a\\", ParseErrorKind::DanglingEscape, 2..3);
+    assert_parse_error!("a\\p{Greek", ParseErrorKind::UnterminatedEscape, 1..9);
+    assert_parse_error!(
+        "é\\p{Foo}",
+        ParseErrorKind::InvalidUnicodeProperty("Foo".into()),
+        1..8
+    );
+    assert_parse_error!(
+        "[[:alfa:]]",
+        ParseErrorKind::InvalidPosixClass("alfa".into()),
+        1..9
+    );
+    assert_parse_error!("[a\\R]", ParseErrorKind::LinebreakInClass, 2..4);
+    assert_parse_error!("[a-\\R]", ParseErrorKind::LinebreakInClass, 3..5);
+}
+
+#[test]
+fn test_render() {
+    let err = RegExp::new("[0-9z-a]").unwrap_err();
+    assert_eq!(err.offset(), 4);
+    assert_eq!(
+        err.render("[0-9z-a]"),
+        "invalid range 'z-a' at position 4\n  |\n  | [0-9z-a]\n  |     ^^^"
+    );
+
+    let err = RegExp::new("ab\\").unwrap_err();
+    assert_eq!(
+        err.render("ab\\"),
+        "dangling escape at position 2\n  |\n  | ab\\\n  |   ^"
+    );
+}
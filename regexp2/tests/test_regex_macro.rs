@@ -0,0 +1,83 @@
+use regexp2::{regex, CompiledRegExp, RegExp};
+
+static IDENT: &CompiledRegExp = regex!(r"[A-Za-z_]\w*");
+
+#[test]
+fn test_static() {
+    assert!(IDENT.is_match("snake_case1"));
+    assert!(!IDENT.is_match("1st"));
+    assert_eq!(r"[A-Za-z_]\w*", IDENT.as_str());
+
+    let found: Vec<_> = IDENT
+        .find_iter("let x1 = y_2;")
+        .map(|m| m.as_str())
+        .collect();
+    assert_eq!(vec!["let", "x1", "y_2"], found);
+}
+
+#[test]
+fn test_same_as_dfa() {
+    let inputs = [
+        "",
+        "a",
+        "ab",
+        "abb",
+        "aab",
+        "1.5e10",
+        "foo.bar",
+        "héllo wörld",
+        "αβγ",
+        "x = 10px;",
+    ];
+    macro_rules! check {
+        ($($expr:literal),*) => {{
+            $(
+                let compiled = regex!($expr);
+                let re = RegExp::new_with_dfa($expr).unwrap();
+                for input in inputs.iter() {
+                    assert_eq!(re.is_match(input), compiled.is_match(input), "{} {}", $expr, input);
+                    assert_eq!(re.find(input), compiled.find(input), "{} {}", $expr, input);
+                    assert_eq!(
+                        re.find_shortest(input),
+                        compiled.find_shortest(input),
+                        "{} {}",
+                        $expr,
+                        input
+                    );
+                    assert_eq!(
+                        re.find_iter(input).collect::<Vec<_>>(),
+                        compiled.find_iter(input).collect::<Vec<_>>(),
+                        "{} {}",
+                        $expr,
+                        input
+                    );
+                }
+                assert_eq!(re.literal_prefix(), compiled.literal_prefix());
+                assert_eq!(re.required_literal(), compiled.required_literal());
+            )*
+        }};
+    }
+
+    check!(
+        "",
+        "a|ab",
+        "ab*",
+        "(a|b)*b",
+        r"\d+(\.\d+)?(e\d+)?",
+        r"\w+\.\w+",
+        r"\p{Greek}+",
+        "[^ ]+",
+        r"\w+px",
+        "é|ö"
+    );
+}
+
+#[test]
+fn test_offsets() {
+    let re = regex!(r"\d+");
+    let m = re.find_at("é1 ü23", 3).unwrap();
+    assert_eq!((4..6, 6..8), (m.range(), m.byte_range()));
+    assert!(re.has_match_at("é1 ü23", 3));
+    assert!(!re.has_match_at("é1 ü", 3));
+    assert_eq!(2, re.engine().total_states());
+}
@@ -0,0 +1,8 @@
+#[test]
+fn test_regex_macro_errors() {
+    // Invalid expressions are compile errors on the literal, with the offending part of the
+    // expression underlined in the message. Where the compiler supports it, the error points at
+    // that part of the literal itself.
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
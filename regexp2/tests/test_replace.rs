@@ -0,0 +1,112 @@
+use regexp2::{Captures, RegExp};
+
+#[test]
+fn test_find_iter() {
+    let re = RegExp::new(r"\d+").unwrap();
+    let ranges: Vec<_> = re.find_iter("a1 22 é333").map(|m| m.range()).collect();
+    assert_eq!(vec![1..2, 3..5, 7..10], ranges);
+
+    // Empty matches are found between chars, but not immediately after another match.
+    let re = RegExp::new_with_dfa("a*").unwrap();
+    let ranges: Vec<_> = re.find_iter("baaé").map(|m| m.range()).collect();
+    assert_eq!(vec![0..0, 1..3, 4..4], ranges);
+
+    let re = RegExp::new("x").unwrap();
+    assert_eq!(0, re.find_iter("").count());
+}
+
+#[test]
+fn test_find() {
+    let re = RegExp::new_with_dfa("ab+").unwrap();
+    assert_eq!(2..5, re.find("xxabbx").unwrap().range());
+    assert_eq!(7..9, re.find_at("xxabbx ab", 3).unwrap().range());
+    assert!(re.find_at("xxabbx", 6).is_none());
+    assert_eq!(2..4, re.find_shortest("xxabbx").unwrap().range());
+    assert!(re.has_match("xxabbx"));
+    assert!(!re.has_match("xxbbx"));
+}
+
+#[test]
+fn test_replace() {
+    let re = RegExp::new(r"(?<word>\w+) (\w+)").unwrap();
+    assert_eq!("b a c d", re.replace("a b c d", "$2 $1"));
+    assert_eq!("b a d c", re.replace_all("a b c d", "${2} ${word}"));
+    assert_eq!("b a c d", re.replacen("a b c d", 1, "$2 $word"));
+    assert_eq!("$a! c d", re.replace("a b c d", "$$$1$3!"));
+    assert_eq!("${x a c d", re.replace("a b c d", "${x $1"));
+
+    let re = RegExp::new_with_dfa(r"\d+").unwrap();
+    assert_eq!("a# b# c", re.replace_all("a12 b3 c", "#"));
+    assert_eq!("a12 b3 c", re.replacen("a12 b3 c", 1, String::from("$0")));
+    assert_eq!(
+        "a13 b4 c",
+        re.replace_all("a12 b3 c", |caps: &Captures<'_, '_>| {
+            let n: u32 = caps.get(0).unwrap().as_str().parse().unwrap();
+            (n + 1).to_string()
+        })
+    );
+    assert_eq!("no digits", re.replace_all("no digits", "#"));
+
+    let re = RegExp::new("x*").unwrap();
+    assert_eq!("-a-b-", re.replace_all("ab", "-"));
+    assert_eq!("-a-b-", re.replace_all("xaxxb", "-"));
+}
+
+#[test]
+fn test_split() {
+    let re = RegExp::new(r"[,;]\s*").unwrap();
+    let parts: Vec<_> = re.split("a, b;c,,  d").collect();
+    assert_eq!(vec!["a", "b", "c", "", "d"], parts);
+
+    let parts: Vec<_> = re.split(",a,").collect();
+    assert_eq!(vec!["", "a", ""], parts);
+
+    let parts: Vec<_> = re.split("").collect();
+    assert_eq!(vec![""], parts);
+
+    let parts: Vec<_> = re.splitn("a, b;c,,  d", 3).collect();
+    assert_eq!(vec!["a", "b", "c,,  d"], parts);
+
+    let parts: Vec<_> = re.splitn("a, b", 5).collect();
+    assert_eq!(vec!["a", "b"], parts);
+
+    let parts: Vec<_> = re.splitn("a, b", 1).collect();
+    assert_eq!(vec!["a, b"], parts);
+
+    assert_eq!(0, re.splitn("a, b", 0).count());
+}
+
+#[test]
+fn test_offsets() {
+    let input = "αβ 12 γ345";
+    let re = RegExp::new_with_dfa(r"\d+").unwrap();
+
+    let m = re.find(input).unwrap();
+    assert_eq!(3..5, m.range());
+    assert_eq!(5..7, m.byte_range());
+    assert_eq!("12", &input[m.byte_range()]);
+    assert_eq!("12", m.as_str());
+
+    // Offsets passed to the *_at methods are byte offsets.
+    let m = re.find_at(input, m.byte_end).unwrap();
+    assert_eq!(7..10, m.range());
+    assert_eq!(10..13, m.byte_range());
+    assert_eq!("345", &input[m.byte_range()]);
+    assert!(re.has_match_at(input, 10));
+    assert!(!re.has_match_at(input, 13));
+
+    let m = re.find_shortest_at(input, 10).unwrap();
+    assert_eq!(10..11, m.byte_range());
+
+    let re = RegExp::new(r"(\p{Greek})(\d*)").unwrap();
+    let caps: Vec<_> = re
+        .captures_iter(input)
+        .map(|caps| {
+            (
+                caps.get(1).unwrap().byte_range(),
+                caps.get(2).unwrap().byte_range(),
+            )
+        })
+        .collect();
+    assert_eq!(vec![(0..2, 2..2), (2..4, 4..4), (8..10, 10..13)], caps);
+}
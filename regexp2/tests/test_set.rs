@@ -0,0 +1,59 @@
+use regexp2::{parser::ParseErrorKind, RegExpSet};
+
+#[test]
+fn test_matches() {
+    let set = RegExpSet::new([r"\d+", r"[a-z]+", r"\w+", "abc", ""]).unwrap();
+    assert_eq!(5, set.len());
+
+    assert_eq!(vec![0, 2], set.matches("123"));
+    assert_eq!(vec![1, 2, 3], set.matches("abc"));
+    assert_eq!(vec![1, 2], set.matches("abcd"));
+    assert_eq!(vec![2], set.matches("a1_"));
+    assert_eq!(vec![4], set.matches(""));
+    assert!(set.matches("a b").is_empty());
+
+    assert!(set.is_match("abc"));
+    assert!(!set.is_match("-"));
+}
+
+#[test]
+fn test_find() {
+    let set = RegExpSet::new(["if", "[a-z]+", r"\d+", "=="]).unwrap();
+
+    let (index, m) = set.find("if").unwrap();
+    assert_eq!(0, index);
+    assert_eq!(0..2, m.range());
+
+    // The longest match wins over an earlier pattern.
+    let (index, m) = set.find("iffy").unwrap();
+    assert_eq!(1, index);
+    assert_eq!(0..4, m.range());
+
+    // The leftmost match wins over a longer one further on.
+    let (index, m) = set.find("  12 == abcdef").unwrap();
+    assert_eq!(2, index);
+    assert_eq!(2..4, m.range());
+    assert_eq!("12", m.as_str());
+
+    let (index, m) = set.find_at("  12 == abcdef", 4).unwrap();
+    assert_eq!(3, index);
+    assert_eq!(5..7, m.range());
+
+    assert!(set.find("  ").is_none());
+    assert!(set.find_at("ab", 2).is_none());
+}
+
+#[test]
+fn test_empty_set() {
+    let set = RegExpSet::new(Vec::<&str>::new()).unwrap();
+    assert!(set.is_empty());
+    assert!(!set.is_match(""));
+    assert!(set.find("abc").is_none());
+}
+
+#[test]
+fn test_invalid() {
+    let err = RegExpSet::new(["a", "b", "(c"]).unwrap_err();
+    assert_eq!(2, err.index);
+    assert_eq!(ParseErrorKind::UnbalancedParentheses, err.error.kind);
+}
@@ -0,0 +1,161 @@
+use regexp2::{Engine, ReadChars, RegExp, StreamMatch};
+
+use std::io::{self, Read};
+
+/// A source that returns its data a few bytes at a time, splitting multi-byte chars.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.0.len().min(buf.len()).min(3);
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+/// A source that fails after returning its data.
+struct Failing<'a>(&'a [u8]);
+
+impl Read for Failing<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Err(io::Error::other("disconnected"));
+        }
+        let n = self.0.len().min(buf.len());
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+fn assert_find_chars<E: Engine>(re: &RegExp<E>, input: &str) {
+    let expected = re.find(input).map(|m| StreamMatch {
+        start: m.start,
+        end: m.end,
+        byte_start: m.byte_start,
+        byte_end: m.byte_end,
+    });
+    assert_eq!(
+        expected,
+        re.find_chars(input.chars()),
+        "{} in {:?}",
+        re.as_str(),
+        input
+    );
+}
+
+#[test]
+fn test_find_chars_agrees() {
+    let exprs = [
+        "abc",
+        "a*",
+        "(a|b)*c",
+        "x|xyz|y",
+        r"\d+(\.\d+)?",
+        "[^a]b",
+        "é+ü",
+        "",
+    ];
+    let inputs = [
+        "",
+        "abc",
+        "xxabcabc",
+        "bbac",
+        "xyzxy",
+        "3.14 and 2.",
+        "cb ab",
+        "aéééüé",
+        "zzz",
+    ];
+    for expr in exprs.iter() {
+        let nfa = RegExp::new(expr).unwrap();
+        let dfa = RegExp::new_with_dfa(expr).unwrap();
+        let backtrack = RegExp::new_with_backtrack(expr).unwrap();
+        let auto = RegExp::new_auto(expr).unwrap();
+        let derivative = RegExp::new_with_derivatives(expr).unwrap();
+        for input in inputs.iter() {
+            assert_find_chars(&nfa, input);
+            assert_find_chars(&dfa, input);
+            assert_find_chars(&backtrack, input);
+            assert_find_chars(&auto, input);
+            assert_find_chars(&derivative, input);
+        }
+    }
+}
+
+#[test]
+fn test_is_match_chars() {
+    let re = RegExp::new_with_dfa("(a|b)*c").unwrap();
+    assert!(re.is_match_chars("abac".chars()));
+    assert!(!re.is_match_chars("abacd".chars()));
+    assert!(re.is_match_chars(vec!['c']));
+
+    let re = RegExp::new_with_backtrack(r"(a+)b\1").unwrap();
+    assert!(re.is_match_chars("aabaa".chars()));
+    assert!(!re.is_match_chars("aaba".chars()));
+
+    // The stream is only read as far as needed.
+    let re = RegExp::new_with_dfa("ab").unwrap();
+    assert!(!re.is_match_chars("xb".chars().chain(std::iter::repeat('b'))));
+    let m = re.find_chars("xxab".chars().chain(std::iter::repeat('b')));
+    assert_eq!(Some(2..4), m.map(|m| m.range()));
+}
+
+#[test]
+fn test_chunks() {
+    let re = RegExp::new_with_dfa(r"fn \w+").unwrap();
+    let chunks = ["let x; f", "n ma", "in() {}"];
+    let m = re.find_chunks(chunks.iter().copied()).unwrap();
+    assert_eq!(7..14, m.range());
+    assert_eq!(7..14, m.byte_range());
+    assert_eq!("fn main", &chunks.concat()[m.byte_range()]);
+    assert!(re.is_match_chunks(vec!["f", "", "n x"]));
+    assert!(!re.is_match_chunks(vec!["fn", " "]));
+
+    let re = RegExp::new_with_derivatives("é+").unwrap();
+    let m = re.find_chunks(vec!["aé", "éb"]).unwrap();
+    assert_eq!(1..3, m.range());
+    assert_eq!(1..5, m.byte_range());
+}
+
+#[test]
+fn test_read() {
+    let text = "π ≈ 3.14159, e ≈ 2.71828";
+    let re = RegExp::new_with_dfa(r"\d\.\d+").unwrap();
+    let m = re.find_read(Trickle(text.as_bytes())).unwrap().unwrap();
+    assert_eq!(4..11, m.range());
+    assert_eq!("3.14159", &text[m.byte_range()]);
+
+    let re = RegExp::new(r"[^,]*, e.*").unwrap();
+    assert!(re.is_match_read(Trickle(text.as_bytes())).unwrap());
+    assert!(re.is_match_read(&b""[..]).is_ok());
+
+    // Invalid UTF-8 and read errors are reported.
+    let err = re.find_read(&b"ab\xffc"[..]).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let err = re.is_match_read(&b"ab\xe2\x89"[..]).unwrap_err();
+    assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    let err = re.is_match_read(Failing(b"abc")).unwrap_err();
+    assert_eq!(io::ErrorKind::Other, err.kind());
+
+    // Errors past the end of the match aren't read.
+    let re = RegExp::new_with_dfa("b").unwrap();
+    let m = re.find_read(&b"abc\xff"[..]).unwrap();
+    assert_eq!(Some(1..2), m.map(|m| m.range()));
+}
+
+#[test]
+fn test_read_chars() {
+    let chars: Vec<char> = ReadChars::new(Trickle("aé€😀".as_bytes()))
+        .collect::<io::Result<_>>()
+        .unwrap();
+    assert_eq!(vec!['a', 'é', '€', '😀'], chars);
+
+    // Overlong encodings and surrogates are invalid.
+    for bytes in [&b"\xc0\x80"[..], &b"\xed\xa0\x80"[..], &b"\x80"[..]].iter() {
+        let mut chars = ReadChars::new(*bytes);
+        assert!(chars.next().unwrap().is_err());
+        assert!(chars.next().is_none());
+    }
+}
@@ -0,0 +1,109 @@
+use regexp2::{class::CharClass, RegExp};
+
+include!("macros.rs");
+
+#[test]
+fn test_general_category() {
+    let exprs = [
+        r"\p{Lu}",
+        r"\p{Uppercase_Letter}",
+        r"\p{gc=Lu}",
+        r"\p{uppercase letter}",
+    ];
+    let valids = ["A", "Z", "Ω", "Ж"];
+    let invalids = ["", "a", "ω", "1", "_", "AB"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\pL", r"\p{L}", r"\p{Letter}"];
+    let valids = ["a", "Z", "ω", "何", "ß"];
+    let invalids = ["", "1", "_", " ", "-"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\PL", r"\P{L}"];
+    let valids = ["1", "_", " ", "-"];
+    let invalids = ["", "a", "Z", "ω", "何"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\p{Nd}+"];
+    let valids = ["0", "123", "٣", "４２"];
+    let invalids = ["", "a", "1a", "Ⅳ"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_script() {
+    let exprs = [
+        r"\p{Greek}+",
+        r"\p{Grek}+",
+        r"\p{sc=Greek}+",
+        r"\p{Script=greek}+",
+    ];
+    let valids = ["α", "λόγος", "Ω"];
+    let invalids = ["", "a", "logos", "λa"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\P{Greek}"];
+    let valids = ["a", "1", "何"];
+    let invalids = ["", "α", "Ω"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_binary_property() {
+    let exprs = [r"\p{XID_Start}\p{XID_Continue}*", r"\p{XIDS}\p{XIDC}*"];
+    let valids = ["a", "abc", "a1", "a_b", "ñandú", "变量", "Ωmega"];
+    let invalids = ["", "1a", "_a", "a-b", "a b"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"\p{White_Space}+"];
+    let valids = [" ", "\t\n", "\u{3000}"];
+    let invalids = ["", "a", " a"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_in_char_class() {
+    let exprs = [r"[\p{Greek}\d]+", r"[0-9\p{Greek}]+"];
+    let valids = ["α", "1", "α1β2"];
+    let invalids = ["", "a", "α a"];
+    run_tests!(&exprs, &valids, &invalids);
+
+    let exprs = [r"[^\p{L}]"];
+    let valids = ["1", " ", "_"];
+    let invalids = ["", "a", "α"];
+    run_tests!(&exprs, &valids, &invalids);
+}
+
+#[test]
+fn test_invalid_property() {
+    let exprs = [
+        r"\p{Foo}",
+        r"\p{Greek",
+        r"\p",
+        r"\p{gc=Greek}",
+        r"\p{Foo=Lu}",
+        r"[\pQ]",
+    ];
+    exprs.iter().for_each(|&expr| {
+        RegExp::new(expr).unwrap_err();
+    });
+}
+
+#[test]
+fn test_constructors() {
+    let lu = CharClass::unicode_category("Lu").unwrap();
+    assert!(lu.contains('A'));
+    assert!(!lu.contains('a'));
+
+    let greek = CharClass::unicode_script("greek").unwrap();
+    assert!(greek.contains('λ'));
+    assert!(!greek.contains('l'));
+
+    let xid_start = CharClass::unicode_property("XID_Start").unwrap();
+    assert!(xid_start.contains('ñ'));
+    assert!(!xid_start.contains('_'));
+
+    assert!(CharClass::unicode_category("Greek").is_none());
+    assert!(CharClass::unicode_script("Lu").is_none());
+    assert!(CharClass::unicode_property("Nope").is_none());
+}
@@ -0,0 +1,7 @@
+use regexp2::{regex, CompiledRegExp};
+
+static WORD: &CompiledRegExp = regex!(r"\w+[z-a]");
+
+fn main() {
+    assert!(WORD.is_match("ab"));
+}
@@ -0,0 +1,7 @@
+use regexp2::{regex, CompiledRegExp};
+
+static GROUP: &CompiledRegExp = regex!("(a|b))*");
+
+fn main() {
+    assert!(GROUP.is_match("ab"));
+}